@@ -1,8 +1,25 @@
 #[cfg(target_os = "linux")]
 use {super::watch_linux::execute_watch, color_eyre::eyre::Context, tracing::debug};
 use {
-    super::{Cli, Command, GenerateArgs, ValidateArgs, tree::execute_tree},
-    crate::{Result, generator::Generator},
+    super::{
+        CheckCommandArgs,
+        CheckPathArgs,
+        Cli,
+        Command,
+        GenerateArgs,
+        ImportArgs,
+        LintArgs,
+        ReportCommand,
+        ValidateArgs,
+        refactor::execute_refactor,
+        tree::execute_tree,
+    },
+    crate::{
+        Result,
+        generator::{Generator, ImportStatus},
+        output::{DiffFormatArg, OutputFormat},
+    },
+    std::process::ExitCode,
 };
 
 #[cfg(not(target_os = "linux"))]
@@ -10,30 +27,129 @@ use super::watch_peasants::execute_watch;
 
 impl Cli {
     /// Execute the CLI command
-    pub async fn execute(&self, generator: &Generator) -> Result<()> {
+    pub async fn execute(&self, generator: &Generator) -> Result<ExitCode> {
         match &self.command {
-            Command::Validate(args) => self.execute_validate(generator, args).await,
-            Command::Generate(args) => self.execute_generate(generator, args).await,
-            Command::Diff(args) => generator.diff(args),
-            Command::Watch(args) => execute_watch(args).await,
-            Command::Tree(args) => execute_tree(generator, args),
-            _ => Ok(()),
+            Command::Validate(args) => self.execute_validate(generator, args).await?,
+            Command::Generate(args) => self.execute_generate(generator, args).await?,
+            Command::Diff(args) => {
+                if let Some(report) = generator.diff(args)?
+                    && args.check
+                    && !report.in_sync()
+                {
+                    return Ok(ExitCode::FAILURE);
+                }
+            }
+            Command::Status(_) => return self.execute_status(generator),
+            Command::Lint(args) => return self.execute_lint(generator, args),
+            Command::CheckCommand(args) => self.execute_check_command(generator, args)?,
+            Command::CheckPath(args) => self.execute_check_path(generator, args)?,
+            Command::Report(ReportCommand::Permissions(args)) => {
+                let report = crate::report::permissions(generator, &args.agents)?;
+                self.format_color()
+                    .permissions_report(&report, !args.agents.is_empty())?
+            }
+            Command::History(args) => {
+                let format = self.format_color();
+                let diff_format = match format {
+                    OutputFormat::Json => DiffFormatArg::Agent,
+                    _ => args.diff_format,
+                };
+                format.history(&generator.history(diff_format)?)?
+            }
+            Command::Rollback(args) => self
+                .format_color()
+                .rollback(&generator.rollback(args.to.as_deref()).await?)?,
+            Command::Watch(args) => execute_watch(args).await?,
+            Command::Tree(args) => execute_tree(generator, args)?,
+            Command::Import(args) => self.execute_import(generator, args).await?,
+            Command::Refactor(args) => execute_refactor(generator, args).await?,
+            _ => {}
+        }
+        Ok(ExitCode::SUCCESS)
+    }
+
+    /// Exit with failure when any agent file is out of sync
+    fn execute_status(&self, generator: &Generator) -> Result<ExitCode> {
+        let report = generator.status()?;
+        self.format_color().status(&report)?;
+        Ok(match report.in_sync() {
+            true => ExitCode::SUCCESS,
+            false => ExitCode::FAILURE,
+        })
+    }
+
+    /// Exit with failure when any error level finding is reported
+    fn execute_lint(&self, generator: &Generator, args: &LintArgs) -> Result<ExitCode> {
+        if args.rules {
+            self.format_color().lint_rules(&crate::lint::rules())?;
+            return Ok(ExitCode::SUCCESS);
         }
+        let report = crate::lint::lint(generator)?;
+        self.format_color().lint(&report)?;
+        Ok(match report.has_errors() {
+            true => ExitCode::FAILURE,
+            false => ExitCode::SUCCESS,
+        })
+    }
+
+    fn execute_check_command(&self, generator: &Generator, args: &CheckCommandArgs) -> Result<()> {
+        let check = generator.check_command(&args.agent, &args.command)?;
+        self.format_color().command_check(&check)
+    }
+
+    fn execute_check_path(&self, generator: &Generator, args: &CheckPathArgs) -> Result<()> {
+        let check = generator.check_path(&args.agent, args.tool, &args.path)?;
+        self.format_color().path_check(&check)
+    }
+
+    async fn execute_import(&self, generator: &Generator, args: &ImportArgs) -> Result<()> {
+        let results = generator.import(args).await?;
+        let mut imported = 0;
+        for r in &results {
+            match &r.status {
+                ImportStatus::Imported if args.dry_run => {
+                    println!("# {}", r.destination.display());
+                    println!("{}", r.toml);
+                    imported += 1;
+                }
+                ImportStatus::Imported => {
+                    println!(
+                        "✓ Imported {} -> {}",
+                        r.source.display(),
+                        r.destination.display()
+                    );
+                    imported += 1;
+                }
+                ImportStatus::Skipped(reason) => println!("- Skipped {}: {reason}", r.name),
+            }
+        }
+        println!(
+            "{} imported, {} skipped",
+            imported,
+            results.len() - imported
+        );
+        if imported > 0 && !args.dry_run {
+            println!("\nRun `kg diff` to confirm the generated agents are unchanged");
+        }
+        Ok(())
     }
 
     async fn execute_validate(&self, generator: &Generator, args: &ValidateArgs) -> Result<()> {
-        let results = generator.write_all(self.dry_run(), false).await?;
+        let results = generator.write_all(self.dry_run(), false, false).await?;
         self.format_color()
             .result(self.dry_run(), args.show_templates, results)
     }
 
     async fn execute_generate(&self, generator: &Generator, args: &GenerateArgs) -> Result<()> {
+        if args.check_secrets {
+            generator.check_secrets()?;
+        }
         if args.diff {
             generator.generate_diff()?;
         }
 
         let result = generator
-            .write_all(self.dry_run(), args.skip_unchanged)
+            .write_all(self.dry_run(), args.skip_unchanged, args.all_or_nothing)
             .await;
 
         #[cfg(target_os = "linux")]
@@ -42,7 +158,34 @@ impl Cli {
         }
 
         self.format_color()
-            .result(self.dry_run(), args.show_templates, result?)
+            .result(self.dry_run(), args.show_templates, result?)?;
+        self.report_orphans(generator, args.prune).await
+    }
+
+    /// List stale generated agent files, deleting them with `--prune`
+    async fn report_orphans(&self, generator: &Generator, prune: bool) -> Result<()> {
+        if prune {
+            for orphan in generator.prune().await? {
+                eprintln!("✓ Pruned {}", orphan.path.display());
+            }
+        }
+        let orphans = generator.orphans()?;
+        for orphan in &orphans {
+            let note = if orphan.modified {
+                " (edited since it was generated)"
+            } else {
+                ""
+            };
+            eprintln!(
+                "- Stale {}, agent '{}' no longer exists{note}",
+                orphan.path.display(),
+                orphan.name
+            );
+        }
+        if !prune && orphans.iter().any(|o| !o.modified) {
+            eprintln!("\nRun `kg generate --prune` to delete stale agent files");
+        }
+        Ok(())
     }
 
     #[cfg(target_os = "linux")]
@@ -73,6 +216,8 @@ impl Cli {
         Ok(())
     }
 }
+
+/// Print each snapshot, newest first, with what restoring it would change
 #[cfg(test)]
 mod tests {
     use {
@@ -1,4 +1,5 @@
 pub(crate) mod execute;
+mod refactor;
 mod runtime;
 mod tree;
 #[cfg(target_os = "linux")]
@@ -7,7 +8,7 @@ mod watch_linux;
 mod watch_peasants;
 
 use {
-    crate::output::{ColorOverride, OutputFormat, OutputFormatArg},
+    crate::output::{ColorOverride, OutputFormat, OutputFormatArg, ReportFormatArg},
     clap::{
         Parser,
         Subcommand,
@@ -90,13 +91,25 @@ pub struct GenerateArgs {
     /// Show template agents in output
     #[arg(long, default_value = "false")]
     pub show_templates: bool,
-    /// Skip writing agent files that appear unchanged (may miss updates due to
-    /// diff limitations)
+    /// Skip writing agent files that are unchanged
     #[arg(long, default_value = "false", env = "KG_SKIP_UNCHANGED")]
     pub skip_unchanged: bool,
     /// Show diff of changes before writing
     #[arg(long, default_value = "false", env = "KG_DIFF")]
     pub diff: bool,
+    /// Fail if an MCP env or headers value looks like a plaintext credential
+    /// instead of a secret reference
+    #[arg(long, default_value = "false", env = "KG_CHECK_SECRETS")]
+    pub check_secrets: bool,
+    /// Restore the agent files already written when writing another one fails
+    #[arg(long, default_value = "false", env = "KG_ALL_OR_NOTHING")]
+    pub all_or_nothing: bool,
+    /// Delete agent JSON files kg generated for agents which no longer exist
+    #[arg(long, default_value = "false", env = "KG_PRUNE")]
+    pub prune: bool,
+    /// Number of snapshots of replaced agent files to keep, see `kg history`
+    #[arg(long, value_name = "N", env = "KG_HISTORY_KEEP", value_parser = clap::value_parser!(u64).range(1..))]
+    pub keep: Option<u64>,
     /// Format of the console output
     #[arg(short = 'f', long,  default_value_t = OutputFormatArg::default(), env = "KG_FORMAT")]
     pub format: OutputFormatArg,
@@ -120,11 +133,169 @@ pub struct DiffArgs {
     #[arg(short = 'f', long, default_value_t, env = "KG_DIFF_FORMAT")]
     pub format: crate::output::DiffFormatArg,
 
+    /// Print a JSON report and exit with status 1 when an agent is new,
+    /// changed or not found (for CI)
+    #[arg(long, default_value = "false", env = "KG_DIFF_CHECK")]
+    pub check: bool,
+
     /// Only show diff for these agents
     #[arg(value_name = "AGENT")]
     pub agents: Vec<String>,
 }
 
+#[derive(clap::Args, Clone, Default)]
+pub struct StatusArgs {
+    /// Use only global configuration (ignore local .kiro/generators/)
+    #[arg(short = 'g', long)]
+    pub global: bool,
+    /// Format of the console output
+    #[arg(short = 'f', long,  default_value_t = OutputFormatArg::default(), env = "KG_FORMAT")]
+    pub format: OutputFormatArg,
+}
+
+#[derive(clap::Args, Clone, Default)]
+pub struct LintArgs {
+    /// Use only global configuration (ignore local .kiro/generators/)
+    #[arg(short = 'g', long)]
+    pub global: bool,
+    /// Format of the console output
+    #[arg(short = 'f', long,  default_value_t = OutputFormatArg::default(), env = "KG_FORMAT")]
+    pub format: OutputFormatArg,
+    /// List the lint rules instead of checking the agents
+    #[arg(long)]
+    pub rules: bool,
+}
+
+#[derive(clap::Args, Clone, Default)]
+pub struct CheckCommandArgs {
+    /// Agent whose merged shell settings are used
+    #[arg(value_name = "AGENT")]
+    pub agent: String,
+    /// Command line to check, quoted as a single argument
+    #[arg(value_name = "COMMAND")]
+    pub command: String,
+    /// Use only global configuration (ignore local .kiro/generators/)
+    #[arg(short = 'g', long)]
+    pub global: bool,
+    /// Format of the console output
+    #[arg(short = 'f', long,  default_value_t = OutputFormatArg::default(), env = "KG_FORMAT")]
+    pub format: OutputFormatArg,
+}
+
+#[derive(clap::Args, Clone, Default)]
+pub struct CheckPathArgs {
+    /// Agent whose merged path settings are used
+    #[arg(value_name = "AGENT")]
+    pub agent: String,
+    /// Path to check, `~` and relative paths are resolved against the
+    /// current directory
+    #[arg(value_name = "PATH")]
+    pub path: String,
+    /// Native tool whose allow and deny lists are checked
+    #[arg(long, short = 't', default_value_t)]
+    pub tool: crate::generator::PathTool,
+    /// Use only global configuration (ignore local .kiro/generators/)
+    #[arg(short = 'g', long)]
+    pub global: bool,
+    /// Format of the console output
+    #[arg(short = 'f', long,  default_value_t = OutputFormatArg::default(), env = "KG_FORMAT")]
+    pub format: OutputFormatArg,
+}
+
+#[derive(clap::Args, Clone, Default, Debug)]
+pub struct ReportPermissionsArgs {
+    /// Show every setting of these agents instead of the matrix
+    #[arg(value_name = "AGENT")]
+    pub agents: Vec<String>,
+    /// Use only global configuration (ignore local .kiro/generators/)
+    #[arg(short = 'g', long)]
+    pub global: bool,
+    /// Format of the console output
+    #[arg(short = 'f', long, default_value_t = ReportFormatArg::default(), env = "KG_FORMAT")]
+    pub format: ReportFormatArg,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum ReportCommand {
+    /// Matrix of agents against the native tools, MCP servers and tools
+    /// they may use, with forceAllow applied
+    #[command(alias = "p")]
+    Permissions(ReportPermissionsArgs),
+}
+
+#[derive(clap::Args, Clone, Default)]
+pub struct HistoryArgs {
+    /// Use only global configuration (ignore local .kiro/generators/)
+    #[arg(short = 'g', long)]
+    pub global: bool,
+    /// Output format of the diffs, plain with `--format json`
+    #[arg(long, default_value_t, env = "KG_DIFF_FORMAT")]
+    pub diff_format: crate::output::DiffFormatArg,
+    /// Format of the console output
+    #[arg(short = 'f', long, default_value_t = OutputFormatArg::default(), env = "KG_FORMAT")]
+    pub format: OutputFormatArg,
+}
+
+#[derive(clap::Args, Clone, Default)]
+pub struct RollbackArgs {
+    /// Timestamp of the snapshot to restore, see `kg history`. Defaults to
+    /// the newest snapshot
+    #[arg(long, value_name = "TIMESTAMP")]
+    pub to: Option<String>,
+    /// Number of snapshots of replaced agent files to keep, see `kg history`
+    #[arg(long, value_name = "N", env = "KG_HISTORY_KEEP", value_parser = clap::value_parser!(u64).range(1..))]
+    pub keep: Option<u64>,
+    /// Use only global configuration (ignore local .kiro/generators/)
+    #[arg(short = 'g', long)]
+    pub global: bool,
+    /// Format of the console output
+    #[arg(short = 'f', long, default_value_t = OutputFormatArg::default(), env = "KG_FORMAT")]
+    pub format: OutputFormatArg,
+}
+
+#[derive(clap::Args, Clone, Default, Debug)]
+pub struct ImportArgs {
+    /// Import ~/.kiro/agents into ~/.kiro/generators instead of the local
+    /// .kiro directory
+    #[arg(short = 'g', long)]
+    pub global: bool,
+    /// Overwrite agents which are already managed by kg
+    #[arg(long, default_value = "false")]
+    pub force: bool,
+    /// Print the generated TOML without writing any files
+    #[arg(long, default_value = "false")]
+    pub dry_run: bool,
+    /// Import agents without tools, which kg generates with all tools ("*")
+    #[arg(long, default_value = "false")]
+    pub allow_all_tools: bool,
+    /// Only import these agents
+    #[arg(value_name = "AGENT")]
+    pub agents: Vec<String>,
+}
+
+#[derive(clap::Args, Clone, Default, Debug)]
+pub struct ExtractTemplatesArgs {
+    /// Name of the template agent to create
+    #[arg(short = 'n', long, default_value = "shared")]
+    pub name: String,
+    /// Write the template and update the agents in place
+    #[arg(long, default_value = "false")]
+    pub write: bool,
+    /// Use agents in ~/.kiro/generators instead of the local .kiro directory
+    #[arg(short = 'g', long)]
+    pub global: bool,
+    /// Only consider these agents (defaults to every agent in scope)
+    #[arg(value_name = "AGENT")]
+    pub agents: Vec<String>,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum RefactorCommand {
+    /// Move configuration shared by several agents into a new template
+    #[command(alias = "x")]
+    ExtractTemplates(ExtractTemplatesArgs),
+}
+
 #[derive(clap::Args, Clone)]
 pub struct WatchArgs {
     /// Disable the watcher instead of enabling it
@@ -165,6 +336,18 @@ pub struct TreeDependentsArgs {
     pub agents: Vec<String>,
 }
 
+#[derive(clap::Args, Clone, Default)]
+pub struct TreeExplainArgs {
+    /// Agent to explain
+    pub agent: String,
+    /// TOML path of the field, for example nativeTools.shell.allow, tools,
+    /// mcpServers.<name>.args, mcpServers or description
+    pub field: String,
+    /// Format of the console output
+    #[arg(short = 'f', long, default_value_t = TreeFormatArg::Table)]
+    pub format: TreeFormatArg,
+}
+
 #[derive(clap::Args, Clone, Default)]
 pub struct TreeSearchArgs {
     /// Scope search to a specific field or field prefix
@@ -205,6 +388,9 @@ pub enum TreeCommand {
     Dependents(TreeDependentsArgs),
     #[command(aliases = ["find", "f", "grep", "g"])]
     Search(TreeSearchArgs),
+    /// Show where every value of a field came from
+    #[command(aliases = ["why", "e"])]
+    Explain(TreeExplainArgs),
 }
 
 impl Debug for TreeCommand {
@@ -214,6 +400,7 @@ impl Debug for TreeCommand {
             Self::Details(_) => write!(f, "details"),
             Self::Dependents(_) => write!(f, "dependents"),
             Self::Search(_) => write!(f, "search"),
+            Self::Explain(_) => write!(f, "explain"),
         }
     }
 }
@@ -237,6 +424,30 @@ pub enum Command {
     Version,
     /// Compare generator files with Kiro agent files
     Diff(DiffArgs),
+    /// Show which agent files are out of sync with the generator files. Exits
+    /// with status 1 when anything needs `kg generate`
+    #[command(alias = "s")]
+    Status(StatusArgs),
+    /// Check the merged agents for risky permissions and unused
+    /// configuration. Exits with status 1 when an error is found
+    Lint(LintArgs),
+    /// Show whether an agent may run a shell command, and which pattern from
+    /// which ancestor decides it
+    #[allow(clippy::enum_variant_names)]
+    CheckCommand(CheckCommandArgs),
+    /// Show whether an agent's read, write, glob or grep tool may access a
+    /// path, and which pattern from which ancestor decides it
+    CheckPath(CheckPathArgs),
+    /// Audit the effective permissions of the merged agents
+    #[command(subcommand)]
+    Report(ReportCommand),
+    /// List the snapshots of agent files replaced by `kg generate` and what
+    /// restoring each one would change
+    History(HistoryArgs),
+    /// Restore the agent files of a snapshot taken by `kg generate`
+    Rollback(RollbackArgs),
+    /// Convert existing Kiro agent JSON files into kg TOML
+    Import(ImportArgs),
     /// Output JSON schema for configuration files
     #[command(subcommand)]
     Schema(SchemaCommand),
@@ -247,6 +458,9 @@ pub enum Command {
     /// Display agent hierarchy and configuration sources as a tree
     #[command(subcommand, alias = "t")]
     Tree(TreeCommand),
+    /// Restructure agent configuration without changing the generated agents
+    #[command(subcommand)]
+    Refactor(RefactorCommand),
 }
 
 #[derive(clap::Args, Clone, Default)]
@@ -288,6 +502,21 @@ impl Cli {
         let format = match &self.command {
             Command::Validate(a) => &a.format,
             Command::Generate(a) => &a.format,
+            Command::Status(a) => &a.format,
+            Command::Lint(a) => &a.format,
+            Command::CheckCommand(a) => &a.format,
+            Command::CheckPath(a) => &a.format,
+            Command::History(a) => &a.format,
+            Command::Rollback(a) => &a.format,
+            Command::Report(ReportCommand::Permissions(a)) => {
+                return match a.format {
+                    ReportFormatArg::Table => OutputFormat::Table(self.color()),
+                    ReportFormatArg::Json => OutputFormat::Json,
+                    ReportFormatArg::Plain => OutputFormat::Plain,
+                    ReportFormatArg::Csv => OutputFormat::Csv,
+                    ReportFormatArg::Markdown => OutputFormat::Markdown,
+                };
+            }
             _ => return OutputFormat::Table(self.color()),
         };
 
@@ -310,6 +539,16 @@ impl Cli {
         }
     }
 
+    /// Snapshots `kg generate` and `kg rollback` keep
+    pub fn history_limit(&self) -> usize {
+        let keep = match &self.command {
+            Command::Generate(args) => args.keep,
+            Command::Rollback(args) => args.keep,
+            _ => None,
+        };
+        keep.map_or(crate::generator::HISTORY_LIMIT, |keep| keep as usize)
+    }
+
     pub(super) fn dry_run(&self) -> bool {
         matches!(self.command, Command::Validate(_))
     }
@@ -318,6 +557,7 @@ impl Cli {
         match &self.command {
             Command::Generate(args) => args.local,
             Command::Validate(args) => args.local,
+            Command::Import(args) => !args.global,
             _ => false,
         }
     }
@@ -327,6 +567,15 @@ impl Cli {
             Command::Generate(args) => args.global,
             Command::Validate(args) => args.global,
             Command::Diff(args) => args.global,
+            Command::Status(args) => args.global,
+            Command::Lint(args) => args.global,
+            Command::CheckCommand(args) => args.global,
+            Command::CheckPath(args) => args.global,
+            Command::Report(ReportCommand::Permissions(args)) => args.global,
+            Command::History(args) => args.global,
+            Command::Rollback(args) => args.global,
+            Command::Import(args) => args.global,
+            Command::Refactor(RefactorCommand::ExtractTemplates(args)) => args.global,
             _ => false,
         }
     }
@@ -428,6 +677,15 @@ mod tests {
             ..cli
         };
         assert!(matches!(cli.format_color(), OutputFormat::Json));
+
+        let cli = Cli {
+            command: Command::Report(ReportCommand::Permissions(ReportPermissionsArgs {
+                format: ReportFormatArg::Markdown,
+                ..Default::default()
+            })),
+            ..cli
+        };
+        assert!(matches!(cli.format_color(), OutputFormat::Markdown));
     }
 
     #[test_log::test]
@@ -475,6 +733,31 @@ mod tests {
         assert!(!cli.is_global());
     }
 
+    #[test_log::test]
+    fn test_history_format() {
+        let cli = Cli::try_parse_from(["kg", "history", "-f", "json"]).unwrap();
+        assert!(matches!(cli.format_color(), OutputFormat::Json));
+        let cli = Cli::try_parse_from(["kg", "rollback", "--format", "plain"]).unwrap();
+        assert!(matches!(cli.format_color(), OutputFormat::Plain));
+    }
+
+    #[test_log::test]
+    fn test_history_keep() {
+        let cli = Cli::try_parse_from(["kg", "generate", "--keep", "3"]).unwrap();
+        assert_eq!(cli.history_limit(), 3);
+        let cli = Cli::try_parse_from(["kg", "rollback"]).unwrap();
+        assert_eq!(cli.history_limit(), crate::generator::HISTORY_LIMIT);
+        assert!(Cli::try_parse_from(["kg", "generate", "--keep", "0"]).is_err());
+    }
+
+    #[test_log::test]
+    fn test_report_only_formats() {
+        let cli = Cli::try_parse_from(["kg", "report", "permissions", "-f", "csv"]).unwrap();
+        assert!(matches!(cli.format_color(), OutputFormat::Csv));
+        assert!(Cli::try_parse_from(["kg", "validate", "-f", "csv"]).is_err());
+        assert!(Cli::try_parse_from(["kg", "generate", "--format", "markdown"]).is_err());
+    }
+
     #[test_log::test]
     fn test_tree_summary_locations_flag() {
         let cli = Cli::try_parse_from(["kg", "tree", "summary", "--locations"]).unwrap();
@@ -511,6 +794,50 @@ mod tests {
         }
     }
 
+    #[test_log::test]
+    fn test_import_args() {
+        let cli = Cli::try_parse_from(["kg", "import", "--dry-run", "rust"]).unwrap();
+        assert!(cli.is_local());
+        assert!(!cli.is_global());
+        match cli.command {
+            Command::Import(args) => {
+                assert!(args.dry_run);
+                assert!(!args.force);
+                assert_eq!(args.agents, vec![String::from("rust")]);
+            }
+            _ => panic!("expected import command"),
+        }
+
+        let cli = Cli::try_parse_from(["kg", "import", "-g"]).unwrap();
+        assert!(cli.is_global());
+        assert!(!cli.is_local());
+    }
+
+    #[test_log::test]
+    fn test_refactor_extract_templates_args() {
+        let cli = Cli::try_parse_from(["kg", "refactor", "extract-templates", "a", "b"]).unwrap();
+        assert!(!cli.is_global());
+        match cli.command {
+            Command::Refactor(RefactorCommand::ExtractTemplates(args)) => {
+                assert_eq!(args.name, "shared");
+                assert!(!args.write);
+                assert_eq!(args.agents.len(), 2);
+            }
+            _ => panic!("expected refactor command"),
+        }
+
+        let cli =
+            Cli::try_parse_from(["kg", "refactor", "x", "-g", "--write", "-n", "common"]).unwrap();
+        assert!(cli.is_global());
+        match cli.command {
+            Command::Refactor(RefactorCommand::ExtractTemplates(args)) => {
+                assert_eq!(args.name, "common");
+                assert!(args.write);
+            }
+            _ => panic!("expected refactor command"),
+        }
+    }
+
     #[test_log::test]
     fn test_diff_agents_args() {
         let cli = Cli::try_parse_from(["kg", "diff", "-f", "agent", "rust", "node"]).unwrap();
@@ -0,0 +1,43 @@
+use crate::{
+    Result,
+    commands::{ExtractTemplatesArgs, RefactorCommand},
+    generator::Generator,
+};
+
+#[tracing::instrument(level = "info", skip(generator))]
+pub(super) async fn execute_refactor(generator: &Generator, cmd: &RefactorCommand) -> Result<()> {
+    match cmd {
+        RefactorCommand::ExtractTemplates(args) => extract_templates(generator, args).await,
+    }
+}
+
+async fn extract_templates(generator: &Generator, args: &ExtractTemplatesArgs) -> Result<()> {
+    let Some(proposal) = generator.extract_templates(args).await? else {
+        println!("No shared configuration found");
+        return Ok(());
+    };
+
+    println!("# {}", proposal.template_file.display());
+    println!("{}", proposal.toml);
+    for edit in &proposal.edits {
+        println!(
+            "{} inherits '{}', {} entries removed",
+            edit.agent, proposal.template.name, edit.removed
+        );
+    }
+    println!(
+        "\n✓ {} agents generate the same Kiro agents",
+        proposal.verified
+    );
+
+    if args.write {
+        println!("\nUpdated:");
+        println!("  {}", proposal.template_file.display());
+        for file in &proposal.files {
+            println!("  {}", file.display());
+        }
+    } else {
+        println!("\nRun again with --write to apply");
+    }
+    Ok(())
+}
@@ -5,15 +5,18 @@ use {
             TreeCommand,
             TreeDependentsArgs,
             TreeDetailArgs,
+            TreeExplainArgs,
             TreeFormatArg,
             TreeSearchArgs,
             TreeSummaryArgs,
         },
         generator::Generator,
         tree::{
+            ExplainReport,
             SearchReport,
             SummaryEntry,
             SummaryReport,
+            explain,
             search,
             summarize_concrete,
             summarize_templates,
@@ -30,6 +33,7 @@ pub(super) fn execute_tree(generator: &Generator, cmd: &TreeCommand) -> Result<(
         TreeCommand::Details(args) => details(generator, args),
         TreeCommand::Dependents(args) => dependencies(generator, args),
         TreeCommand::Search(args) => search_tree(generator, args),
+        TreeCommand::Explain(args) => explain_field(generator, args),
     }
 }
 
@@ -180,6 +184,45 @@ pub fn search_tree(generator: &Generator, args: &TreeSearchArgs) -> Result<()> {
     Ok(())
 }
 
+fn explain_field(generator: &Generator, args: &TreeExplainArgs) -> Result<()> {
+    let report = explain(generator, &args.agent, &args.field)?;
+    match args.format {
+        TreeFormatArg::Json => println!("{}", facet_json::to_string_pretty(&report)?),
+        TreeFormatArg::Table => println!("{}", build_explain_table(&report)),
+    };
+    Ok(())
+}
+
+fn build_explain_table(report: &ExplainReport) -> Table {
+    let mut table = build_titled_table(&format!("{} {}", report.agent, report.field), 4);
+    table.add_row(vec![
+        Cell::new("Value"),
+        Cell::new("Status"),
+        Cell::new("Agent"),
+        Cell::new("Source"),
+    ]);
+    let rows: Vec<Row> = report
+        .entries
+        .iter()
+        .map(|e| {
+            let status = match &e.by {
+                Some(by) => format!("{} by {by}", e.status),
+                None => e.status.to_string(),
+            };
+            vec![
+                Cell::new(e.value.clone()),
+                Cell::new(status),
+                Cell::new(e.agent.clone()),
+                Cell::new(format!("{}://{}", e.source_type, e.path)),
+            ]
+            .into()
+        })
+        .collect();
+    table.add_rows(rows);
+
+    table
+}
+
 #[cfg(test)]
 mod tests {
     use {
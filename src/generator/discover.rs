@@ -37,7 +37,10 @@ fn load_manifests(
         let _span = tracing::info_span!("parse_manifest", path = %path.display()).entered();
         if let Some(config_result) = crate::toml_parse_path(fs, &path) {
             let config: GeneratorConfig = config_result?;
-            let config = config.populate_names();
+            let mut config = config.populate_names();
+            for agent in config.agents.values_mut() {
+                agent.load_prompt(fs, &path)?;
+            }
             // Check for duplicate agent names
             for name in config.agents.keys() {
                 if let Some(a) = merged.get(name) {
@@ -81,7 +84,7 @@ pub fn load_inline(fs: &Fs, path: impl AsRef<Path>) -> crate::Result<GeneratorCo
     }
 }
 
-fn merge_manifests(
+pub(super) fn merge_manifests(
     name: &str,
     global_manifest: &SourceSlot,
     local_manifest: &SourceSlot,
@@ -376,6 +379,33 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_load_sources_inherits_order() -> Result<()> {
+        let fs = Fs::new();
+        let g_path = PathBuf::from(ACTIVE_USER_HOME)
+            .join(".kiro")
+            .join("generators");
+        fs.write(
+            g_path.join("manifests").join("slots.toml"),
+            "[agents.slots]\ninherits = [\"base\", \"strict\"]\n",
+        )
+        .await?;
+        fs.write(
+            ".kiro/generators/manifests/slots.toml",
+            "[agents.slots]\ninherits = [\"strict\", \"local\"]\n",
+        )
+        .await?;
+        let sources = load_sources(&fs, &ConfigLocation::Both(g_path))?;
+        let slots = sources
+            .iter()
+            .find(|slot| slot.name == "slots")
+            .ok_or_else(|| eyre!("missing slots manifest slots"))?;
+        // The global parents come first so the local ones win
+        assert_eq!(slots.merged.inherits, vec!["base", "strict", "local"]);
+        Ok(())
+    }
+
     #[tokio::test]
     #[test_log::test]
     async fn test_template_not_inherited() -> Result<()> {
@@ -416,10 +446,12 @@ mod tests {
             agents,
             fs,
             format: crate::output::OutputFormat::Plain,
+            policies: Vec::new(),
+            history_limit: super::HISTORY_LIMIT,
         };
 
         let templates = generator
-            .write_all(true, false)
+            .write_all(true, false, false)
             .await?
             .iter()
             .filter(|a| a.is_template())
@@ -0,0 +1,346 @@
+use {
+    super::*,
+    std::{
+        path::Path,
+        time::{SystemTime, UNIX_EPOCH},
+    },
+};
+
+/// Directory in the generators directory holding the agent files replaced by
+/// `kg generate`
+pub const HISTORY_DIR: &str = ".history";
+/// Number of snapshots kept by default, older ones are deleted
+pub const HISTORY_LIMIT: usize = 10;
+
+/// Agent files replaced by one `kg generate` or `kg rollback`
+#[derive(Facet, Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// UTC time the snapshot was taken, `20261018T093000Z`
+    pub timestamp: String,
+    /// Directory holding the saved agent files
+    pub path: String,
+    /// Names of the agents in the snapshot, sorted
+    pub agents: Vec<String>,
+}
+
+/// What restoring a snapshot would change in one current agent file
+#[derive(Facet, Debug, Clone)]
+pub struct SnapshotChange {
+    pub agent: String,
+    /// `new` when the agent file was deleted since
+    pub state: SyncState,
+    /// Diff between the current file and the snapshot, when changed
+    pub diff: Option<String>,
+}
+
+/// A snapshot and what restoring it would change, listed by `kg history`
+#[derive(Facet, Debug, Clone)]
+pub struct SnapshotHistory {
+    pub snapshot: Snapshot,
+    pub changes: Vec<SnapshotChange>,
+}
+
+/// UTC timestamp which sorts in time order, `YYYYMMDDTHHMMSSZ`
+fn timestamp(now: SystemTime) -> String {
+    let secs = now
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since the epoch, see
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+impl Generator {
+    /// Keep `limit` snapshots instead of [`HISTORY_LIMIT`]
+    pub fn with_history_limit(mut self, limit: usize) -> Self {
+        self.history_limit = limit;
+        self
+    }
+
+    /// History of the agents `kg generate` writes for the current
+    /// configuration
+    fn history_dir(&self) -> PathBuf {
+        match self.contains_local_agents() {
+            true => PathBuf::from(".kiro").join("generators").join(HISTORY_DIR),
+            false => self.global_path.join(HISTORY_DIR),
+        }
+    }
+
+    /// Save the current content of agent files about to be replaced and
+    /// delete the snapshots beyond the history limit, [`HISTORY_LIMIT`]
+    /// unless set with [`Generator::with_history_limit`]
+    pub(super) async fn snapshot(&self, files: &[(&Path, &str)]) -> Result<Option<Snapshot>> {
+        if files.is_empty() {
+            return Ok(None);
+        }
+        let history = self.history_dir();
+        let stamp = timestamp(SystemTime::now());
+        let mut timestamp = stamp.clone();
+        let mut n = 0;
+        while self.fs.exists(history.join(&timestamp)) {
+            n += 1;
+            timestamp = format!("{stamp}-{n}");
+        }
+        let path = history.join(&timestamp);
+        self.fs
+            .create_dir_all(&path)
+            .await
+            .wrap_err_with(|| format!("failed to create directory {}", path.display()))?;
+        // Snapshots hold resolved secrets, keep them out of version control
+        self.fs.write(history.join(".gitignore"), "*\n").await?;
+
+        let mut agents = Vec::with_capacity(files.len());
+        for (file, content) in files {
+            let Some(name) = file.file_name() else {
+                continue;
+            };
+            self.fs
+                .write(path.join(name), content)
+                .await
+                .wrap_err_with(|| format!("failed to save {} in history", file.display()))?;
+            if let Some(stem) = file.file_stem() {
+                agents.push(stem.to_string_lossy().to_string());
+            }
+        }
+        agents.sort();
+
+        for old in self.snapshots()?.into_iter().skip(self.history_limit) {
+            self.fs.remove_dir_all(&old.path).await?;
+        }
+        Ok(Some(Snapshot {
+            timestamp,
+            path: path.display().to_string(),
+            agents,
+        }))
+    }
+
+    /// Saved snapshots, newest first
+    pub fn snapshots(&self) -> Result<Vec<Snapshot>> {
+        let history = self.history_dir();
+        if !self.fs.exists(&history) {
+            return Ok(Vec::new());
+        }
+        let mut snapshots = Vec::new();
+        for entry in self.fs.read_dir_sync(&history)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let timestamp = entry.file_name().to_string_lossy().to_string();
+            let path = history.join(&timestamp);
+            let mut agents = Vec::new();
+            for file in self.fs.read_dir_sync(&path)? {
+                let file = PathBuf::from(file?.file_name());
+                if file.extension().and_then(|s| s.to_str()) == Some("json")
+                    && let Some(stem) = file.file_stem()
+                {
+                    agents.push(stem.to_string_lossy().to_string());
+                }
+            }
+            agents.sort();
+            snapshots.push(Snapshot {
+                timestamp,
+                path: path.display().to_string(),
+                agents,
+            });
+        }
+        snapshots.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        Ok(snapshots)
+    }
+
+    /// What restoring `snapshot` would change in each current agent file
+    pub fn snapshot_diff(
+        &self,
+        snapshot: &Snapshot,
+        format: crate::output::DiffFormatArg,
+    ) -> Result<Vec<(String, AgentDiff)>> {
+        let mut diffs = Vec::with_capacity(snapshot.agents.len());
+        for name in &snapshot.agents {
+            let file = Path::new(&snapshot.path).join(format!("{name}.json"));
+            let content = self.fs.read_to_string_sync(&file)?;
+            let saved = facet_json::from_str::<KiroAgent>(&content)
+                .wrap_err_with(|| format!("Failed to parse {}", file.display()))?;
+            diffs.push((name.clone(), self.compute_diff(name, &saved, format)?));
+        }
+        Ok(diffs)
+    }
+
+    /// Saved snapshots, newest first, with what restoring each would change
+    pub fn history(&self, format: crate::output::DiffFormatArg) -> Result<Vec<SnapshotHistory>> {
+        let mut history = Vec::new();
+        for snapshot in self.snapshots()? {
+            let changes = self
+                .snapshot_diff(&snapshot, format)?
+                .into_iter()
+                .map(|(agent, diff)| {
+                    let (state, diff) = match diff {
+                        AgentDiff::Same => (SyncState::Same, None),
+                        AgentDiff::New => (SyncState::New, None),
+                        AgentDiff::Changed(diff) => (SyncState::Changed, Some(diff)),
+                    };
+                    SnapshotChange { agent, state, diff }
+                })
+                .collect();
+            history.push(SnapshotHistory { snapshot, changes });
+        }
+        Ok(history)
+    }
+
+    /// Restore the agent files of the snapshot `to`, or of the newest one.
+    /// The files it replaces are saved as a new snapshot first.
+    pub async fn rollback(&self, to: Option<&str>) -> Result<Snapshot> {
+        let snapshots = self.snapshots()?;
+        if snapshots.is_empty() {
+            crate::bail!("No snapshots in {}", self.history_dir().display());
+        }
+        let snapshot = match to {
+            None => snapshots.first(),
+            Some(to) => snapshots.iter().find(|s| s.timestamp == to),
+        };
+        let Some(snapshot) = snapshot.cloned() else {
+            let available: Vec<&str> = snapshots.iter().map(|s| s.timestamp.as_str()).collect();
+            crate::bail!(
+                "Snapshot '{}' not found, available: {}",
+                to.unwrap_or_default(),
+                available.join(", ")
+            );
+        };
+
+        let dir = self.managed_dir();
+        let mut restore = Vec::with_capacity(snapshot.agents.len());
+        for name in &snapshot.agents {
+            let saved = self
+                .fs
+                .read_to_string_sync(Path::new(&snapshot.path).join(format!("{name}.json")))?;
+            restore.push((dir.join(format!("{name}.json")), saved));
+        }
+        let mut current = Vec::new();
+        for (out, saved) in &restore {
+            if self.fs.exists(out) {
+                let content = self.fs.read_to_string_sync(out)?;
+                if content != *saved {
+                    current.push((out.clone(), content));
+                }
+            }
+        }
+        let current: Vec<(&Path, &str)> = current
+            .iter()
+            .map(|(out, content)| (out.as_path(), content.as_str()))
+            .collect();
+        self.snapshot(&current).await?;
+
+        for (out, saved) in &restore {
+            self.write_atomic(out, saved).await?;
+        }
+        let names: Vec<&str> = snapshot.agents.iter().map(String::as_str).collect();
+        self.record_files(&dir, &names).await?;
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::output::OutputFormat, std::time::Duration};
+
+    #[test]
+    fn test_timestamp() {
+        assert_eq!(timestamp(UNIX_EPOCH), "19700101T000000Z");
+        assert_eq!(
+            timestamp(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            "20000229T000000Z"
+        );
+        assert_eq!(
+            timestamp(UNIX_EPOCH + Duration::from_secs(1_792_000_000)),
+            "20261014T174640Z"
+        );
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_history_limit() -> Result<()> {
+        let fs = Fs::new();
+        let manifest = ".kiro/generators/manifests/history.toml";
+        for description in ["one", "two", "three", "four"] {
+            fs.write(
+                manifest,
+                format!("[agents.historic]\ndescription = \"{description}\"\n"),
+            )
+            .await?;
+            Generator::new(fs.clone(), ConfigLocation::Local, OutputFormat::Json)?
+                .with_history_limit(2)
+                .write_all(false, false, false)
+                .await?;
+        }
+        let generator = Generator::new(fs, ConfigLocation::Local, OutputFormat::Json)?;
+        assert_eq!(generator.snapshots()?.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_generate_snapshots_and_rollback() -> Result<()> {
+        let fs = Fs::new();
+        let manifest = ".kiro/generators/manifests/history.toml";
+        let with_description =
+            |description: &str| format!("[agents.historic]\ndescription = \"{description}\"\n");
+        fs.write(manifest, with_description("one")).await?;
+        let generator = Generator::new(fs.clone(), ConfigLocation::Local, OutputFormat::Json)?;
+        generator.write_all(false, false, false).await?;
+        let out = PathBuf::from(".kiro/agents/historic.json");
+        let first = fs.read_to_string_sync(&out)?;
+
+        fs.write(manifest, with_description("two")).await?;
+        let generator = Generator::new(fs.clone(), ConfigLocation::Local, OutputFormat::Json)?;
+        generator.write_all(false, false, false).await?;
+        let snapshots = generator.snapshots()?;
+        let snapshot = snapshots
+            .iter()
+            .find(|s| s.agents.contains(&String::from("historic")))
+            .expect("historic not saved");
+        assert_eq!(
+            fs.read_to_string_sync(Path::new(&snapshot.path).join("historic.json"))?,
+            first
+        );
+        assert!(fs.exists(".kiro/generators/.history/.gitignore"));
+
+        let diffs = generator.snapshot_diff(snapshot, crate::output::DiffFormatArg::Agent)?;
+        let (_, diff) = diffs
+            .iter()
+            .find(|(name, _)| name == "historic")
+            .expect("historic diff");
+        assert!(diff.to_string().contains("description"), "{diff}");
+        let history = generator.history(crate::output::DiffFormatArg::Agent)?;
+        let json = facet_json::to_string(&history)?;
+        assert!(
+            json.contains("\"agent\":\"historic\",\"state\":\"changed\""),
+            "{json}"
+        );
+
+        let restored = generator.rollback(Some(&snapshot.timestamp)).await?;
+        assert_eq!(restored.timestamp, snapshot.timestamp);
+        assert_eq!(fs.read_to_string_sync(&out)?, first);
+        // The replaced file is saved, so the rollback can be undone
+        assert_eq!(generator.snapshots()?.len(), snapshots.len() + 1);
+        assert!(generator.orphans()?.is_empty());
+
+        let err = generator.rollback(Some("19700101T000000Z")).await;
+        assert!(err.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,317 @@
+use {
+    super::*,
+    crate::{KgAgentFileDoc, commands::ImportArgs, toml_key, toml_to_string},
+    color_eyre::eyre::bail,
+    std::path::Path,
+};
+
+const MANIFEST_SCHEMA_URL: &str = "https://kiro-generator.io/manifest.json";
+/// kg-helper.json is installed by `kg init` and is not a user agent
+pub(super) const KG_HELPER_AGENT: &str = "kg-helper";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportStatus {
+    Imported,
+    Skipped(String),
+}
+
+pub struct ImportResult {
+    pub name: String,
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    pub status: ImportStatus,
+    pub toml: String,
+}
+
+impl Generator {
+    /// Convert Kiro agent JSON files into kg agent TOML files and declare
+    /// them in `manifests/kg.toml`
+    #[tracing::instrument(level = "info", skip(self))]
+    pub async fn import(&self, args: &ImportArgs) -> Result<Vec<ImportResult>> {
+        let generators_dir = if args.global {
+            self.global_path.clone()
+        } else {
+            PathBuf::from(".kiro").join("generators")
+        };
+        let source_dir = generators_dir
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default()
+            .join("agents");
+        if !self.fs.exists(&source_dir) {
+            bail!(
+                "No Kiro agents found, {} does not exist",
+                source_dir.display()
+            );
+        }
+
+        let mut sources: Vec<(String, PathBuf)> = Vec::new();
+        for entry in self.fs.read_dir_sync(&source_dir)? {
+            let path = source_dir.join(entry?.file_name());
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if name == KG_HELPER_AGENT
+                || (!args.agents.is_empty() && !args.agents.iter().any(|a| a == name))
+            {
+                continue;
+            }
+            sources.push((name.to_string(), path));
+        }
+        sources.sort();
+
+        for name in &args.agents {
+            if !sources.iter().any(|(n, _)| n == name) {
+                tracing::warn!("{name}.json not found in {}", source_dir.display());
+            }
+        }
+
+        let mut results = Vec::with_capacity(sources.len());
+        let mut new_entries = Vec::new();
+        for (name, source) in sources {
+            let declared = self.agents.get(&name);
+            let existing_file = declared.and_then(|slots| {
+                if args.global {
+                    slots.global_agent_file.location()
+                } else {
+                    slots.local_agent_file.location()
+                }
+            });
+            let destination = existing_file
+                .unwrap_or_else(|| generators_dir.join("agents").join(format!("{name}.toml")));
+
+            let skip_reason = if args.force {
+                None
+            } else if declared.is_some() {
+                Some(String::from(
+                    "already managed by kg, use --force to overwrite",
+                ))
+            } else if self.fs.exists(&destination) {
+                Some(format!(
+                    "{} already exists, use --force to overwrite",
+                    destination.display()
+                ))
+            } else {
+                None
+            };
+            if let Some(reason) = skip_reason {
+                results.push(ImportResult {
+                    name,
+                    source,
+                    destination,
+                    status: ImportStatus::Skipped(reason),
+                    toml: String::new(),
+                });
+                continue;
+            }
+
+            let content = self
+                .fs
+                .read_to_string(&source)
+                .await
+                .wrap_err_with(|| format!("Failed to read {}", source.display()))?;
+            let agent = facet_json::from_str::<KiroAgent>(&content)
+                .wrap_err_with(|| format!("Failed to parse agent file {}", source.display()))?;
+            // An empty tools list cannot be expressed in kg and would widen to "*"
+            if agent.tools.is_empty() && !args.allow_all_tools {
+                results.push(ImportResult {
+                    name,
+                    source,
+                    destination,
+                    status: ImportStatus::Skipped(String::from(
+                        "has no tools and kg would generate it with all tools (\"*\"), use \
+                         --allow-all-tools to import it anyway",
+                    )),
+                    toml: String::new(),
+                });
+                continue;
+            }
+            let toml = toml_to_string(&KgAgentFileDoc::from(&agent))
+                .wrap_err_with(|| format!("Failed to convert agent '{name}' to TOML"))?;
+
+            if !args.dry_run {
+                if let Some(parent) = destination.parent() {
+                    self.fs.create_dir_all(parent).await.wrap_err_with(|| {
+                        format!("failed to create directory {}", parent.display())
+                    })?;
+                }
+                self.fs
+                    .write(&destination, &toml)
+                    .await
+                    .wrap_err_with(|| format!("failed to write file {}", destination.display()))?;
+            }
+            if declared.is_none() {
+                new_entries.push(name.clone());
+            }
+            results.push(ImportResult {
+                name,
+                source,
+                destination,
+                status: ImportStatus::Imported,
+                toml,
+            });
+        }
+
+        if !args.dry_run && !new_entries.is_empty() {
+            let entries: String = new_entries
+                .iter()
+                .map(|name| format!("\n[agents.{}]\n", toml_key(name)))
+                .collect();
+            self.append_manifest(&generators_dir.join("manifests").join("kg.toml"), &entries)
+                .await?;
+        }
+
+        Ok(results)
+    }
+
+    /// Append `snippet` to a manifest file, creating it when missing
+    pub(super) async fn append_manifest(&self, kg_toml: &Path, snippet: &str) -> Result<()> {
+        let content = if self.fs.exists(kg_toml) {
+            Some(
+                self.fs
+                    .read_to_string(kg_toml)
+                    .await
+                    .wrap_err_with(|| format!("Failed to read {}", kg_toml.display()))?,
+            )
+        } else {
+            if let Some(dir) = kg_toml.parent() {
+                self.fs
+                    .create_dir_all(dir)
+                    .await
+                    .wrap_err_with(|| format!("failed to create directory {}", dir.display()))?;
+            }
+            None
+        };
+        self.fs
+            .write(kg_toml, appended_manifest(content, snippet))
+            .await
+            .wrap_err_with(|| format!("failed to write file {}", kg_toml.display()))?;
+        Ok(())
+    }
+}
+
+/// `content` of a manifest with `snippet` appended, or a new manifest when
+/// there is none
+pub(super) fn appended_manifest(content: Option<String>, snippet: &str) -> String {
+    let mut content =
+        content.unwrap_or_else(|| format!("\"$schema\" = \"{MANIFEST_SCHEMA_URL}\"\n"));
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(snippet);
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::output::OutputFormat};
+
+    async fn bootstrap(fs: &Fs) -> Result<()> {
+        let agents = PathBuf::from(".kiro").join("agents");
+        fs.write(
+            agents.join("rust.json"),
+            include_str!("../../fixtures/kiro/bootstrap/rust.json"),
+        )
+        .await?;
+        fs.write(
+            agents.join("default.json"),
+            include_str!("../../fixtures/kiro/bootstrap/default.json"),
+        )
+        .await?;
+        fs.write(agents.join("kg-helper.json"), "{}").await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_import_then_diff_has_no_changes() -> Result<()> {
+        let fs = Fs::new();
+        bootstrap(&fs).await?;
+        let generator = Generator::new(fs.clone(), ConfigLocation::Local, OutputFormat::Json)?;
+
+        let results = generator.import(&ImportArgs::default()).await?;
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.status == ImportStatus::Imported));
+        assert!(fs.exists(".kiro/generators/agents/rust.toml"));
+        let manifest = fs
+            .read_to_string(".kiro/generators/manifests/kg.toml")
+            .await?;
+        assert!(manifest.contains("[agents.rust]"));
+        assert!(manifest.contains("[agents.default]"));
+
+        let generator = Generator::new(fs.clone(), ConfigLocation::Local, OutputFormat::Json)?;
+        for agent in generator.merge()? {
+            if agent.name != "rust" && agent.name != "default" {
+                continue;
+            }
+            let kiro_agent = KiroAgent::try_from(&agent)?;
+            let diff = generator.compute_diff(
+                &agent.name,
+                &kiro_agent,
+                crate::output::DiffFormatArg::Plain,
+            )?;
+            assert!(matches!(diff, AgentDiff::Same), "{}: {diff}", agent.name);
+        }
+
+        // Second import skips agents kg already manages
+        let results = generator.import(&ImportArgs::default()).await?;
+        assert!(
+            results
+                .iter()
+                .all(|r| matches!(r.status, ImportStatus::Skipped(_)))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_import_dry_run_writes_nothing() -> Result<()> {
+        let fs = Fs::new();
+        bootstrap(&fs).await?;
+        let generator = Generator::new(fs.clone(), ConfigLocation::Local, OutputFormat::Json)?;
+        let results = generator
+            .import(&ImportArgs {
+                dry_run: true,
+                agents: vec![String::from("rust")],
+                ..Default::default()
+            })
+            .await?;
+        assert_eq!(results.len(), 1);
+        assert!(results[0].toml.contains("[nativeTools.shell]"));
+        assert!(!fs.exists(".kiro/generators/agents/rust.toml"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_import_refuses_agent_without_tools() -> Result<()> {
+        let fs = Fs::new();
+        fs.write(
+            ".kiro/agents/toolless.json",
+            r#"{ "name": "toolless", "description": "no tools" }"#,
+        )
+        .await?;
+        let generator = Generator::new(fs.clone(), ConfigLocation::Local, OutputFormat::Json)?;
+        let results = generator.import(&ImportArgs::default()).await?;
+        assert_eq!(results.len(), 1);
+        assert!(
+            matches!(&results[0].status, ImportStatus::Skipped(reason) if reason.contains("--allow-all-tools")),
+            "{:?}",
+            results[0].status
+        );
+        assert!(!fs.exists(".kiro/generators/agents/toolless.toml"));
+
+        let results = generator
+            .import(&ImportArgs {
+                allow_all_tools: true,
+                ..Default::default()
+            })
+            .await?;
+        assert_eq!(results[0].status, ImportStatus::Imported);
+        assert!(fs.exists(".kiro/generators/agents/toolless.toml"));
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+use {
+    super::{import::KG_HELPER_AGENT, *},
+    std::{collections::BTreeMap, path::Path},
+};
+
+/// Records the agent JSON files kg generated in a destination directory. It
+/// has no `.json` extension so Kiro does not load it as an agent.
+pub const LOCK_FILE: &str = ".kg.lock";
+
+/// Agent JSON written by `kg generate`
+#[derive(Facet, Debug, Clone, Default, PartialEq, Eq)]
+pub struct LockEntry {
+    /// Hash of the file content when it was generated
+    pub hash: String,
+}
+
+#[derive(Facet, Debug, Clone, Default, PartialEq, Eq)]
+#[facet(default)]
+pub struct GeneratedLock {
+    #[facet(default)]
+    pub agents: BTreeMap<String, LockEntry>,
+}
+
+/// Generated agent JSON which no longer matches a concrete agent
+#[derive(Facet, Debug, Clone, PartialEq, Eq)]
+pub struct Orphan {
+    pub name: String,
+    pub path: PathBuf,
+    /// The file changed since kg generated it, it is never pruned
+    pub modified: bool,
+}
+
+/// FNV-1a, stable across Rust releases unlike `DefaultHasher`
+pub fn content_hash(content: &str) -> String {
+    let hash = content.bytes().fold(0xcbf29ce484222325_u64, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}")
+}
+
+impl Generator {
+    pub(super) fn read_lock(&self, dir: &Path) -> Result<GeneratedLock> {
+        let path = dir.join(LOCK_FILE);
+        if !self.fs.exists(&path) {
+            return Ok(GeneratedLock::default());
+        }
+        let content = self.fs.read_to_string_sync(&path)?;
+        facet_json::from_str(&content).wrap_err_with(|| format!("invalid {}", path.display()))
+    }
+
+    async fn write_lock(&self, dir: &Path, lock: &GeneratedLock) -> Result<()> {
+        let path = dir.join(LOCK_FILE);
+        if lock.agents.is_empty() {
+            if self.fs.exists(&path) {
+                self.fs.remove_file(&path).await?;
+            }
+            return Ok(());
+        }
+        self.fs
+            .write(&path, facet_json::to_string_pretty(lock)?)
+            .await
+            .wrap_err_with(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Record the agent files written by `kg generate`
+    pub(super) async fn record_generated(&self, results: &[AgentResult]) -> Result<()> {
+        let mut by_dir: HashMap<&Path, Vec<&str>> = HashMap::new();
+        for result in results.iter().filter(|r| r.writable) {
+            by_dir
+                .entry(result.destination.as_path())
+                .or_default()
+                .push(&result.agent.name);
+        }
+        for (dir, names) in by_dir {
+            self.record_files(dir, &names).await?;
+        }
+        Ok(())
+    }
+
+    /// Record the current content of the agent files `names` in `dir`
+    pub(super) async fn record_files(&self, dir: &Path, names: &[&str]) -> Result<()> {
+        let mut lock = self.read_lock(dir)?;
+        for name in names {
+            let content = self
+                .fs
+                .read_to_string_sync(dir.join(format!("{name}.json")))?;
+            lock.agents.insert(name.to_string(), LockEntry {
+                hash: content_hash(&content),
+            });
+        }
+        self.write_lock(dir, &lock).await
+    }
+
+    /// Directory `kg generate` writes to for the current configuration
+    pub(super) fn managed_dir(&self) -> PathBuf {
+        match self.contains_local_agents() {
+            true => PathBuf::from(".kiro").join("agents"),
+            false => self.destination_dir(""),
+        }
+    }
+
+    /// Agent JSON files kg generated which no longer match a concrete agent.
+    /// Hand-written files are not in the lock file and never reported.
+    pub fn orphans(&self) -> Result<Vec<Orphan>> {
+        let dir = self.managed_dir();
+        let lock = self.read_lock(&dir)?;
+        let mut orphans = Vec::new();
+        for (name, entry) in lock.agents {
+            let concrete = self
+                .agents
+                .get(&name)
+                .is_some_and(|slots| !slots.merged.template);
+            if concrete || name == KG_HELPER_AGENT {
+                continue;
+            }
+            let path = dir.join(format!("{name}.json"));
+            if !self.fs.exists(&path) {
+                continue;
+            }
+            let modified = content_hash(&self.fs.read_to_string_sync(&path)?) != entry.hash;
+            orphans.push(Orphan {
+                name,
+                path,
+                modified,
+            });
+        }
+        Ok(orphans)
+    }
+
+    /// Delete the unmodified orphans and drop them from the lock file
+    pub async fn prune(&self) -> Result<Vec<Orphan>> {
+        let dir = self.managed_dir();
+        let orphans = self.orphans()?;
+        let mut lock = self.read_lock(&dir)?;
+        let mut pruned = Vec::new();
+        for orphan in orphans {
+            if orphan.modified {
+                tracing::warn!(
+                    "{} changed since it was generated, not pruning it",
+                    orphan.path.display()
+                );
+                continue;
+            }
+            self.fs
+                .remove_file(&orphan.path)
+                .await
+                .wrap_err_with(|| format!("failed to remove {}", orphan.path.display()))?;
+            lock.agents.remove(&orphan.name);
+            pruned.push(orphan);
+        }
+        self.write_lock(&dir, &lock).await?;
+        Ok(pruned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::output::OutputFormat};
+
+    const AGENTS: &str = r#"
+[agents.keep]
+description = "kept"
+
+[agents.gone]
+description = "removed later"
+"#;
+
+    async fn with_manifest(fs: &Fs, manifest: &str) -> Result<Generator> {
+        fs.write(".kiro/generators/manifests/lock.toml", manifest)
+            .await?;
+        Generator::new(fs.clone(), ConfigLocation::Local, OutputFormat::Json)
+    }
+
+    #[test]
+    fn test_content_hash_is_stable() {
+        assert_eq!(content_hash(""), "cbf29ce484222325");
+        assert_eq!(content_hash("a"), "af63dc4c8601ec8c");
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_prune_removes_only_generated_orphans() -> Result<()> {
+        let fs = Fs::new();
+        let generator = with_manifest(&fs, AGENTS).await?;
+        generator.write_all(false, false, false).await?;
+        let dir = PathBuf::from(".kiro").join("agents");
+        let lock = generator.read_lock(&dir)?;
+        assert!(lock.agents.contains_key("keep"));
+        assert!(lock.agents.contains_key("gone"));
+        assert!(generator.orphans()?.is_empty());
+
+        fs.write(dir.join("hand-written.json"), "{}").await?;
+        fs.write(dir.join("kg-helper.json"), "{}").await?;
+        let generator = with_manifest(&fs, "[agents.keep]\ndescription = \"kept\"\n").await?;
+        let orphans = generator.orphans()?;
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].name, "gone");
+        assert!(!orphans[0].modified);
+
+        let pruned = generator.prune().await?;
+        assert_eq!(pruned.len(), 1);
+        assert!(!fs.exists(dir.join("gone.json")));
+        assert!(fs.exists(dir.join("keep.json")));
+        assert!(fs.exists(dir.join("hand-written.json")));
+        assert!(fs.exists(dir.join("kg-helper.json")));
+        assert!(!generator.read_lock(&dir)?.agents.contains_key("gone"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_prune_keeps_modified_orphans() -> Result<()> {
+        let fs = Fs::new();
+        let generator = with_manifest(&fs, AGENTS).await?;
+        generator.write_all(false, false, false).await?;
+        let gone = PathBuf::from(".kiro").join("agents").join("gone.json");
+        fs.write(&gone, "{ \"edited\": true }").await?;
+
+        let generator = with_manifest(&fs, "[agents.keep]\ndescription = \"kept\"\n").await?;
+        assert!(generator.orphans()?[0].modified);
+        assert!(generator.prune().await?.is_empty());
+        assert!(fs.exists(&gone));
+        Ok(())
+    }
+}
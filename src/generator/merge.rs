@@ -1,14 +1,105 @@
 use {super::*, std::collections::HashSet};
 
+/// Scalar fields a parent can set for a child which does not set them
+const SCALAR_FIELDS: [&str; 6] = [
+    "description",
+    "prompt",
+    "model",
+    "keyboardShortcut",
+    "welcomeMessage",
+    "useLegacyMcpJson",
+];
+
+fn scalar_value(manifest: &Manifest, field: &str) -> Option<String> {
+    match field {
+        "description" => manifest.description.clone(),
+        "prompt" => manifest
+            .prompt
+            .as_ref()
+            .and_then(|p| p.text())
+            .map(String::from),
+        "model" => manifest.model.clone(),
+        "keyboardShortcut" => manifest.keyboard_shortcut.clone(),
+        "welcomeMessage" => manifest.welcome_message.clone(),
+        "useLegacyMcpJson" => manifest.include_mcp_json.map(|b| b.to_string()),
+        _ => None,
+    }
+}
+
+/// A value set by a direct parent
+#[derive(Facet, Debug, Clone, PartialEq, Eq)]
+pub struct ParentValue {
+    pub parent: String,
+    pub value: String,
+}
+
+/// Direct parents providing different values for a scalar field the agent
+/// does not set. The last parent listed in `inherits` wins
+#[derive(Facet, Debug, Clone, PartialEq, Eq)]
+pub struct InheritanceConflict {
+    pub field: String,
+    pub winner: String,
+    pub values: Vec<ParentValue>,
+}
+
+impl Display for InheritanceConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parents: Vec<&str> = self.values.iter().map(|v| v.parent.as_str()).collect();
+        write!(
+            f,
+            "{} is set differently by {}, using '{}'",
+            self.field,
+            parents.join(", "),
+            self.winner
+        )
+    }
+}
+
+/// C3 merge of the parents' linearizations, `None` when the order they
+/// impose is inconsistent
+fn c3_merge(mut sequences: Vec<Vec<String>>) -> Option<Vec<String>> {
+    let mut out = Vec::new();
+    loop {
+        sequences.retain(|s| !s.is_empty());
+        if sequences.is_empty() {
+            return Some(out);
+        }
+        let head = sequences
+            .iter()
+            .map(|s| &s[0])
+            .find(|head| !sequences.iter().any(|s| s[1..].contains(head)))?
+            .clone();
+        for sequence in &mut sequences {
+            if sequence[0] == head {
+                sequence.remove(0);
+            }
+        }
+        out.push(head);
+    }
+}
+
 impl Generator {
-    /// Resolve transitive inheritance chain for an agent
-    /// Returns ordered list of parent names from base to most derived
-    #[tracing::instrument(level = "info", skip(self))]
-    fn resolve_transitive_inheritance(
+    /// Ancestors of `agent` in precedence order, nearest first, using the C3
+    /// linearization: later parents in `inherits` take precedence over
+    /// earlier ones and every ancestor comes after all agents inheriting
+    /// from it
+    fn linearize(&self, agent: &Manifest, visited: &mut HashSet<String>) -> Result<Vec<String>> {
+        let (order, warnings) = self.order_ancestors(agent, visited)?;
+        for warning in warnings {
+            tracing::debug!(agent = %agent.name, "{warning}");
+        }
+        Ok(order)
+    }
+
+    /// [`Generator::linearize`] and why `inherits` could not be ordered as
+    /// written. A parent listed after one of its own descendants is
+    /// redundant and dropped, an order C3 still cannot satisfy falls back
+    /// to depth-first
+    fn order_ancestors(
         &self,
         agent: &Manifest,
         visited: &mut HashSet<String>,
-    ) -> Result<Vec<String>> {
+    ) -> Result<(Vec<String>, Vec<String>)> {
         if visited.contains(&agent.name) {
             return Err(crate::format_err!(
                 "Circular inheritance detected: {} already in chain",
@@ -17,25 +108,85 @@ impl Generator {
         }
         visited.insert(agent.name.clone());
 
-        let mut chain = Vec::new();
-        for parent_name in agent.inherits.iter() {
+        let mut parents: Vec<String> = Vec::with_capacity(agent.inherits.len());
+        for parent_name in agent.inherits.iter().rev() {
+            if !parents.contains(parent_name) {
+                parents.push(parent_name.clone());
+            }
+        }
+        let mut sequences = Vec::with_capacity(parents.len());
+        for parent_name in &parents {
             let parent = self
                 .agents
                 .get(parent_name)
                 .ok_or_else(|| crate::format_err!("Agent '{parent_name}' not found"))?;
+            let mut sequence = vec![parent_name.clone()];
+            sequence.extend(self.linearize(&parent.merged, visited)?);
+            sequences.push(sequence);
+        }
+        visited.remove(&agent.name);
 
-            let parent_chain = self.resolve_transitive_inheritance(&parent.merged, visited)?;
-            for p in parent_chain {
-                if !chain.contains(&p) {
-                    chain.push(p);
-                }
-            }
-            if !chain.contains(parent_name) {
-                chain.push(parent_name.clone());
-            }
+        let c3 = |sequences: &[Vec<String>], parents: &[String]| {
+            let mut sequences = sequences.to_vec();
+            sequences.push(parents.to_vec());
+            c3_merge(sequences)
+        };
+        if let Some(order) = c3(&sequences, &parents) {
+            return Ok((order, Vec::new()));
         }
 
-        visited.remove(&agent.name);
+        let mut warnings = Vec::new();
+        let redundant: Vec<(String, String)> = parents
+            .iter()
+            .filter_map(|parent| {
+                sequences
+                    .iter()
+                    .find(|s| s[0] != *parent && s.contains(parent))
+                    .map(|s| (parent.clone(), s[0].clone()))
+            })
+            .collect();
+        for (parent, descendant) in &redundant {
+            warnings.push(format!(
+                "inherits '{parent}' is already inherited through '{descendant}', ignoring it"
+            ));
+        }
+        parents.retain(|p| !redundant.iter().any(|(r, _)| r == p));
+        sequences.retain(|s| parents.contains(&s[0]));
+        if let Some(order) = c3(&sequences, &parents) {
+            return Ok((order, warnings));
+        }
+
+        warnings.push(format!(
+            "cannot order inherits [{}] consistently, using depth-first order",
+            agent.inherits.join(", ")
+        ));
+        // Keep the last occurrence so every ancestor stays after the
+        // agents inheriting from it
+        let flat: Vec<String> = sequences.concat();
+        let order = flat
+            .iter()
+            .enumerate()
+            .filter(|(i, name)| !flat[i + 1..].contains(name))
+            .map(|(_, name)| name.clone())
+            .collect();
+        Ok((order, warnings))
+    }
+
+    /// Why the `inherits` of a named agent could not be ordered as written
+    pub(super) fn inherits_warnings(&self, name: &str) -> Result<Vec<String>> {
+        let agent = self
+            .agents
+            .get(name)
+            .ok_or_else(|| crate::format_err!("Agent '{name}' not found"))?;
+        Ok(self.order_ancestors(&agent.merged, &mut HashSet::new())?.1)
+    }
+
+    /// Resolve transitive inheritance chain for an agent
+    /// Returns ordered list of parent names from base to most derived
+    #[tracing::instrument(level = "info", skip(self))]
+    fn resolve_transitive_inheritance(&self, agent: &Manifest) -> Result<Vec<String>> {
+        let mut chain = self.linearize(agent, &mut HashSet::new())?;
+        chain.reverse();
         Ok(chain)
     }
 
@@ -45,7 +196,7 @@ impl Generator {
             .agents
             .get(name)
             .ok_or_else(|| crate::format_err!("Agent '{name}' not found"))?;
-        self.resolve_transitive_inheritance(&agent.merged, &mut HashSet::new())
+        self.resolve_transitive_inheritance(&agent.merged)
     }
 
     pub fn inheritance_chain_safe(&self, name: &str) -> Vec<String> {
@@ -55,6 +206,106 @@ impl Generator {
         })
     }
 
+    /// Expand `${NAME}` references in a merged agent. On failure the error
+    /// names the source file which declared the reference.
+    fn interpolate(&self, agent: &mut Manifest, parents: &[String]) -> Result<()> {
+        let Err((field, err)) = agent.interpolate_vars() else {
+            return Ok(());
+        };
+        let source = std::iter::once(&agent.name)
+            .chain(parents.iter().rev())
+            .filter_map(|name| self.agents.get(name))
+            .flat_map(|slots| {
+                [
+                    &slots.local_agent_file,
+                    &slots.local_manifest,
+                    &slots.global_agent_file,
+                    &slots.global_manifest,
+                ]
+            })
+            .filter(|slot| {
+                slot.manifest
+                    .interpolate_vars_as(agent)
+                    .is_err_and(|(f, _)| f == field)
+            })
+            .find_map(|slot| slot.path.as_ref());
+        match source {
+            Some(source) => Err(crate::format_err!(
+                "Agent '{}': {err} in {field} ({source})",
+                agent.name
+            )),
+            None => Err(crate::format_err!(
+                "Agent '{}': {err} in {field}",
+                agent.name
+            )),
+        }
+    }
+
+    /// Merge `agent` with its ancestors, returning the merged agent and the
+    /// chain from base to most derived
+    fn merge_ancestors(&self, agent: &Manifest) -> Result<(Manifest, Vec<String>)> {
+        let parents = self.resolve_transitive_inheritance(agent)?;
+        let mut merged = agent.clone();
+        for parent_name in parents.iter().rev() {
+            let parent = &self
+                .agents
+                .get(parent_name)
+                .ok_or_else(|| crate::format_err!("Parent agent '{parent_name}' not found"))?
+                .merged;
+            tracing::trace!(parent = %parent_name, parent_template = parent.template, "merging parent");
+            merged = merged.merge(parent.clone());
+        }
+        merged.resolve_removals();
+        Ok((merged, parents))
+    }
+
+    /// Scalar fields the agent leaves to its parents which two direct parents
+    /// set to different values
+    pub fn inheritance_conflicts(&self, name: &str) -> Result<Vec<InheritanceConflict>> {
+        let agent = &self
+            .agents
+            .get(name)
+            .ok_or_else(|| crate::format_err!("Agent '{name}' not found"))?
+            .merged;
+        if agent.inherits.len() < 2 {
+            return Ok(Vec::new());
+        }
+        let mut parents = Vec::with_capacity(agent.inherits.len());
+        for parent_name in &agent.inherits {
+            let parent = self
+                .agents
+                .get(parent_name)
+                .ok_or_else(|| crate::format_err!("Agent '{parent_name}' not found"))?;
+            parents.push((parent_name, self.merge_ancestors(&parent.merged)?.0));
+        }
+
+        let mut conflicts = Vec::new();
+        for field in SCALAR_FIELDS {
+            if scalar_value(agent, field).is_some() {
+                continue;
+            }
+            let values: Vec<ParentValue> = parents
+                .iter()
+                .filter_map(|(parent, merged)| {
+                    Some(ParentValue {
+                        parent: parent.to_string(),
+                        value: scalar_value(merged, field)?,
+                    })
+                })
+                .collect();
+            if let Some(winner) = values.last()
+                && values.iter().any(|v| v.value != winner.value)
+            {
+                conflicts.push(InheritanceConflict {
+                    field: field.to_string(),
+                    winner: winner.parent.clone(),
+                    values,
+                });
+            }
+        }
+        Ok(conflicts)
+    }
+
     /// Merge all agents with transitive inheritance resolution
     #[tracing::instrument(level = "info", skip(self))]
     pub fn merge(&self) -> Result<Vec<Manifest>> {
@@ -63,25 +314,18 @@ impl Generator {
 
         for (name, agent_slots) in &self.agents {
             let agent = &agent_slots.merged;
-            let mut visited = HashSet::new();
-            let parents = self.resolve_transitive_inheritance(agent, &mut visited)?;
             let span = tracing::info_span!(
                 "agent",
                 name = name.as_str(),
-                parents = parents.len(),
+                parents = agent.inherits.len(),
                 template = agent.template
             );
             let _enter = span.enter();
 
-            let mut merged = agent.clone();
-            for parent_name in parents.iter().rev() {
-                let parent = &self
-                    .agents
-                    .get(parent_name)
-                    .ok_or_else(|| crate::format_err!("Parent agent '{parent_name}' not found"))?
-                    .merged;
-                tracing::trace!(parent = %parent_name, parent_template = parent.template, "merging parent");
-                merged = merged.merge(parent.clone());
+            let (mut merged, parents) = self.merge_ancestors(agent)?;
+            if !merged.template {
+                self.interpolate(&mut merged, &parents)?;
+                self.expand_subagent_globs(&mut merged);
             }
 
             resolved_agents.insert(name.clone(), merged);
@@ -124,8 +368,8 @@ mod tests {
 
         // Should have prompt from aws-test
         assert_eq!(
-            dependabot.prompt.clone().unwrap_or_default(),
-            "you are an AWS expert".to_string()
+            dependabot.prompt.as_ref().and_then(|p| p.text()),
+            Some("you are an AWS expert")
         );
 
         // Should have tools from base
@@ -177,9 +421,181 @@ mod tests {
         assert!(aws.allows.contains("s3"));
 
         // check try_from
-        let results = generator.write_all(true, false).await?;
+        let results = generator.write_all(true, false, false).await?;
         assert!(!results.is_empty());
 
         Ok(())
     }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_merge_interpolates_vars() -> Result<()> {
+        let fs = Fs::new();
+        fs.write(
+            ".kiro/generators/manifests/vars.toml",
+            r#"
+[agents.vars-base]
+template = true
+vars = { org = "acme", team = "core" }
+
+[agents.vars-child]
+inherits = ["vars-base"]
+prompt = "${agent.name} for ${org}/${team}"
+vars = { team = "platform" }
+"#,
+        )
+        .await?;
+        let generator = Generator::new(
+            fs.clone(),
+            ConfigLocation::Local,
+            crate::output::OutputFormat::Json,
+        )?;
+        let merged = generator.merge()?;
+        let child = merged
+            .iter()
+            .find(|a| a.name == "vars-child")
+            .expect("vars-child agent not found");
+        assert_eq!(
+            child.prompt.as_ref().and_then(|p| p.text()),
+            Some("vars-child for acme/platform")
+        );
+
+        fs.write(
+            ".kiro/generators/manifests/broken.toml",
+            r#"
+[agents.broken]
+description = "uses ${KG_TEST_UNDEFINED_VAR}"
+"#,
+        )
+        .await?;
+        let generator =
+            Generator::new(fs, ConfigLocation::Local, crate::output::OutputFormat::Json)?;
+        let err = generator.merge().unwrap_err().to_string();
+        assert!(
+            err.contains("undefined variable 'KG_TEST_UNDEFINED_VAR'"),
+            "{err}"
+        );
+        assert!(err.contains("in description"), "{err}");
+        assert!(err.contains("broken.toml"), "{err}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_ordered_inheritance_and_conflicts() -> Result<()> {
+        let fs = Fs::new();
+        fs.write(
+            ".kiro/generators/manifests/order.toml",
+            r#"
+[agents.order-base]
+template = true
+description = "shared"
+model = "base-model"
+
+[agents.order-left]
+template = true
+inherits = ["order-base"]
+model = "left-model"
+
+[agents.order-right]
+template = true
+inherits = ["order-base"]
+model = "right-model"
+
+[agents.order-child]
+inherits = ["order-left", "order-right"]
+
+[agents.order-pinned]
+inherits = ["order-left", "order-right"]
+model = "pinned"
+"#,
+        )
+        .await?;
+        let generator = Generator::new(
+            fs.clone(),
+            ConfigLocation::Local,
+            crate::output::OutputFormat::Json,
+        )?;
+
+        // The shared base comes before both parents, the last parent wins
+        assert_eq!(generator.inheritance_chain("order-child")?, vec![
+            "order-base",
+            "order-left",
+            "order-right"
+        ]);
+        for _ in 0..5 {
+            let merged = generator.merge()?;
+            let child = merged.iter().find(|a| a.name == "order-child").unwrap();
+            assert_eq!(child.model.as_deref(), Some("right-model"));
+        }
+
+        let conflicts = generator.inheritance_conflicts("order-child")?;
+        assert_eq!(conflicts.len(), 1, "{conflicts:?}");
+        assert_eq!(conflicts[0].field, "model");
+        assert_eq!(conflicts[0].winner, "order-right");
+        assert_eq!(
+            conflicts[0].to_string(),
+            "model is set differently by order-left, order-right, using 'order-right'"
+        );
+        assert!(generator.inheritance_conflicts("order-pinned")?.is_empty());
+
+        let details = crate::tree::details(&generator, &[String::from("order-child")]);
+        let detail = &details["order-child"];
+        assert_eq!(detail.inherits, vec!["order-left", "order-right"]);
+        assert_eq!(detail.conflicts, conflicts);
+
+        fs.write(
+            ".kiro/generators/manifests/broken-order.toml",
+            r#"
+[agents.order-broken]
+inherits = ["order-left", "order-base"]
+"#,
+        )
+        .await?;
+        let generator = Generator::new(
+            fs.clone(),
+            ConfigLocation::Local,
+            crate::output::OutputFormat::Json,
+        )?;
+        // order-base is already inherited through order-left
+        generator.merge()?;
+        assert_eq!(generator.inheritance_chain("order-broken")?, vec![
+            "order-base",
+            "order-left"
+        ]);
+        assert_eq!(generator.inherits_warnings("order-broken")?, vec![
+            "inherits 'order-base' is already inherited through 'order-left', ignoring it"
+        ]);
+        assert!(generator.inherits_warnings("order-child")?.is_empty());
+
+        fs.write(
+            ".kiro/generators/manifests/crossed-order.toml",
+            r#"
+[agents.order-a]
+template = true
+inherits = ["order-left", "order-right"]
+
+[agents.order-b]
+template = true
+inherits = ["order-right", "order-left"]
+
+[agents.order-crossed]
+inherits = ["order-a", "order-b"]
+"#,
+        )
+        .await?;
+        let generator =
+            Generator::new(fs, ConfigLocation::Local, crate::output::OutputFormat::Json)?;
+        assert_eq!(generator.inheritance_chain("order-crossed")?, vec![
+            "order-base",
+            "order-left",
+            "order-right",
+            "order-a",
+            "order-b"
+        ]);
+        assert_eq!(generator.inherits_warnings("order-crossed")?, vec![
+            "cannot order inherits [order-a, order-b] consistently, using depth-first order"
+        ]);
+        Ok(())
+    }
 }
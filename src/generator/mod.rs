@@ -2,7 +2,7 @@ use {
     crate::{
         Manifest,
         Result,
-        kiro::{KiroAgent, ToolTarget},
+        kiro::{KiroAgent, ToolTarget, diff::NormalizedAgent},
         os::Fs,
     },
     color_eyre::eyre::Context,
@@ -11,7 +11,7 @@ use {
     std::{
         collections::{HashMap, HashSet},
         fmt::{self, Debug, Display},
-        path::PathBuf,
+        path::{Path, PathBuf},
     },
 };
 
@@ -19,12 +19,34 @@ pub(super) const MAX_AGENT_DIR_DEPTH: usize = 5;
 /// max number of files or directories in a given Path.
 /// 1000 should be more than enough to handle templates and real agents
 pub(super) const MAX_AGENT_DIR_ENTRIES: usize = 1000;
+/// Suffix of the temporary files agent JSON is written to before being renamed
+/// into place
+const TMP_SUFFIX: &str = ".kg-tmp";
 
 mod config_location;
 pub(crate) mod discover;
+mod history;
+mod import;
+mod lock;
 mod merge;
-
-pub use config_location::*;
+mod path;
+mod policy;
+mod refactor;
+mod resource;
+mod shell;
+mod status;
+mod subagent;
+
+pub use {
+    config_location::*,
+    history::{HISTORY_LIMIT, Snapshot, SnapshotHistory},
+    import::*,
+    merge::InheritanceConflict,
+    path::*,
+    policy::*,
+    shell::*,
+    status::*,
+};
 
 use crate::source::*;
 
@@ -98,6 +120,12 @@ pub struct Generator {
     #[facet(skip, default)]
     #[allow(unused)]
     format: crate::output::OutputFormat,
+    /// Policies every generated agent must satisfy
+    #[facet(skip, default)]
+    policies: Vec<LoadedPolicy>,
+    /// Snapshots kept in the history directory
+    #[facet(skip, default)]
+    history_limit: usize,
 }
 
 impl Debug for Generator {
@@ -120,11 +148,14 @@ impl Generator {
     ) -> Result<Self> {
         let global_path = location.global_path();
         let agents = discover::discover(&fs, &location, &format)?;
+        let policies = load_policies(&fs)?;
         Ok(Self {
             global_path,
             agents,
             fs,
             format,
+            policies,
+            history_limit: HISTORY_LIMIT,
         })
     }
 
@@ -162,19 +193,19 @@ impl Generator {
         }
     }
 
-    /// Compute diff between existing agent file and generated agent
-    fn compute_diff(
+    /// Existing and generated agent prepared for diffing, `None` when the
+    /// agent was not generated yet
+    fn normalized_pair(
         &self,
         agent_name: &str,
         generated: &KiroAgent,
-        format: crate::output::DiffFormatArg,
-    ) -> Result<AgentDiff> {
+    ) -> Result<Option<(NormalizedAgent, NormalizedAgent)>> {
         let destination = self
             .destination_dir(agent_name)
             .join(format!("{}.json", agent_name));
 
         if !self.fs.exists(&destination) {
-            return Ok(AgentDiff::New);
+            return Ok(None);
         }
 
         let existing = self.fs.read_to_string_sync(&destination)?;
@@ -185,8 +216,24 @@ impl Generator {
             )
         })?;
 
-        let normalized_existing = existing_agent.normalize();
-        let normalized_generated = generated.clone().normalize();
+        Ok(Some((
+            existing_agent.redact_like(generated).normalize(),
+            generated.clone().normalize(),
+        )))
+    }
+
+    /// Compute diff between existing agent file and generated agent
+    fn compute_diff(
+        &self,
+        agent_name: &str,
+        generated: &KiroAgent,
+        format: crate::output::DiffFormatArg,
+    ) -> Result<AgentDiff> {
+        let Some((normalized_existing, normalized_generated)) =
+            self.normalized_pair(agent_name, generated)?
+        else {
+            return Ok(AgentDiff::New);
+        };
         let diff = normalized_existing.diff(&normalized_generated);
 
         if diff.is_equal() {
@@ -194,7 +241,9 @@ impl Generator {
         } else {
             // Choose formatting based on args
             let formatted = match format {
-                crate::output::DiffFormatArg::Agent => rediff::format_diff_compact_plain(&diff),
+                crate::output::DiffFormatArg::Agent | crate::output::DiffFormatArg::Json => {
+                    rediff::format_diff_compact_plain(&diff)
+                }
                 crate::output::DiffFormatArg::Compact => rediff::format_diff_compact(&diff),
                 crate::output::DiffFormatArg::Plain => {
                     let config = rediff::DiffFormat {
@@ -218,9 +267,25 @@ impl Generator {
         }
     }
 
+    /// Print the diff, or with `--check` or `--format json` the JSON report
+    /// which is returned
     #[tracing::instrument(level = "info")]
-    pub fn diff(&self, args: &crate::commands::DiffArgs) -> Result<()> {
-        self.diff_agents(args.format, &args.agents)
+    pub fn diff(&self, args: &crate::commands::DiffArgs) -> Result<Option<DiffReport>> {
+        match args.check || matches!(args.format, crate::output::DiffFormatArg::Json) {
+            true => {
+                let report = self.diff_report(&args.agents)?;
+                println!(
+                    "{}",
+                    facet_json::to_string_pretty(&report)
+                        .wrap_err("Failed to serialize diff report to JSON")?
+                );
+                Ok(Some(report))
+            }
+            false => {
+                self.diff_agents(args.format, &args.agents)?;
+                Ok(None)
+            }
+        }
     }
 
     /// Diff for generate command — always compact, no filter
@@ -228,17 +293,23 @@ impl Generator {
         self.diff_agents(crate::output::DiffFormatArg::Compact, &[])
     }
 
-    fn diff_agents(&self, format: crate::output::DiffFormatArg, filter: &[String]) -> Result<()> {
+    /// Concrete agents in scope, restricted to `filter` when not empty, and
+    /// the requested agents which were not found
+    fn diff_scope(&self, filter: &[String]) -> Result<(Vec<Manifest>, Vec<String>)> {
         let agents: Vec<Manifest> = self.merge()?.into_iter().filter(|a| !a.template).collect();
         let all_agents = !self.contains_local_agents();
-        let mut changed = 0;
-        let mut unchanged = 0;
         let visible_agents = agents
             .into_iter()
             .filter(|agent| all_agents || self.is_local(&agent.name))
             .collect::<Vec<_>>();
-        let missing_agents = missing_agents(&visible_agents, filter);
-        let agents = filter_agents(visible_agents, filter);
+        let missing = missing_agents(&visible_agents, filter);
+        Ok((filter_agents(visible_agents, filter), missing))
+    }
+
+    fn diff_agents(&self, format: crate::output::DiffFormatArg, filter: &[String]) -> Result<()> {
+        let (agents, missing_agents) = self.diff_scope(filter)?;
+        let mut changed = 0;
+        let mut unchanged = 0;
 
         for a in agents {
             let destination = self
@@ -269,8 +340,81 @@ impl Generator {
         Ok(())
     }
 
-    #[tracing::instrument(skip(dry_run, skip_unchanged), level = "info")]
-    pub async fn write_all(&self, dry_run: bool, skip_unchanged: bool) -> Result<Vec<AgentResult>> {
+    /// Report of `kg diff --check` and `kg diff --format json`
+    fn diff_report(&self, filter: &[String]) -> Result<DiffReport> {
+        let (agents, missing) = self.diff_scope(filter)?;
+        let mut report = DiffReport {
+            missing,
+            ..Default::default()
+        };
+
+        for a in agents {
+            let destination = self
+                .destination_dir(&a.name)
+                .join(format!("{}.json", a.name));
+            let generated_agent = KiroAgent::try_from(&a)?;
+
+            let mut entry = AgentDiffReport {
+                name: a.name,
+                destination: destination.display().to_string(),
+                state: SyncState::New,
+                changes: Vec::new(),
+                fields: Vec::new(),
+            };
+            if let Some((existing, generated)) =
+                self.normalized_pair(&entry.name, &generated_agent)?
+            {
+                let diff = existing.diff(&generated);
+                let changes = rediff::collect_leaf_changes(&diff);
+                entry.state = match diff.is_equal() {
+                    true => SyncState::Same,
+                    false => SyncState::Changed,
+                };
+                entry.changes = changes.iter().map(|c| c.format_plain()).collect();
+                entry.fields = changes
+                    .iter()
+                    .map(FieldChange::try_from)
+                    .collect::<Result<_>>()?;
+            }
+            report.agents.push(entry);
+        }
+        Ok(report)
+    }
+
+    /// Fail when an agent has a literal MCP `env` or `headers` value that
+    /// looks like a credential
+    pub fn check_secrets(&self) -> Result<()> {
+        let found: Vec<String> = self
+            .merge()?
+            .iter()
+            .filter(|a| !a.template)
+            .flat_map(|a| {
+                a.plaintext_secrets()
+                    .into_iter()
+                    .map(move |field| format!("  {}: {field}", a.name))
+            })
+            .collect();
+        if !found.is_empty() {
+            crate::bail!(
+                "Plaintext credentials found, use {{ secret = \"file:<path>\" }} or {{ env = \
+                 \"NAME\" }} instead:\n{}",
+                found.join("\n")
+            );
+        }
+        Ok(())
+    }
+
+    /// Merge and validate every agent in scope, then write the agent files.
+    /// Nothing is written when an agent fails validation. With
+    /// `all_or_nothing` the files already replaced are restored when a write
+    /// fails.
+    #[tracing::instrument(skip(dry_run, skip_unchanged, all_or_nothing), level = "info")]
+    pub async fn write_all(
+        &self,
+        dry_run: bool,
+        skip_unchanged: bool,
+        all_or_nothing: bool,
+    ) -> Result<Vec<AgentResult>> {
         let agents = self.merge()?;
         let mut results = Vec::with_capacity(agents.len());
         // If no local agents defined, write all (global) agents
@@ -280,19 +424,61 @@ impl Generator {
             let span = tracing::info_span!("agent", name = ?agent.name, local = self.is_local(&agent.name));
             let _enter = span.enter();
             if write_all_agents || self.is_local(&agent.name) {
-                results.push(self.write(agent, dry_run, skip_unchanged).await?);
+                results.push(self.prepare(agent)?);
             }
         }
+        if dry_run {
+            return Ok(results);
+        }
+
+        let mut pending = Vec::new();
+        for result in results.iter().filter(|r| r.writable) {
+            if let Some(content) = self.render(result, skip_unchanged)? {
+                let out = result
+                    .destination
+                    .join(format!("{}.json", result.agent.name));
+                pending.push((out, content));
+            }
+        }
+
+        let mut previous = Vec::with_capacity(pending.len());
+        for (out, _) in &pending {
+            previous.push(match self.fs.exists(out) {
+                true => Some(self.fs.read_to_string_sync(out)?),
+                false => None,
+            });
+        }
+        // Keep the files about to be overwritten for `kg rollback`
+        let overwritten: Vec<(&Path, &str)> = pending
+            .iter()
+            .zip(&previous)
+            .filter_map(|((out, content), previous)| match previous {
+                Some(previous) if previous != content => Some((out.as_path(), previous.as_str())),
+                _ => None,
+            })
+            .collect();
+        if let Some(snapshot) = self.snapshot(&overwritten).await? {
+            tracing::info!("saved {} in {}", snapshot.agents.join(", "), snapshot.path);
+        }
+
+        let mut replaced: Vec<(&PathBuf, Option<String>)> = Vec::new();
+        for ((out, content), previous) in pending.iter().zip(previous) {
+            if let Err(e) = self.write_atomic(out, content).await {
+                if all_or_nothing {
+                    self.restore(replaced).await;
+                    return Err(e.wrap_err("restored the agent files written before the failure"));
+                }
+                return Err(e);
+            }
+            replaced.push((out, previous));
+        }
+        self.record_generated(&results).await?;
         Ok(results)
     }
 
-    #[tracing::instrument(skip(dry_run,skip_unchanged), level = "info", fields(out = tracing::field::Empty))]
-    pub(crate) async fn write(
-        &self,
-        agent: Manifest,
-        dry_run: bool,
-        skip_unchanged: bool,
-    ) -> Result<AgentResult> {
+    /// Build and validate the Kiro agent without writing it
+    #[tracing::instrument(level = "info", skip_all, fields(name = %agent.name))]
+    fn prepare(&self, agent: Manifest) -> Result<AgentResult> {
         let destination = self.destination_dir(&agent.name);
         let result = AgentResult {
             kiro_agent: KiroAgent::try_from(&agent)?,
@@ -304,64 +490,107 @@ impl Generator {
         if let Ok(j) = facet_json::to_string_pretty(&result.kiro_agent) {
             tracing::trace!("{j}");
         }
+        self.enforce_policies(&result.agent)?;
         result.kiro_agent.validate()?;
-        if dry_run {
-            return Ok(result);
+        self.validate_shell_patterns(&result.agent)?;
+        for warning in self.check_resources(&result.agent)? {
+            tracing::warn!("{warning}");
         }
-        if !self.fs.exists(&result.destination) {
-            self.fs
-                .create_dir_all(&result.destination)
-                .await
-                .wrap_err_with(|| {
-                    format!(
-                        "failed to create directory {}",
-                        result.destination.display()
-                    )
-                })?;
+        for warning in self.inherits_warnings(&result.agent.name)? {
+            tracing::warn!("Agent '{}': {warning}", result.agent.name);
         }
-        if result.writable {
-            let out = result
-                .destination
-                .join(format!("{}.json", result.agent.name));
-
-            tracing::Span::current().record("out", tracing::field::display(&out.display()));
-
-            if !skip_unchanged {
-                // Default: always write
-                self.fs
-                    .write(&out, facet_json::to_string_pretty(&result.kiro_agent)?)
-                    .await
-                    .wrap_err_with(|| format!("failed to write file {}", out.display()))?;
-                return Ok(result);
+        if !result.agent.template {
+            for conflict in self.inheritance_conflicts(&result.agent.name)? {
+                tracing::warn!("{conflict}");
             }
+            for warning in self.subagent_warnings(&result.agent) {
+                tracing::warn!("{warning}");
+            }
+            let mut servers: Vec<_> = result.agent.mcp_servers.iter().collect();
+            servers.sort_by(|a, b| a.0.cmp(b.0));
+            for (name, mcp) in servers {
+                for tool in mcp.approved_and_disabled() {
+                    tracing::warn!(
+                        "mcpServers.{name}.autoApprove '{tool}' is also in disabledTools, Kiro \
+                         will not load it"
+                    );
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// JSON written for an agent, `None` when `skip_unchanged` and the file
+    /// is up to date
+    fn render(&self, result: &AgentResult, skip_unchanged: bool) -> Result<Option<String>> {
+        // Secret references are only resolved for the file written to disk
+        let mut resolved = result.agent.clone();
+        resolved.resolve_secrets(&self.fs)?;
+        let kiro_agent = KiroAgent::try_from(&resolved)?;
 
-            // --skip-unchanged: compute diff and skip if unchanged
+        if skip_unchanged {
             let diff = self.compute_diff(
                 &result.agent.name,
-                &result.kiro_agent,
+                &kiro_agent,
                 crate::output::DiffFormatArg::Compact,
             )?;
             tracing::debug!("{diff}");
-            match diff {
-                AgentDiff::New => {
-                    self.fs
-                        .write(&out, facet_json::to_string_pretty(&result.kiro_agent)?)
-                        .await
-                        .wrap_err_with(|| format!("failed to write file {}", out.display()))?;
-                }
-                AgentDiff::Changed(_) => {
-                    self.fs
-                        .write(&out, facet_json::to_string_pretty(&result.kiro_agent)?)
-                        .await
-                        .wrap_err_with(|| format!("failed to write file {}", out.display()))?;
-                }
-                AgentDiff::Same => {}
+            if matches!(diff, AgentDiff::Same) {
+                return Ok(None);
+            }
+        }
+        Ok(Some(facet_json::to_string_pretty(&kiro_agent)?))
+    }
+
+    /// Write `content` to a temporary file next to `out` and rename it into
+    /// place, so `out` is never left truncated
+    #[tracing::instrument(level = "info", skip(self, content), fields(out = %out.display()))]
+    async fn write_atomic(&self, out: &Path, content: &str) -> Result<()> {
+        let dir = out.parent().unwrap_or(Path::new(""));
+        if !self.fs.exists(dir) {
+            self.fs
+                .create_dir_all(dir)
+                .await
+                .wrap_err_with(|| format!("failed to create directory {}", dir.display()))?;
+        }
+        let file_name = out.file_name().unwrap_or_default().to_string_lossy();
+        let tmp = dir.join(format!(".{file_name}{TMP_SUFFIX}"));
+        self.fs
+            .write(&tmp, content)
+            .await
+            .wrap_err_with(|| format!("failed to write file {}", out.display()))?;
+        if let Err(e) = self.fs.rename(&tmp, out).await {
+            let _ = self.fs.remove_file(&tmp).await;
+            return Err(e.wrap_err(format!("failed to write file {}", out.display())));
+        }
+        Ok(())
+    }
+
+    /// Put back the previous content of replaced agent files, removing the
+    /// ones which did not exist
+    async fn restore(&self, replaced: Vec<(&PathBuf, Option<String>)>) {
+        for (out, previous) in replaced.into_iter().rev() {
+            let restored = match previous {
+                Some(content) => self.write_atomic(out, &content).await,
+                None => self.fs.remove_file(out).await,
+            };
+            match restored {
+                Ok(()) => tracing::info!("restored {}", out.display()),
+                Err(e) => tracing::error!("failed to restore {}: {e:?}", out.display()),
             }
         }
-        Ok(result)
     }
 }
 
+/// Generator over `fs` with `toml` as the local manifest
+/// `manifests/<name>.toml`
+#[cfg(test)]
+pub(crate) async fn manifest_generator(fs: Fs, name: &str, toml: &str) -> Result<Generator> {
+    fs.write(format!(".kiro/generators/manifests/{name}.toml"), toml)
+        .await?;
+    Generator::new(fs, ConfigLocation::Local, crate::output::OutputFormat::Json)
+}
+
 fn filter_agents(agents: Vec<Manifest>, filter: &[String]) -> Vec<Manifest> {
     if filter.is_empty() {
         agents
@@ -499,4 +728,287 @@ mod tests {
             )
         );
     }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn secrets_are_only_resolved_when_writing() -> Result<()> {
+        let fs = Fs::new();
+        fs.create_dir_all("secrets").await?;
+        fs.write("secrets/gh", "ghp_filesecret\n").await?;
+        fs.write(
+            ".kiro/generators/manifests/secrets.toml",
+            r#"
+[agents.secretive.mcpServers.github]
+command = "github-mcp"
+env = { GITHUB_TOKEN = { secret = "file:secrets/gh" }, LOG = "debug" }
+"#,
+        )
+        .await?;
+        let generator = Generator::new(
+            fs.clone(),
+            ConfigLocation::Local,
+            crate::output::OutputFormat::Json,
+        )?;
+        generator.check_secrets()?;
+
+        let secretive = |results: Vec<AgentResult>| {
+            results
+                .into_iter()
+                .find(|r| r.agent.name == "secretive")
+                .expect("secretive agent not found")
+        };
+        let result = secretive(generator.write_all(true, false, false).await?);
+        let env = &result.kiro_agent.mcp_servers["github"].env;
+        assert_eq!(env["GITHUB_TOKEN"], crate::kg_config::REDACTED);
+        assert!(!fs.exists(".kiro/agents/secretive.json"));
+
+        let result = secretive(generator.write_all(false, false, false).await?);
+        let written = fs.read_to_string_sync(".kiro/agents/secretive.json")?;
+        assert!(written.contains("ghp_filesecret"));
+        assert!(!written.contains(crate::kg_config::REDACTED));
+        // The diff compares redacted values
+        let diff = generator.compute_diff(
+            "secretive",
+            &result.kiro_agent,
+            crate::output::DiffFormatArg::Plain,
+        )?;
+        assert!(matches!(diff, AgentDiff::Same), "{diff}");
+
+        fs.write(
+            ".kiro/generators/manifests/secrets.toml",
+            r#"
+[agents.secretive.mcpServers.github]
+command = "github-mcp"
+env = { GITHUB_TOKEN = "ghp_0123456789abcdef" }
+"#,
+        )
+        .await?;
+        let generator =
+            Generator::new(fs, ConfigLocation::Local, crate::output::OutputFormat::Json)?;
+        let err = generator.check_secrets().unwrap_err().to_string();
+        assert!(
+            err.contains("secretive: mcpServers.github.env.GITHUB_TOKEN"),
+            "{err}"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn generated_json_is_byte_stable() -> Result<()> {
+        let fs = Fs::new();
+        fs.write(
+            ".kiro/generators/manifests/stable.toml",
+            r#"
+[agents.stable]
+description = "Byte stable agent"
+tools = ["write", "@zeta", "read", "shell", "@alpha"]
+allowedTools = ["shell", "read", "@alpha"]
+toolAliases = { "@zeta/b" = "b", "@alpha/a" = "a" }
+
+[agents.stable.nativeTools.shell]
+allow = ["npm test .*", "cargo .*", "git status .*"]
+deny = ["git push .*", "cargo publish .*"]
+
+[agents.stable.nativeTools.read]
+allow = ["./src/**", "./docs/**"]
+
+[agents.stable.mcpServers.zeta]
+command = "zeta-mcp"
+args = ["--verbose", "--port", "8080"]
+env = { ZED = "1", ALPHA = "2", MIDDLE = "3" }
+autoApprove = ["search", "fetch"]
+
+[agents.stable.mcpServers.alpha]
+command = "alpha-mcp"
+
+[agents.stable.hooks.stop.summary]
+command = "echo summary"
+
+[agents.stable.hooks.stop.cleanup]
+command = "echo cleanup"
+
+[agents.stable.hooks.agentSpawn.status]
+command = "git status"
+
+[agents.stable.toolSettings."@zeta/b"]
+zulu = 1
+alpha = { yankee = true, bravo = false }
+"#,
+        )
+        .await?;
+        let out = ".kiro/agents/stable.json";
+        let mut written = Vec::new();
+        for _ in 0..3 {
+            Generator::new(
+                fs.clone(),
+                ConfigLocation::Local,
+                crate::output::OutputFormat::Json,
+            )?
+            .write_all(false, false, false)
+            .await?;
+            written.push(fs.read_to_string_sync(out)?);
+        }
+        assert_eq!(written[0], written[1]);
+        assert_eq!(written[1], written[2]);
+
+        let json = &written[0];
+        let before = |a: &str, b: &str| {
+            let (a_at, b_at) = (json.find(a), json.find(b));
+            assert!(
+                a_at.is_some() && b_at.is_some(),
+                "{a} or {b} missing: {json}"
+            );
+            assert!(a_at < b_at, "{a} should come before {b}: {json}");
+        };
+        before("\"name\"", "\"description\"");
+        before("\"tools\"", "\"allowedTools\"");
+        before("\"@alpha\"", "\"@zeta\"");
+        before("\"alpha-mcp\"", "\"zeta-mcp\"");
+        before("\"ALPHA\"", "\"MIDDLE\"");
+        before("\"MIDDLE\"", "\"ZED\"");
+        before("\"fetch\"", "\"search\"");
+        before("\"agentSpawn\"", "\"stop\"");
+        before("echo cleanup", "echo summary");
+        before("\"cargo .*\"", "\"git status .*\"");
+        before("\"git status .*\"", "\"npm test .*\"");
+        before("\"./docs/**\"", "\"./src/**\"");
+        before("\"bravo\"", "\"yankee\"");
+        before("\"alpha\"", "\"zulu\"");
+        // Argument order is meaningful and kept as declared
+        before("\"--verbose\"", "\"--port\"");
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn check_reports_new_changed_and_missing_agents() -> Result<()> {
+        let fs = Fs::new();
+        let manifest = ".kiro/generators/manifests/check.toml";
+        fs.write(manifest, "[agents.checked]\ndescription = \"before\"\n")
+            .await?;
+        let generator = Generator::new(
+            fs.clone(),
+            ConfigLocation::Local,
+            crate::output::OutputFormat::Json,
+        )?;
+        let filter = [String::from("checked")];
+        let report = generator.diff_report(&filter)?;
+        assert_eq!(report.agents[0].state, SyncState::New);
+        assert!(!report.in_sync());
+
+        generator.write_all(false, false, false).await?;
+        let report = generator.diff_report(&filter)?;
+        assert_eq!(report.agents[0].state, SyncState::Same);
+        assert!(report.in_sync());
+
+        let report = generator.diff_report(&[String::from("checked"), String::from("nope")])?;
+        assert_eq!(report.missing, vec![String::from("nope")]);
+        assert!(!report.in_sync());
+
+        fs.write(manifest, "[agents.checked]\ndescription = \"after\"\n")
+            .await?;
+        let generator =
+            Generator::new(fs, ConfigLocation::Local, crate::output::OutputFormat::Json)?;
+        let report = generator.diff_report(&filter)?;
+        assert_eq!(report.agents[0].state, SyncState::Changed);
+        assert_eq!(report.agents[0].changes, vec![String::from(
+            "description: \"before\" → \"after\""
+        )]);
+        let field = &report.agents[0].fields[0];
+        assert_eq!(field.path, "description");
+        assert_eq!(field.kind, ChangeKind::Modified);
+        assert_eq!(field.old, Some(facet_value::Value::from("before")));
+        assert_eq!(field.new, Some(facet_value::Value::from("after")));
+        assert!(!report.in_sync());
+        Ok(())
+    }
+
+    const TWO_AGENTS: &str = r#"
+[agents.first]
+description = "first"
+
+[agents.second]
+description = "second"
+"#;
+
+    /// MCP server of `second` whose secret cannot resolve, appended to
+    /// [`TWO_AGENTS`]
+    const UNRESOLVED_SECRET: &str = r#"
+[agents.second.mcpServers.gh]
+command = "gh"
+env = { TOKEN = { env = "KG_TEST_UNSET_ATOMIC" } }
+"#;
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn write_all_checks_every_agent_before_writing() -> Result<()> {
+        let fs = Fs::new();
+        let manifest = ".kiro/generators/manifests/atomic.toml";
+        fs.write(manifest, format!("{TWO_AGENTS}{UNRESOLVED_SECRET}"))
+            .await?;
+        let generator = Generator::new(
+            fs.clone(),
+            ConfigLocation::Local,
+            crate::output::OutputFormat::Json,
+        )?;
+        let err = format!(
+            "{:?}",
+            generator
+                .write_all(false, false, false)
+                .await
+                .err()
+                .expect("write_all should fail")
+        );
+        assert!(err.contains("KG_TEST_UNSET_ATOMIC"), "{err}");
+        assert!(!fs.exists(".kiro/agents/first.json"));
+        assert!(!fs.exists(".kiro/agents/second.json"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn write_all_or_nothing_restores_replaced_files() -> Result<()> {
+        let fs = Fs::new();
+        fs.write(".kiro/generators/manifests/atomic.toml", TWO_AGENTS)
+            .await?;
+        let generator = Generator::new(
+            fs.clone(),
+            ConfigLocation::Local,
+            crate::output::OutputFormat::Json,
+        )?;
+        let dir = PathBuf::from(".kiro").join("agents");
+        fs.create_dir_all(&dir).await?;
+        fs.write(dir.join("first.json"), "{ \"name\": \"first\" }")
+            .await?;
+        // A directory in place of the temporary file makes the write fail
+        fs.create_dir_all(dir.join(format!(".second.json{TMP_SUFFIX}/x")))
+            .await?;
+
+        let err = format!(
+            "{:?}",
+            generator
+                .write_all(false, false, true)
+                .await
+                .err()
+                .expect("write_all should fail")
+        );
+        assert!(err.contains("second.json"), "{err}");
+        assert_eq!(
+            fs.read_to_string_sync(dir.join("first.json"))?,
+            "{ \"name\": \"first\" }"
+        );
+        assert!(!fs.exists(dir.join("second.json")));
+        assert!(!fs.exists(dir.join(format!(".first.json{TMP_SUFFIX}"))));
+
+        fs.remove_dir_all(dir.join(format!(".second.json{TMP_SUFFIX}")))
+            .await?;
+        generator.write_all(false, false, true).await?;
+        assert!(
+            fs.read_to_string_sync(dir.join("first.json"))?
+                .contains("\"description\"")
+        );
+        assert!(fs.exists(dir.join("second.json")));
+        Ok(())
+    }
 }
@@ -0,0 +1,388 @@
+use {
+    super::*,
+    crate::kiro::{
+        GlobTool as KiroGlobTool,
+        GrepTool as KiroGrepTool,
+        ReadTool as KiroReadTool,
+        WriteTool as KiroWriteTool,
+    },
+    std::{collections::BTreeSet, path::Component},
+};
+
+/// Native tools taking `allowedPaths` and `deniedPaths`
+#[derive(Facet, Copy, Clone, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+#[facet(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum PathTool {
+    #[default]
+    Read,
+    Write,
+    Glob,
+    Grep,
+}
+
+impl Display for PathTool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read => write!(f, "read"),
+            Self::Write => write!(f, "write"),
+            Self::Glob => write!(f, "glob"),
+            Self::Grep => write!(f, "grep"),
+        }
+    }
+}
+
+impl PathTool {
+    /// Final `(allowedPaths, deniedPaths)` after `forceAllow`
+    fn paths(&self, agent: &Manifest) -> (BTreeSet<String>, BTreeSet<String>) {
+        let tools = &agent.native_tools;
+        match self {
+            Self::Read => {
+                let t = KiroReadTool::from(tools);
+                (t.allowed_paths, t.denied_paths)
+            }
+            Self::Write => {
+                let t = KiroWriteTool::from(tools);
+                (t.allowed_paths, t.denied_paths)
+            }
+            Self::Glob => {
+                let t = KiroGlobTool::from(tools);
+                (t.allowed_paths, t.denied_paths)
+            }
+            Self::Grep => {
+                let t = KiroGrepTool::from(tools);
+                (t.allowed_paths, t.denied_paths)
+            }
+        }
+    }
+}
+
+/// Result of `kg check-path`
+#[derive(Facet, Debug, Clone)]
+pub struct PathCheck {
+    pub agent: String,
+    pub tool: PathTool,
+    pub path: String,
+    /// `path` with `~` expanded, relative to the current directory
+    pub resolved: String,
+    pub decision: Decision,
+    /// Pattern which decided, `None` when no pattern matched
+    pub pattern: Option<String>,
+    /// `nativeTools` list declaring the pattern
+    pub list: Option<String>,
+    /// Agent or ancestor declaring the pattern
+    pub source: Option<String>,
+    pub source_path: Option<String>,
+    /// Allow entries of the tool which a deny entry makes unreachable
+    pub warnings: Vec<String>,
+}
+
+/// Expand `~` and make `path` absolute against `cwd`, resolving `.` and `..`
+/// without touching the file system
+pub fn resolve_path(path: &str, cwd: &Path, home: Option<&Path>) -> PathBuf {
+    let expanded = match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            home.join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(path),
+    };
+    let absolute = match expanded.is_absolute() {
+        true => expanded,
+        false => cwd.join(expanded),
+    };
+    let mut resolved = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            c => resolved.push(c),
+        }
+    }
+    resolved
+}
+
+/// Expand `{a,b}` alternatives, one level deep
+fn expand_braces(pattern: &str) -> Vec<String> {
+    let (Some(open), Some(close)) = (pattern.find('{'), pattern.find('}')) else {
+        return vec![pattern.to_string()];
+    };
+    if close < open {
+        return vec![pattern.to_string()];
+    }
+    let (head, tail) = (&pattern[..open], &pattern[close + 1..]);
+    pattern[open + 1..close]
+        .split(',')
+        .flat_map(|alt| expand_braces(&format!("{head}{alt}{tail}")))
+        .collect()
+}
+
+/// Match a `[...]` class at the start of `pattern`, returning the rest of the
+/// pattern after the class
+fn match_class(pattern: &[char], c: char) -> Option<(bool, &[char])> {
+    let end = pattern.iter().skip(1).position(|p| *p == ']')? + 1;
+    let mut class = &pattern[1..end];
+    let negate = matches!(class.first(), Some('!' | '^'));
+    if negate {
+        class = &class[1..];
+    }
+    let mut matched = false;
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            matched |= class[i] <= c && c <= class[i + 2];
+            i += 3;
+        } else {
+            matched |= class[i] == c;
+            i += 1;
+        }
+    }
+    Some((matched != negate, &pattern[end + 1..]))
+}
+
+fn glob_chars(pattern: &[char], path: &[char]) -> bool {
+    match pattern.first() {
+        None => path.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            let rest = &pattern[2..];
+            // `**/` also matches no directory at all
+            if rest.first() == Some(&'/') && glob_chars(&rest[1..], path) {
+                return true;
+            }
+            (0..=path.len()).any(|i| glob_chars(rest, &path[i..]))
+        }
+        Some('*') => {
+            let rest = &pattern[1..];
+            for i in 0..=path.len() {
+                if glob_chars(rest, &path[i..]) {
+                    return true;
+                }
+                if path.get(i) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        Some('?') => {
+            path.first().is_some_and(|c| *c != '/') && glob_chars(&pattern[1..], &path[1..])
+        }
+        Some('[') => {
+            let Some(c) = path.first() else {
+                return false;
+            };
+            match match_class(pattern, *c) {
+                Some((matched, rest)) => matched && *c != '/' && glob_chars(rest, &path[1..]),
+                // No closing `]`, match it literally
+                None => *c == '[' && glob_chars(&pattern[1..], &path[1..]),
+            }
+        }
+        Some(p) => path.first() == Some(p) && glob_chars(&pattern[1..], &path[1..]),
+    }
+}
+
+/// Glob matching like Kiro's path settings: `*` and `?` stay within a path
+/// component, `**` crosses components. A pattern matching a directory also
+/// matches everything below it.
+pub fn path_matches(pattern: &Path, path: &Path) -> bool {
+    let pattern = pattern.to_string_lossy();
+    let path: Vec<char> = path.to_string_lossy().chars().collect();
+    expand_braces(&pattern).iter().any(|pattern| {
+        let pattern: Vec<char> = pattern.chars().collect();
+        glob_chars(&pattern, &path)
+            || path
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| **c == '/')
+                .any(|(i, _)| i > 0 && glob_chars(&pattern, &path[..i]))
+    })
+}
+
+/// Same as [`path_matches`] without matching the paths below a matching
+/// directory
+pub(super) fn path_matches_exactly(pattern: &Path, path: &Path) -> bool {
+    let path: Vec<char> = path.to_string_lossy().chars().collect();
+    expand_braces(&pattern.to_string_lossy())
+        .iter()
+        .any(|pattern| glob_chars(&pattern.chars().collect::<Vec<_>>(), &path))
+}
+
+pub(super) fn has_wildcard(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '[', '{'])
+}
+
+/// Whether every path `allow` matches is also matched by `deny`
+fn unreachable(allow: &Path, deny: &Path) -> bool {
+    let allow_text = allow.to_string_lossy();
+    if !has_wildcard(&allow_text) {
+        return path_matches(deny, allow);
+    }
+    // `dir/**` or `dir` covers every pattern below `dir`
+    let deny_text = deny.to_string_lossy();
+    let dir = deny_text.strip_suffix("/**").unwrap_or(&deny_text);
+    !has_wildcard(dir) && allow.starts_with(dir)
+}
+
+impl Generator {
+    /// Decide whether `tool` of `agent` may access `path` under the merged
+    /// settings: deny patterns win, then allow patterns, otherwise the user
+    /// is asked
+    pub fn check_path(&self, agent: &str, tool: PathTool, path: &str) -> Result<PathCheck> {
+        let merged = self
+            .merge()?
+            .into_iter()
+            .find(|a| a.name == agent)
+            .ok_or_else(|| crate::format_err!("Agent '{agent}' not found"))?;
+        let cwd = std::env::current_dir().wrap_err("Failed to read the current directory")?;
+        let home = dirs::home_dir();
+        let resolve = |p: &str| resolve_path(p, &cwd, home.as_deref());
+
+        let (allowed, denied) = tool.paths(&merged);
+        let mut allowed: Vec<String> = allowed.into_iter().collect();
+        let mut denied: Vec<String> = denied.into_iter().collect();
+        allowed.sort();
+        denied.sort();
+        let target = resolve(path);
+
+        let mut check = PathCheck {
+            agent: agent.to_string(),
+            tool,
+            path: path.to_string(),
+            resolved: target.display().to_string(),
+            decision: Decision::Prompted,
+            pattern: None,
+            list: None,
+            source: None,
+            source_path: None,
+            warnings: Vec::new(),
+        };
+        for allow in &allowed {
+            if let Some(deny) = denied
+                .iter()
+                .find(|d| unreachable(&resolve(allow), &resolve(d)))
+            {
+                check.warnings.push(format!(
+                    "allow '{allow}' is unreachable, deny '{deny}' covers it"
+                ));
+            }
+        }
+
+        let matching = |patterns: &[String]| {
+            patterns
+                .iter()
+                .find(|p| path_matches(&resolve(p), &target))
+                .cloned()
+        };
+        let (decision, pattern, lists): (_, _, &[&str]) = if let Some(p) = matching(&denied) {
+            (Decision::Denied, p, &["deny"])
+        } else if let Some(p) = matching(&allowed) {
+            (Decision::Allowed, p, &["forceAllow", "allow"])
+        } else {
+            return Ok(check);
+        };
+        check.decision = decision;
+        if let Some(rule) = self.attribute(agent, &tool.to_string(), lists, &pattern)? {
+            check.list = Some(rule.field);
+            check.source = Some(rule.source);
+            check.source_path = Some(rule.path);
+        }
+        check.pattern = Some(pattern);
+        Ok(check)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::output::OutputFormat};
+
+    #[test]
+    fn test_resolve_path() {
+        let cwd = Path::new("/work/project");
+        let home = Some(Path::new("/home/me"));
+        assert_eq!(
+            resolve_path("./src/main.rs", cwd, home),
+            PathBuf::from("/work/project/src/main.rs")
+        );
+        assert_eq!(
+            resolve_path("~/.ssh/id", cwd, home),
+            PathBuf::from("/home/me/.ssh/id")
+        );
+        assert_eq!(
+            resolve_path("../other/x", cwd, home),
+            PathBuf::from("/work/other/x")
+        );
+        assert_eq!(
+            resolve_path("/etc/hosts", cwd, home),
+            PathBuf::from("/etc/hosts")
+        );
+    }
+
+    #[test]
+    fn test_path_matches() {
+        let m = |pattern: &str, path: &str| path_matches(Path::new(pattern), Path::new(path));
+        assert!(m("/p/src/**", "/p/src/a/b.rs"));
+        assert!(m("/p/**/*.rs", "/p/main.rs"));
+        assert!(m("/p/src/*.rs", "/p/src/main.rs"));
+        assert!(!m("/p/src/*.rs", "/p/src/a/main.rs"));
+        assert!(m("/p/src", "/p/src/a/main.rs"));
+        assert!(m("/p/{src,tests}/**", "/p/tests/it.rs"));
+        assert!(m("/p/file[0-9].txt", "/p/file7.txt"));
+        assert!(!m("/p/file[!0-9].txt", "/p/file7.txt"));
+        assert!(!m("/p/src/**", "/p/srcx/main.rs"));
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let u = |allow: &str, deny: &str| unreachable(Path::new(allow), Path::new(deny));
+        assert!(u("/p/secrets/key", "/p/secrets/**"));
+        assert!(u("/p/secrets/*.pem", "/p/secrets"));
+        assert!(!u("/p/src/**", "/p/src/generated/**"));
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_check_path() -> Result<()> {
+        let fs = Fs::new();
+        fs.write(
+            ".kiro/generators/manifests/paths.toml",
+            r#"
+            [agents.paths-base]
+            template = true
+            nativeTools.write.allow = ["./src/**", "./src/generated/keep.rs"]
+            nativeTools.write.deny = ["./src/generated/**", "./secrets/**"]
+
+            [agents.paths-child]
+            inherits = ["paths-base"]
+            nativeTools.write.forceAllow = ["./secrets/**"]
+            "#,
+        )
+        .await?;
+        let generator = Generator::new(fs, ConfigLocation::Local, OutputFormat::Json)?;
+
+        let check = generator.check_path("paths-child", PathTool::Write, "./src/main.rs")?;
+        assert_eq!(check.decision, Decision::Allowed);
+        assert_eq!(check.pattern.as_deref(), Some("./src/**"));
+        assert_eq!(check.source.as_deref(), Some("paths-base"));
+        assert_eq!(check.warnings, vec![String::from(
+            "allow './src/generated/keep.rs' is unreachable, deny './src/generated/**' covers it"
+        )]);
+
+        let check = generator.check_path("paths-child", PathTool::Write, "src/generated/x.rs")?;
+        assert_eq!(check.decision, Decision::Denied);
+        assert_eq!(check.list.as_deref(), Some("nativeTools.write.deny"));
+
+        // forceAllow only overrides the identical deny entry
+        let check =
+            generator.check_path("paths-child", PathTool::Write, "src/generated/keep.rs")?;
+        assert_eq!(check.decision, Decision::Denied);
+
+        let check = generator.check_path("paths-child", PathTool::Write, "secrets/key")?;
+        assert_eq!(check.decision, Decision::Allowed);
+        assert_eq!(check.list.as_deref(), Some("nativeTools.write.forceAllow"));
+        assert_eq!(check.source.as_deref(), Some("paths-child"));
+
+        let check = generator.check_path("paths-child", PathTool::Read, "./src/main.rs")?;
+        assert_eq!(check.decision, Decision::Prompted);
+        Ok(())
+    }
+}
@@ -0,0 +1,284 @@
+use {
+    super::*,
+    crate::{Policy, glob_match, sorted, toml_parse_path},
+};
+
+/// Policy shared by every user of the machine, applied next to the user's own
+pub const SYSTEM_POLICY: &str = "/etc/kg/policy.toml";
+/// File name of the user's policy in `~/.kiro/generators`
+pub const POLICY_FILE: &str = "policy.toml";
+
+/// A `policy.toml` and the file it was read from
+#[derive(Facet, Debug, Clone)]
+pub struct LoadedPolicy {
+    pub path: PathBuf,
+    pub policy: Policy,
+}
+
+/// Read the system policy and the user's policy. Both apply when both exist
+pub(super) fn load_policies(fs: &Fs) -> Result<Vec<LoadedPolicy>> {
+    #[cfg(not(test))]
+    let home = dirs::home_dir();
+    #[cfg(test)]
+    let home = Some(PathBuf::from(crate::os::ACTIVE_USER_HOME));
+
+    let mut paths = vec![PathBuf::from(SYSTEM_POLICY)];
+    paths.extend(home.map(|h| h.join(".kiro").join("generators").join(POLICY_FILE)));
+    let mut policies = Vec::new();
+    for path in paths {
+        let Some(policy) = toml_parse_path::<Policy>(fs, &path) else {
+            continue;
+        };
+        let policy =
+            policy.wrap_err_with(|| format!("failed to parse policy {}", path.display()))?;
+        tracing::debug!(path = %path.display(), "loaded policy");
+        policies.push(LoadedPolicy { path, policy });
+    }
+    Ok(policies)
+}
+
+impl Generator {
+    /// Fail when the merged agent breaks a loaded policy, listing every
+    /// violation with the field and the file declaring it. Templates are
+    /// checked through the agents inheriting them
+    pub(super) fn enforce_policies(&self, agent: &Manifest) -> Result<()> {
+        if agent.template {
+            return Ok(());
+        }
+        let violations: Vec<String> = self
+            .policies
+            .iter()
+            .flat_map(|loaded| {
+                self.policy_violations(agent, &loaded.policy)
+                    .into_iter()
+                    .map(|v| format!("  {v} (policy {})", loaded.path.display()))
+            })
+            .collect();
+        if !violations.is_empty() {
+            crate::bail!(
+                "Agent '{}' violates policy\n{}",
+                agent.name,
+                violations.join("\n")
+            );
+        }
+        Ok(())
+    }
+
+    fn policy_violations(&self, agent: &Manifest, policy: &Policy) -> Vec<String> {
+        let mut found = Vec::new();
+        let lists = agent.native_tools.lists();
+        let list = |tool: &str, name: &str| {
+            lists
+                .iter()
+                .find(|((t, l), _)| *t == tool && *l == name)
+                .map(|(_, values)| *values)
+        };
+
+        for (tool, denies) in policy.native_tools.denies() {
+            for deny in sorted(denies) {
+                // aws ignores forceAllow
+                let forced =
+                    tool != "aws" && list(tool, "forceAllow").is_some_and(|f| f.contains(deny));
+                if forced {
+                    let field = format!("nativeTools.{tool}.forceAllow");
+                    let source = self.declared_in(&agent.name, &field, deny);
+                    found.push(format!("{field} '{deny}'{source}: overrides a policy deny"));
+                } else if !list(tool, "deny").is_some_and(|d| d.contains(deny)) {
+                    found.push(format!(
+                        "nativeTools.{tool}.deny: missing '{deny}', add it to the agent or a \
+                         template it inherits"
+                    ));
+                }
+            }
+        }
+
+        if let Some(required) = policy.native_tools.shell.deny_by_default
+            && agent.native_tools.shell.deny_by_default.unwrap_or(false) != required
+        {
+            found.push(format!(
+                "nativeTools.shell.denyByDefault: must be {required}"
+            ));
+        }
+
+        let mut servers: Vec<_> = agent.mcp_servers.iter().collect();
+        servers.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, mcp) in servers {
+            let source = || self.declared_in(&agent.name, "mcpServers", name);
+            let file_name = Path::new(&mcp.command)
+                .file_name()
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if !mcp.command.is_empty()
+                && policy
+                    .mcp
+                    .forbidden_commands
+                    .iter()
+                    .any(|f| glob_match(f, &mcp.command) || glob_match(f, &file_name))
+            {
+                found.push(format!(
+                    "mcpServers.{name}.command '{}'{}: forbidden by policy",
+                    mcp.command,
+                    source()
+                ));
+            }
+            if !mcp.url.is_empty()
+                && policy
+                    .mcp
+                    .forbidden_urls
+                    .iter()
+                    .any(|f| glob_match(f, &mcp.url))
+            {
+                found.push(format!(
+                    "mcpServers.{name}.url '{}'{}: forbidden by policy",
+                    mcp.url,
+                    source()
+                ));
+            }
+        }
+
+        for trigger in sorted(policy.required_hooks.keys()) {
+            for command in sorted(&policy.required_hooks[trigger]) {
+                let present = agent
+                    .hooks
+                    .get(trigger)
+                    .is_some_and(|hooks| hooks.values().any(|h| &h.command == command));
+                if !present {
+                    found.push(format!(
+                        "hooks.{trigger}: missing a hook running '{command}'"
+                    ));
+                }
+            }
+        }
+
+        if let Some(model) = &agent.model
+            && !policy.models.is_empty()
+            && !policy.models.iter().any(|m| glob_match(m, model))
+        {
+            let allowed: Vec<&str> = sorted(&policy.models)
+                .into_iter()
+                .map(String::as_str)
+                .collect();
+            found.push(format!(
+                "model '{model}'{}: not allowed by policy, use one of {}",
+                self.declared_in(&agent.name, "model", model),
+                allowed.join(", ")
+            ));
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: &str = r#"
+        models = ["claude-sonnet-*"]
+        nativeTools.shell.deny = ["rm -rf .*"]
+        nativeTools.shell.denyByDefault = true
+        mcp.forbiddenCommands = ["curl"]
+        mcp.forbiddenUrls = ["http://*"]
+        requiredHooks.preToolUse = ["audit-log"]
+    "#;
+
+    async fn generator(policy_path: &str, toml: &str) -> Result<Generator> {
+        let fs = Fs::new();
+        if let Some(dir) = Path::new(policy_path).parent() {
+            fs.create_dir_all(dir).await?;
+        }
+        fs.write(policy_path, POLICY).await?;
+        manifest_generator(fs, "policy", toml).await
+    }
+
+    fn user_policy() -> String {
+        format!(
+            "{}/.kiro/generators/{POLICY_FILE}",
+            crate::os::ACTIVE_USER_HOME
+        )
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_policy_violations() -> Result<()> {
+        let generator = generator(
+            SYSTEM_POLICY,
+            r#"
+            [agents.policy-base]
+            template = true
+            model = "claude-sonnet-4"
+            nativeTools.shell.deny = ["rm -rf .*"]
+            nativeTools.shell.denyByDefault = true
+
+            [agents.policy-base.hooks.preToolUse.audit]
+            command = "audit-log"
+
+            [agents.policy-ok]
+            inherits = ["policy-base"]
+
+            [agents.policy-bad]
+            inherits = ["policy-base"]
+            model = "gpt-4"
+            nativeTools.shell.forceAllow = ["rm -rf .*"]
+            mcpServers.fetch.command = "/usr/bin/curl"
+            mcpServers.web.url = "http://example.com/mcp"
+            "#,
+        )
+        .await?;
+        assert_eq!(generator.policies.len(), 1);
+
+        let merged = generator.merge()?;
+        let agent = |name: &str| merged.iter().find(|a| a.name == name).expect(name);
+        generator.enforce_policies(agent("policy-ok"))?;
+        generator.enforce_policies(agent("policy-base"))?;
+
+        let err = format!(
+            "{:?}",
+            generator
+                .enforce_policies(agent("policy-bad"))
+                .expect_err("policy-bad accepted")
+        );
+        for expected in [
+            "Agent 'policy-bad' violates policy",
+            "nativeTools.shell.forceAllow 'rm -rf .*' (local-manifest://",
+            "manifests/policy.toml): overrides a policy deny",
+            "mcpServers.fetch.command '/usr/bin/curl'",
+            "mcpServers.web.url 'http://example.com/mcp'",
+            "model 'gpt-4'",
+            SYSTEM_POLICY,
+        ] {
+            assert!(err.contains(expected), "{expected}: {err}");
+        }
+        assert!(!err.contains("hooks.preToolUse"), "{err}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_policy_blocks_generate() -> Result<()> {
+        let generator = generator(
+            &user_policy(),
+            r#"
+            [agents.policy-missing]
+            description = "no guardrails"
+            "#,
+        )
+        .await?;
+        let err = generator
+            .write_all(false, false, false)
+            .await
+            .err()
+            .expect("agent breaking policy was written");
+        let err = format!("{err:?}");
+        assert!(
+            err.contains("nativeTools.shell.deny: missing 'rm -rf .*'"),
+            "{err}"
+        );
+        assert!(err.contains("nativeTools.shell.denyByDefault"), "{err}");
+        assert!(
+            err.contains("hooks.preToolUse: missing a hook running 'audit-log'"),
+            "{err}"
+        );
+        assert!(!generator.fs.exists(".kiro/agents/policy-missing.json"));
+        Ok(())
+    }
+}
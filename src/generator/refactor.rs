@@ -0,0 +1,801 @@
+use {
+    super::{discover::merge_manifests, *},
+    crate::{KgAgentFileDoc, commands::ExtractTemplatesArgs, toml_to_string},
+    color_eyre::eyre::bail,
+    std::{collections::BTreeMap, path::Path},
+    toml_edit::{Array, DocumentMut, Item, Table, TableLike},
+};
+
+/// An agent whose own definition shrinks once it inherits the template
+pub struct TemplateEdit {
+    pub agent: String,
+    /// Number of shared entries removed from the agent's own files
+    pub removed: usize,
+}
+
+pub struct TemplateProposal {
+    pub template: Manifest,
+    /// Agent file holding the template configuration
+    pub template_file: PathBuf,
+    /// Generated TOML for `template_file`
+    pub toml: String,
+    pub edits: Vec<TemplateEdit>,
+    /// Files rewritten to drop the shared entries or add `inherits`
+    pub files: Vec<PathBuf>,
+    /// Number of concrete agents checked to generate identical Kiro agents
+    pub verified: usize,
+}
+
+/// The manifest and agent file slots `kg refactor` is allowed to edit
+fn scoped(slots: &AgentSourceSlots, local: bool) -> [&SourceSlot; 2] {
+    if local {
+        [&slots.local_manifest, &slots.local_agent_file]
+    } else {
+        [&slots.global_manifest, &slots.global_agent_file]
+    }
+}
+
+fn scoped_mut(slots: &mut AgentSourceSlots, local: bool) -> [&mut SourceSlot; 2] {
+    if local {
+        [&mut slots.local_manifest, &mut slots.local_agent_file]
+    } else {
+        [&mut slots.global_manifest, &mut slots.global_agent_file]
+    }
+}
+
+fn out_of_scope(slots: &AgentSourceSlots, local: bool) -> [&SourceSlot; 2] {
+    scoped(slots, !local)
+}
+
+/// Map entries present and equal in every agent. `providers` are manifests
+/// the template cannot replace, anything they declare is left alone.
+fn shared_entries<V: Clone + PartialEq>(
+    finals: &[&Manifest],
+    providers: &[&Manifest],
+    field: impl Fn(&Manifest) -> &HashMap<String, V>,
+) -> HashMap<String, V> {
+    let Some(first) = finals.first() else {
+        return HashMap::new();
+    };
+    field(first)
+        .iter()
+        .filter(|(k, v)| {
+            finals.iter().all(|m| field(m).get(*k) == Some(v))
+                && providers.iter().all(|m| !field(m).contains_key(*k))
+        })
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+/// Set entries present in every agent and declared by at least one of them
+fn shared_set(
+    finals: &[&Manifest],
+    own: &[Manifest],
+    field: impl Fn(&Manifest) -> &HashSet<String>,
+) -> HashSet<String> {
+    let Some(first) = finals.first() else {
+        return HashSet::new();
+    };
+    field(first)
+        .iter()
+        .filter(|e| {
+            finals.iter().all(|m| field(m).contains(*e))
+                && own.iter().any(|m| field(m).contains(*e))
+        })
+        .cloned()
+        .collect()
+}
+
+fn is_empty_template(template: &Manifest) -> bool {
+    template.tools.is_empty()
+        && template.allowed_tools.is_empty()
+        && template
+            .native_tools
+            .lists()
+            .iter()
+            .all(|(_, l)| l.is_empty())
+        && template.mcp_servers.is_empty()
+        && template.resources.is_empty()
+        && template.skills.is_empty()
+        && template.knowledge.is_empty()
+}
+
+/// Names of the map entries moved into `template`, keyed by TOML field
+fn template_keys(template: &Manifest) -> [(&'static str, Vec<&String>); 4] {
+    [
+        ("mcpServers", template.mcp_servers.keys().collect()),
+        ("resources", template.resources.keys().collect()),
+        ("skills", template.skills.keys().collect()),
+        ("knowledge", template.knowledge.keys().collect()),
+    ]
+}
+
+/// Remove the entries provided by `template` from `manifest`
+fn strip(manifest: &mut Manifest, template: &Manifest) -> usize {
+    let mut removed = 0;
+    let mut retain = |set: &mut HashSet<String>, shared: &HashSet<String>| {
+        let before = set.len();
+        set.retain(|e| !shared.contains(e));
+        removed += before - set.len();
+    };
+    retain(&mut manifest.tools, &template.tools);
+    retain(&mut manifest.allowed_tools, &template.allowed_tools);
+    for ((_, list), (_, shared)) in manifest
+        .native_tools
+        .lists_mut()
+        .into_iter()
+        .zip(template.native_tools.lists())
+    {
+        retain(list, shared);
+    }
+    for key in template.mcp_servers.keys() {
+        removed += usize::from(manifest.mcp_servers.remove(key).is_some());
+    }
+    for key in template.resources.keys() {
+        removed += usize::from(manifest.resources.remove(key).is_some());
+    }
+    for key in template.skills.keys() {
+        removed += usize::from(manifest.skills.remove(key).is_some());
+    }
+    for key in template.knowledge.keys() {
+        removed += usize::from(manifest.knowledge.remove(key).is_some());
+    }
+    removed
+}
+
+/// Remove the strings in `shared` from the array at `key`, dropping the key
+/// once the array is empty
+fn strip_array(table: &mut dyn TableLike, key: &str, shared: &HashSet<String>) {
+    let Some(array) = table.get_mut(key).and_then(Item::as_array_mut) else {
+        return;
+    };
+    array.retain(|v| !v.as_str().is_some_and(|s| shared.contains(s)));
+    if array.is_empty() {
+        table.remove(key);
+    }
+}
+
+fn remove_if_empty(table: &mut dyn TableLike, key: &str) {
+    if table
+        .get(key)
+        .and_then(Item::as_table_like)
+        .is_some_and(TableLike::is_empty)
+    {
+        table.remove(key);
+    }
+}
+
+/// Same as [`strip`] on the TOML table defining an agent. The table is
+/// edited in place, so comments and formatting of the remaining entries are
+/// kept
+fn strip_table(table: &mut dyn TableLike, template: &Manifest) {
+    strip_array(table, "tools", &template.tools);
+    strip_array(table, "allowedTools", &template.allowed_tools);
+    if let Some(native) = table
+        .get_mut("nativeTools")
+        .and_then(Item::as_table_like_mut)
+    {
+        for ((tool, list), shared) in template.native_tools.lists() {
+            if let Some(t) = native.get_mut(tool).and_then(Item::as_table_like_mut) {
+                strip_array(t, list, shared);
+            }
+            remove_if_empty(native, tool);
+        }
+    }
+    remove_if_empty(table, "nativeTools");
+    for (field, keys) in template_keys(template) {
+        if let Some(entries) = table.get_mut(field).and_then(Item::as_table_like_mut) {
+            for key in keys {
+                entries.remove(key);
+            }
+        }
+        remove_if_empty(table, field);
+    }
+}
+
+/// Add `template` to the `inherits` of the agent table, creating the key
+fn add_inherits(table: &mut dyn TableLike, template: &str) {
+    match table.get_mut("inherits").and_then(Item::as_array_mut) {
+        Some(inherits) => inherits.push(template),
+        None => {
+            table.insert("inherits", toml_edit::value(Array::from_iter([template])));
+        }
+    }
+}
+
+/// Parse a TOML file and apply `edit` to it in place
+fn edit_toml(
+    path: &Path,
+    content: &str,
+    edit: impl FnOnce(&mut DocumentMut) -> Result<()>,
+) -> Result<String> {
+    let mut doc: DocumentMut = content
+        .parse()
+        .wrap_err_with(|| format!("Failed to parse {}", path.display()))?;
+    edit(&mut doc)?;
+    Ok(doc.to_string())
+}
+
+impl Generator {
+    /// Find configuration shared by a group of agents and move it into a new
+    /// template they inherit.
+    ///
+    /// Only entries that every candidate ends up with, and that none of their
+    /// parents or out of scope files provide, are extracted. The proposal is
+    /// checked by merging the edited agents again: every concrete agent must
+    /// normalize to the same [`KiroAgent`] as before, otherwise nothing is
+    /// written. Returns `None` when the agents share nothing.
+    #[tracing::instrument(level = "info", skip(self))]
+    pub async fn extract_templates(
+        &self,
+        args: &ExtractTemplatesArgs,
+    ) -> Result<Option<TemplateProposal>> {
+        let local = self.contains_local_agents();
+        let generators_dir = if local {
+            PathBuf::from(".kiro").join("generators")
+        } else {
+            self.global_path.clone()
+        };
+        let kg_toml = generators_dir.join("manifests").join("kg.toml");
+        let template_file = generators_dir
+            .join("agents")
+            .join(format!("{}.toml", args.name));
+
+        if self.agents.contains_key(&args.name) {
+            bail!(
+                "Agent '{}' already exists, choose another template name with --name",
+                args.name
+            );
+        }
+        if self.fs.exists(&template_file) {
+            bail!("{} already exists", template_file.display());
+        }
+
+        let finals: HashMap<String, Manifest> = self
+            .merge()?
+            .into_iter()
+            .map(|m| (m.name.clone(), m))
+            .collect();
+        let in_scope = |name: &str| self.agents[name].has_local() == local;
+        let mut names: Vec<String> = if args.agents.is_empty() {
+            finals
+                .values()
+                .filter(|m| !m.template && in_scope(&m.name))
+                .map(|m| m.name.clone())
+                .collect()
+        } else {
+            for name in &args.agents {
+                if !finals.contains_key(name) {
+                    bail!("Agent '{name}' not found");
+                }
+                if !in_scope(name) {
+                    bail!(
+                        "Agent '{name}' is not defined in {}",
+                        generators_dir.display()
+                    );
+                }
+            }
+            args.agents.clone()
+        };
+        names.sort();
+        names.dedup();
+        if names.len() < 2 {
+            bail!("At least two agents are needed to extract a template");
+        }
+
+        let candidates: Vec<&Manifest> = names.iter().map(|n| &finals[n]).collect();
+        let own: Vec<Manifest> = names
+            .iter()
+            .map(|n| {
+                let [manifest, file] = scoped(&self.agents[n], local);
+                [file, manifest]
+                    .into_iter()
+                    .filter(|s| s.path.is_some())
+                    .fold(Manifest::new(n.clone(), false), |acc, s| {
+                        acc.merge(s.manifest.clone())
+                    })
+            })
+            .collect();
+        let mut providers: Vec<&Manifest> = Vec::new();
+        for name in &names {
+            for parent in self.inheritance_chain(name)? {
+                providers.push(&self.agents[&parent].merged);
+            }
+            for slot in out_of_scope(&self.agents[name], local) {
+                if slot.path.is_some() {
+                    providers.push(&slot.manifest);
+                }
+            }
+        }
+
+        let mut template = Manifest::new(args.name.clone(), true);
+        template.tools = shared_set(&candidates, &own, |m| &m.tools);
+        template.allowed_tools = shared_set(&candidates, &own, |m| &m.allowed_tools);
+        for (i, (_, list)) in template.native_tools.lists_mut().into_iter().enumerate() {
+            *list = shared_set(&candidates, &own, |m| m.native_tools.lists()[i].1);
+        }
+        template.mcp_servers = shared_entries(&candidates, &providers, |m| &m.mcp_servers);
+        template.resources = shared_entries(&candidates, &providers, |m| &m.resources);
+        template.skills = shared_entries(&candidates, &providers, |m| &m.skills);
+        template.knowledge = shared_entries(&candidates, &providers, |m| &m.knowledge);
+        if is_empty_template(&template) {
+            return Ok(None);
+        }
+
+        // Apply the proposal to a copy of the sources and merge again
+        let mut agents = self.agents.clone();
+        let mut edits = Vec::with_capacity(names.len());
+        let mut files: Vec<PathBuf> = Vec::new();
+        for name in &names {
+            let slots = agents
+                .get_mut(name)
+                .ok_or_else(|| crate::format_err!("Agent '{name}' not found"))?;
+            let mut removed = 0;
+            let [manifest_slot, file_slot] = scoped_mut(slots, local);
+            for slot in [&mut *file_slot, &mut *manifest_slot] {
+                if let Some(path) = slot.location() {
+                    removed += strip(&mut slot.manifest, &template);
+                    files.push(path);
+                }
+            }
+            if manifest_slot.path.is_none() {
+                manifest_slot.path = Some(KgAgentSource::manifest(kg_toml.clone(), local));
+                manifest_slot.manifest = Manifest::new(name.clone(), false);
+                files.push(kg_toml.clone());
+            }
+            if !manifest_slot.manifest.inherits.contains(&args.name) {
+                manifest_slot.manifest.inherits.push(args.name.clone());
+            }
+            slots.merged = merge_manifests(
+                name,
+                &slots.global_manifest,
+                &slots.local_manifest,
+                &slots.global_agent_file,
+                &slots.local_agent_file,
+                &ConfigLocation::Both(self.global_path.clone()),
+            );
+            edits.push(TemplateEdit {
+                agent: name.clone(),
+                removed,
+            });
+        }
+        agents.insert(args.name.clone(), AgentSourceSlots {
+            name: args.name.clone(),
+            merged: template.clone(),
+            ..Default::default()
+        });
+        files.push(kg_toml.clone());
+        files.sort();
+        files.dedup();
+
+        let proposed = Generator {
+            global_path: self.global_path.clone(),
+            agents,
+            fs: self.fs.clone(),
+            format: self.format,
+            policies: self.policies.clone(),
+            history_limit: self.history_limit,
+        };
+        let verified = self.verify_same_agents(&finals, &proposed)?;
+        let toml = toml_to_string(&KgAgentFileDoc::from(template.clone()))?;
+
+        let proposal = TemplateProposal {
+            template,
+            template_file,
+            toml,
+            edits,
+            files,
+            verified,
+        };
+        if args.write {
+            self.write_template(&proposal, &kg_toml, local).await?;
+        }
+        Ok(Some(proposal))
+    }
+
+    /// Check that `proposed` generates the same Kiro agents as `finals`
+    fn verify_same_agents(
+        &self,
+        finals: &HashMap<String, Manifest>,
+        proposed: &Generator,
+    ) -> Result<usize> {
+        let mut verified = 0;
+        for agent in proposed.merge()? {
+            if agent.template {
+                continue;
+            }
+            let Some(before) = finals.get(&agent.name) else {
+                bail!(
+                    "Agent '{}' appeared after extracting the template",
+                    agent.name
+                );
+            };
+            let before = KiroAgent::try_from(before)?.normalize();
+            let after = KiroAgent::try_from(&agent)?.normalize();
+            let diff = before.diff(&after);
+            if !diff.is_equal() {
+                bail!(
+                    "Extracting the template changes agent '{}':\n{}",
+                    agent.name,
+                    rediff::format_diff_compact(&diff)
+                );
+            }
+            verified += 1;
+        }
+        Ok(verified)
+    }
+
+    /// Read `path`, or the content already edited in `pending`
+    async fn pending_content(
+        &self,
+        pending: &mut BTreeMap<PathBuf, String>,
+        path: &Path,
+    ) -> Result<String> {
+        if let Some(content) = pending.get(path) {
+            return Ok(content.clone());
+        }
+        self.fs
+            .read_to_string(path)
+            .await
+            .wrap_err_with(|| format!("Failed to read {}", path.display()))
+    }
+
+    /// Compute the new content of every file touched by the proposal, then
+    /// replace them with temporary files renamed into place. The files
+    /// already replaced are restored when a write fails, so the agents never
+    /// inherit a template which was not written
+    async fn write_template(
+        &self,
+        proposal: &TemplateProposal,
+        kg_toml: &Path,
+        local: bool,
+    ) -> Result<()> {
+        let template = &proposal.template;
+        let mut pending: BTreeMap<PathBuf, String> = BTreeMap::new();
+        // Agents declared only in agent files are declared in kg.toml, after
+        // the template
+        let mut declared = Table::new();
+        declared.set_implicit(true);
+        let mut template_table = Table::new();
+        template_table.insert("template", toml_edit::value(true));
+        declared.insert(&template.name, Item::Table(template_table));
+        for edit in &proposal.edits {
+            let [manifest, file] = scoped(&self.agents[&edit.agent], local);
+            if let Some(path) = file.location() {
+                let content = self.pending_content(&mut pending, &path).await?;
+                let content = edit_toml(&path, &content, |doc| {
+                    strip_table(doc.as_table_mut(), template);
+                    Ok(())
+                })?;
+                pending.insert(path, content);
+            }
+            match manifest.location() {
+                Some(path) => {
+                    let content = self.pending_content(&mut pending, &path).await?;
+                    let content = edit_toml(&path, &content, |doc| {
+                        let table = doc
+                            .get_mut("agents")
+                            .and_then(Item::as_table_like_mut)
+                            .and_then(|agents| agents.get_mut(&edit.agent))
+                            .and_then(Item::as_table_like_mut)
+                            .ok_or_else(|| {
+                                crate::format_err!(
+                                    "Agent '{}' not found in {}",
+                                    edit.agent,
+                                    path.display()
+                                )
+                            })?;
+                        strip_table(table, template);
+                        add_inherits(table, &template.name);
+                        Ok(())
+                    })?;
+                    pending.insert(path, content);
+                }
+                None => {
+                    let mut table = Table::new();
+                    add_inherits(&mut table, &template.name);
+                    declared.insert(&edit.agent, Item::Table(table));
+                }
+            }
+        }
+        for (_, table) in declared.iter_mut() {
+            if let Some(table) = table.as_table_mut() {
+                table.decor_mut().set_prefix("\n");
+            }
+        }
+        let mut snippet = DocumentMut::new();
+        snippet.insert("agents", Item::Table(declared));
+        let manifest = match pending.get(kg_toml) {
+            Some(content) => Some(content.clone()),
+            None if self.fs.exists(kg_toml) => {
+                Some(self.pending_content(&mut pending, kg_toml).await?)
+            }
+            None => None,
+        };
+        pending.insert(
+            kg_toml.to_path_buf(),
+            appended_manifest(manifest, &snippet.to_string()),
+        );
+        pending.insert(proposal.template_file.clone(), proposal.toml.clone());
+
+        let mut replaced: Vec<(&PathBuf, Option<String>)> = Vec::new();
+        for (path, content) in &pending {
+            let previous = match self.fs.exists(path) {
+                true => Some(self.fs.read_to_string_sync(path)?),
+                false => None,
+            };
+            if let Err(e) = self.write_atomic(path, content).await {
+                self.restore(replaced).await;
+                return Err(e.wrap_err("restored the files written before the failure"));
+            }
+            replaced.push((path, previous));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::output::OutputFormat};
+
+    const TEAM: &str = r#"
+[agents.alpha]
+tools = ["read", "shell"]
+
+[agents.alpha.nativeTools.shell]
+allow = ["git status", "ls"]
+
+[agents.alpha.mcpServers.fetch]
+command = "uvx"
+args = ["mcp-server-fetch"]
+
+[agents.beta]
+tools = ["read", "write"]
+
+[agents.beta.nativeTools.shell]
+allow = ["git status", "cargo build"]
+
+[agents.beta.mcpServers.fetch]
+command = "uvx"
+args = ["mcp-server-fetch"]
+"#;
+
+    fn args(write: bool) -> ExtractTemplatesArgs {
+        ExtractTemplatesArgs {
+            name: String::from("shared"),
+            write,
+            agents: vec![String::from("alpha"), String::from("beta")],
+            ..Default::default()
+        }
+    }
+
+    async fn team(fs: &Fs) -> Result<Generator> {
+        fs.write(".kiro/generators/manifests/team.toml", TEAM)
+            .await?;
+        Generator::new(fs.clone(), ConfigLocation::Local, OutputFormat::Json)
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_extract_templates_proposes_shared_config() -> Result<()> {
+        let fs = Fs::new();
+        let generator = team(&fs).await?;
+        let proposal = generator
+            .extract_templates(&args(false))
+            .await?
+            .ok_or_else(|| crate::format_err!("expected a proposal"))?;
+
+        let template = &proposal.template;
+        assert!(template.template);
+        assert_eq!(template.tools, HashSet::from([String::from("read")]));
+        assert_eq!(
+            template.native_tools.shell.allows,
+            HashSet::from([String::from("git status")])
+        );
+        assert!(template.mcp_servers.contains_key("fetch"));
+        assert!(proposal.toml.contains("[mcpServers.fetch]"));
+        assert!(proposal.verified >= 2);
+        assert_eq!(proposal.edits.len(), 2);
+        assert!(proposal.edits.iter().all(|e| e.removed == 3));
+        assert!(!fs.exists(".kiro/generators/agents/shared.toml"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_extract_templates_write_keeps_generated_agents() -> Result<()> {
+        let fs = Fs::new();
+        let generator = team(&fs).await?;
+        let generated = |generator: &Generator| -> Result<Vec<_>> {
+            let mut agents = Vec::new();
+            for agent in generator.merge()? {
+                if agent.name == "alpha" || agent.name == "beta" {
+                    agents.push((agent.name.clone(), KiroAgent::try_from(&agent)?.normalize()));
+                }
+            }
+            agents.sort_by(|a, b| a.0.cmp(&b.0));
+            Ok(agents)
+        };
+        let before = generated(&generator)?;
+        generator.extract_templates(&args(true)).await?;
+
+        assert!(fs.exists(".kiro/generators/agents/shared.toml"));
+        let generator = Generator::new(fs.clone(), ConfigLocation::Local, OutputFormat::Json)?;
+        let alpha = &generator.agents["alpha"].merged;
+        assert_eq!(alpha.inherits, vec!["shared"]);
+        assert!(alpha.mcp_servers.is_empty());
+        assert!(generator.agents["shared"].merged.template);
+        let after = generated(&generator)?;
+        assert_eq!(after.len(), 2);
+        for ((name, before), (_, after)) in before.iter().zip(&after) {
+            assert!(before.diff(after).is_equal(), "{name} changed");
+        }
+
+        // Nothing is left to share
+        assert!(
+            generator
+                .extract_templates(&ExtractTemplatesArgs {
+                    name: String::from("again"),
+                    ..args(false)
+                })
+                .await?
+                .is_none()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_extract_templates_write_keeps_comments() -> Result<()> {
+        let fs = Fs::new();
+        let team = ".kiro/generators/manifests/team.toml";
+        fs.write(
+            team,
+            r#"# Team agents, reviewed by the platform group
+[agents.alpha]
+# alpha only reads
+tools = ["read", "shell"] # keep shell for git
+
+[agents.alpha.mcpServers.fetch]
+command = "uvx"
+args = ["mcp-server-fetch"]
+
+# beta is only declared through its sub-tables
+[agents.beta.mcpServers.fetch]
+command = "uvx"
+args = ["mcp-server-fetch"]
+
+[agents.beta.nativeTools.shell]
+allow = ["cargo build"] # builds only
+"#,
+        )
+        .await?;
+        let generator = Generator::new(fs.clone(), ConfigLocation::Local, OutputFormat::Json)?;
+        generator.extract_templates(&args(true)).await?;
+
+        let content = fs.read_to_string(team).await?;
+        for kept in [
+            "# Team agents, reviewed by the platform group",
+            "# alpha only reads",
+            "tools = [\"read\", \"shell\"] # keep shell for git",
+            "allow = [\"cargo build\"] # builds only",
+        ] {
+            assert!(content.contains(kept), "{kept} lost:\n{content}");
+        }
+        assert!(!content.contains("mcp-server-fetch"), "{content}");
+
+        let generator = Generator::new(fs.clone(), ConfigLocation::Local, OutputFormat::Json)?;
+        for name in ["alpha", "beta"] {
+            let merged = &generator.agents[name].merged;
+            assert_eq!(merged.inherits, vec!["shared"], "{name}:\n{content}");
+            assert!(merged.mcp_servers.is_empty(), "{name}");
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_extract_templates_declares_agent_file_agents() -> Result<()> {
+        let fs = Fs::new();
+        let global = PathBuf::from(crate::os::ACTIVE_USER_HOME)
+            .join(".kiro")
+            .join("generators");
+        fs.write(
+            global.join("manifests").join("team.toml"),
+            "[agents.alpha]\n[agents.beta]\n",
+        )
+        .await?;
+        for name in ["alpha", "beta"] {
+            fs.write(
+                format!(".kiro/generators/agents/{name}.toml"),
+                "tools = [\"read\"]\n",
+            )
+            .await?;
+        }
+        let generator = Generator::new(
+            fs.clone(),
+            ConfigLocation::Both(global.clone()),
+            OutputFormat::Json,
+        )?;
+        // Quotes and backslashes in the name stay part of the TOML string
+        let name = r#"team "core" \ v2"#;
+        generator
+            .extract_templates(&ExtractTemplatesArgs {
+                name: String::from(name),
+                ..args(true)
+            })
+            .await?;
+
+        let generator = Generator::new(fs, ConfigLocation::Both(global), OutputFormat::Json)?;
+        assert!(generator.agents[name].merged.template);
+        for agent in ["alpha", "beta"] {
+            assert_eq!(
+                generator.agents[agent].merged.inherits,
+                vec![name],
+                "{agent}"
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_extract_templates_write_restores_on_failure() -> Result<()> {
+        let fs = Fs::new();
+        let generator = team(&fs).await?;
+        let manifests = PathBuf::from(".kiro/generators/manifests");
+        // team.toml is written last, block its temporary file
+        fs.create_dir_all(manifests.join(format!(".team.toml{TMP_SUFFIX}/x")))
+            .await?;
+        let kg_toml = fs.read_to_string(manifests.join("kg.toml")).await?;
+
+        let Err(err) = generator.extract_templates(&args(true)).await else {
+            crate::bail!("write should fail");
+        };
+        assert!(format!("{err:?}").contains("restored"), "{err:?}");
+        assert_eq!(fs.read_to_string(manifests.join("team.toml")).await?, TEAM);
+        assert_eq!(fs.read_to_string(manifests.join("kg.toml")).await?, kg_toml);
+        assert!(!fs.exists(".kiro/generators/agents/shared.toml"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_extract_templates_rejects_existing_name() -> Result<()> {
+        let fs = Fs::new();
+        let generator = team(&fs).await?;
+        let result = generator
+            .extract_templates(&ExtractTemplatesArgs {
+                name: String::from("base"),
+                ..args(false)
+            })
+            .await;
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_strip_table_removes_emptied_tables() -> Result<()> {
+        let mut doc: DocumentMut = r#"
+tools = ["read"]
+[nativeTools.shell]
+allow = ["ls"]
+[mcpServers.fetch]
+command = "uvx"
+"#
+        .parse()?;
+        let mut template = Manifest::new(String::from("t"), true);
+        template.tools.insert(String::from("read"));
+        template
+            .native_tools
+            .shell
+            .allows
+            .insert(String::from("ls"));
+        template
+            .mcp_servers
+            .insert(String::from("fetch"), Default::default());
+        strip_table(doc.as_table_mut(), &template);
+        assert!(doc.as_table().is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,374 @@
+use {
+    super::*,
+    crate::kg_config::{KgFileResource, KgSkillResource},
+};
+
+/// Directory levels searched below the literal part of a resource glob
+const MAX_RESOURCE_DEPTH: usize = 16;
+/// File a `skill://` location must point at, directly or in the matched
+/// directory
+const SKILL_FILE: &str = "SKILL.md";
+
+/// Why the YAML frontmatter of a `SKILL.md` is unusable, `None` when it
+/// declares a `name` and a `description`
+fn skill_frontmatter_error(content: &str) -> Option<String> {
+    let Some(rest) = content.strip_prefix("---") else {
+        return Some(String::from("has no YAML frontmatter"));
+    };
+    let Some(end) = rest.find("\n---") else {
+        return Some(String::from("frontmatter is not closed by '---'"));
+    };
+    let frontmatter: HashMap<String, serde_json::Value> = match serde_yaml2::from_str(&rest[..end])
+    {
+        Ok(v) => v,
+        Err(e) => return Some(format!("frontmatter is not valid YAML: {e}")),
+    };
+    let missing: Vec<&str> = ["name", "description"]
+        .into_iter()
+        .filter(|key| {
+            frontmatter
+                .get(*key)
+                .and_then(|v| v.as_str())
+                .is_none_or(|v| v.trim().is_empty())
+        })
+        .collect();
+    match missing.is_empty() {
+        true => None,
+        false => Some(format!("frontmatter has no {}", missing.join(" or "))),
+    }
+}
+
+/// A resource of the merged agent, flattened for checking
+struct Location<'a> {
+    field: &'static str,
+    key: &'a str,
+    scheme: &'static str,
+    location: &'a str,
+    optional: bool,
+}
+
+fn locations(agent: &Manifest) -> Vec<Location<'_>> {
+    let files = agent.resources.iter().map(|(key, r)| {
+        let KgFileResource {
+            disabled,
+            optional,
+            locations,
+        } = r;
+        ("resources", "file", key, disabled, optional, locations)
+    });
+    let skills = agent.skills.iter().map(|(key, r)| {
+        let KgSkillResource {
+            disabled,
+            optional,
+            locations,
+        } = r;
+        ("skills", "skill", key, disabled, optional, locations)
+    });
+    let mut found: Vec<Location<'_>> = files
+        .chain(skills)
+        .filter(|(.., disabled, _, _)| **disabled != Some(true))
+        .flat_map(|(field, scheme, key, _, optional, locations)| {
+            locations.iter().map(move |location| Location {
+                field,
+                key,
+                scheme,
+                location,
+                optional: *optional == Some(true),
+            })
+        })
+        .collect();
+    found.sort_by(|a, b| (a.field, a.key, a.location).cmp(&(b.field, b.key, b.location)));
+    found
+}
+
+impl Generator {
+    /// Paths matching `pattern`, which is absolute or relative to the
+    /// current directory. Symlinked directories are not followed
+    fn expand_location(&self, pattern: &Path) -> Vec<PathBuf> {
+        let text = pattern.to_string_lossy();
+        if !has_wildcard(&text) {
+            return match self.fs.exists(pattern) {
+                true => vec![pattern.to_path_buf()],
+                false => Vec::new(),
+            };
+        }
+        let base: PathBuf = pattern
+            .components()
+            .take_while(|c| !has_wildcard(&c.as_os_str().to_string_lossy()))
+            .collect();
+        let mut found = Vec::new();
+        let mut pending = vec![(base, 0)];
+        while let Some((dir, depth)) = pending.pop() {
+            let dir_path = match dir.as_os_str().is_empty() {
+                true => Path::new("."),
+                false => dir.as_path(),
+            };
+            let Ok(entries) = self.fs.read_dir_sync(dir_path) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = dir.join(entry.file_name());
+                if path_matches_exactly(pattern, &path) {
+                    found.push(path.clone());
+                }
+                if depth < MAX_RESOURCE_DEPTH && entry.file_type().is_ok_and(|t| t.is_dir()) {
+                    pending.push((path, depth + 1));
+                }
+            }
+        }
+        found.sort();
+        found
+    }
+
+    /// Why a matched `skill://` path is not a usable skill
+    fn skill_error(&self, path: &Path) -> Option<String> {
+        let file = match path.file_name().is_some_and(|f| f == SKILL_FILE) {
+            true => path.to_path_buf(),
+            false => path.join(SKILL_FILE),
+        };
+        match self.fs.read_to_string_sync(&file) {
+            Ok(content) => skill_frontmatter_error(&content)
+                .map(|reason| format!("{}: {reason}", file.display())),
+            Err(_) => Some(format!("{} not found", file.display())),
+        }
+    }
+
+    /// Resolve every `file://` and `skill://` location of the merged agent,
+    /// relative to the current directory for local agents and to the home
+    /// directory for global ones. Fails when a location matches nothing or a
+    /// skill has no valid `SKILL.md`, returns the same problems as warnings
+    /// for `optional` resources. Templates are checked through the agents
+    /// inheriting them
+    pub(super) fn check_resources(&self, agent: &Manifest) -> Result<Vec<String>> {
+        #[cfg(not(test))]
+        let (cwd, home) = (
+            std::env::current_dir().wrap_err("Failed to read the current directory")?,
+            dirs::home_dir(),
+        );
+        #[cfg(test)]
+        let (cwd, home) = (
+            PathBuf::from("/"),
+            Some(PathBuf::from(crate::os::ACTIVE_USER_HOME)),
+        );
+        self.check_resources_in(agent, &cwd, home.as_deref())
+    }
+
+    /// [`Generator::check_resources`] with the relative locations of local
+    /// agents resolved against `cwd`
+    fn check_resources_in(
+        &self,
+        agent: &Manifest,
+        cwd: &Path,
+        home: Option<&Path>,
+    ) -> Result<Vec<String>> {
+        if agent.template {
+            return Ok(Vec::new());
+        }
+        let base = match self.is_local(&agent.name) {
+            true => cwd,
+            false => home.unwrap_or(cwd),
+        };
+
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        for l in locations(agent) {
+            let pattern = resolve_path(l.location, base, home);
+            let matches = self.expand_location(&pattern);
+            let mut problems = Vec::new();
+            if matches.is_empty() {
+                problems.push(String::from("matches nothing"));
+            } else if l.scheme == "skill" {
+                problems.extend(matches.iter().filter_map(|m| self.skill_error(m)));
+            }
+            for problem in problems {
+                let entry = format!("{}.{} '{}://{}'", l.field, l.key, l.scheme, l.location);
+                match l.optional {
+                    true => warnings.push(format!("{entry} is optional and {problem}")),
+                    false => errors.push(format!(
+                        "  {entry}{}: {problem}",
+                        self.declared_in(&agent.name, l.field, l.key)
+                    )),
+                }
+            }
+        }
+        if !errors.is_empty() {
+            crate::bail!(
+                "Agent '{}' has invalid resources\n{}",
+                agent.name,
+                errors.join("\n")
+            );
+        }
+        Ok(warnings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SKILL: &str = "---\nname: review\ndescription: Review a pull request\n---\n# Review\n";
+
+    #[test]
+    fn test_skill_frontmatter() {
+        assert_eq!(skill_frontmatter_error(SKILL), None);
+        assert_eq!(
+            skill_frontmatter_error("# Review\n").as_deref(),
+            Some("has no YAML frontmatter")
+        );
+        assert_eq!(
+            skill_frontmatter_error("---\nname: review\n---\n").as_deref(),
+            Some("frontmatter has no description")
+        );
+        assert!(
+            skill_frontmatter_error("---\nname: [review\n---\n")
+                .is_some_and(|e| e.starts_with("frontmatter is not valid YAML"))
+        );
+    }
+
+    fn agent<'a>(merged: &'a [Manifest], name: &str) -> &'a Manifest {
+        merged.iter().find(|a| a.name == name).expect(name)
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_check_resources() -> Result<()> {
+        let fs = Fs::new();
+        fs.create_dir_all("docs/guides").await?;
+        fs.write("docs/guides/setup.md", "# Setup").await?;
+        fs.create_dir_all(".kiro/skills/review").await?;
+        fs.write(".kiro/skills/review/SKILL.md", SKILL).await?;
+        fs.create_dir_all(".kiro/skills/broken").await?;
+        fs.write(".kiro/skills/broken/SKILL.md", "# Broken").await?;
+        let generator = manifest_generator(
+            fs,
+            "resources",
+            r#"
+            [agents.resources-base]
+            template = true
+            resources.missing.locations = ["notes/*.md"]
+
+            [agents.resources-ok]
+            resources.docs.locations = ["docs/**/*.md", "docs/guides/setup.md"]
+            skills.review.locations = [".kiro/skills/review/SKILL.md", ".kiro/skills/rev*"]
+
+            [agents.resources-ok.resources.notes]
+            locations = ["notes/todo.md"]
+            optional = true
+
+            [agents.resources-bad]
+            inherits = ["resources-base"]
+            resources.docs.locations = ["docs/*.txt"]
+            skills.all.locations = [".kiro/skills/*"]
+            "#,
+        )
+        .await?;
+        let merged = generator.merge()?;
+        assert_eq!(
+            generator.check_resources(agent(&merged, "resources-ok"))?,
+            vec!["resources.notes 'file://notes/todo.md' is optional and matches nothing"]
+        );
+        assert!(
+            generator
+                .check_resources(agent(&merged, "resources-base"))?
+                .is_empty()
+        );
+
+        let err = format!(
+            "{:?}",
+            generator
+                .check_resources(agent(&merged, "resources-bad"))
+                .expect_err("missing resources accepted")
+        );
+        for expected in [
+            "Agent 'resources-bad' has invalid resources",
+            "resources.docs 'file://docs/*.txt' (local-manifest://",
+            "manifests/resources.toml): matches nothing",
+            "resources.missing 'file://notes/*.md'",
+            "skills.all 'skill://.kiro/skills/*'",
+            ".kiro/skills/broken/SKILL.md: has no YAML frontmatter",
+        ] {
+            assert!(err.contains(expected), "{expected}: {err}");
+        }
+        assert!(!err.contains("skills/review"), "{err}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_check_resources_outside_cwd() -> Result<()> {
+        let fs = Fs::new();
+        fs.create_dir_all("project/docs").await?;
+        fs.write("project/docs/setup.md", "# Setup").await?;
+        fs.create_dir_all("shared").await?;
+        fs.write("shared/notes.md", "# Notes").await?;
+        let generator = manifest_generator(
+            fs,
+            "resources",
+            r#"
+            [agents.resources-parent]
+            resources.docs.locations = ["docs/setup.md", "../shared/notes.md", "./../shared/*.md"]
+            "#,
+        )
+        .await?;
+        let merged = generator.merge()?;
+        let agent = agent(&merged, "resources-parent");
+        assert!(
+            generator
+                .check_resources_in(agent, Path::new("/project"), None)?
+                .is_empty()
+        );
+        // `..` climbs above the current directory instead of being dropped
+        let err = format!(
+            "{:?}",
+            generator
+                .check_resources_in(agent, Path::new("/project/docs"), None)
+                .expect_err("missing resources accepted")
+        );
+        assert!(err.contains("'file://docs/setup.md'"), "{err}");
+        assert!(err.contains("'file://../shared/notes.md'"), "{err}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_check_resources_global_home() -> Result<()> {
+        let fs = Fs::new();
+        let home = PathBuf::from(crate::os::ACTIVE_USER_HOME);
+        let global = home.join(".kiro").join("generators");
+        fs.write(
+            global.join("manifests").join("resources.toml"),
+            r#"
+            [agents.resources-global]
+            resources.readme.locations = ["README.md", "notes/*.md", "~/notes/*.md"]
+            "#,
+        )
+        .await?;
+        let generator = Generator::new(
+            fs,
+            ConfigLocation::Global(global),
+            crate::output::OutputFormat::Json,
+        )?;
+        let merged = generator.merge()?;
+        let agent = agent(&merged, "resources-global");
+        // README.md exists in the current directory, not in the home directory
+        let err = format!(
+            "{:?}",
+            generator
+                .check_resources(agent)
+                .expect_err("missing home resource accepted")
+        );
+        for expected in [
+            "'file://README.md'",
+            "'file://notes/*.md'",
+            "'file://~/notes/*.md'",
+        ] {
+            assert!(err.contains(expected), "{expected}: {err}");
+        }
+
+        generator.fs.create_dir_all(home.join("notes")).await?;
+        generator.fs.write(home.join("notes/todo.md"), "").await?;
+        generator.fs.write(home.join("README.md"), "").await?;
+        assert!(generator.check_resources(agent)?.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,299 @@
+use {
+    super::*,
+    crate::{
+        kiro::ExecuteShellTool as KiroShellTool,
+        tree::{ExplainEntry, Provenance, explain},
+    },
+    regex::Regex,
+};
+
+/// Compile a shell `allow` or `deny` entry. Kiro matches the pattern against
+/// the whole command.
+pub fn shell_pattern(pattern: &str) -> std::result::Result<Regex, regex::Error> {
+    Regex::new(&format!(r"\A(?:{pattern})\z"))
+}
+
+/// What Kiro does when the agent runs a shell command or accesses a path
+#[derive(Facet, Debug, Clone, Copy, PartialEq, Eq)]
+#[facet(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum Decision {
+    Allowed,
+    Denied,
+    /// The user is asked before running the command
+    Prompted,
+}
+
+impl Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Allowed => write!(f, "allowed"),
+            Self::Denied => write!(f, "denied"),
+            Self::Prompted => write!(f, "prompted"),
+        }
+    }
+}
+
+/// Result of `kg check-command`
+#[derive(Facet, Debug, Clone)]
+pub struct CommandCheck {
+    pub agent: String,
+    pub command: String,
+    pub decision: Decision,
+    /// Pattern which decided, `None` when no pattern matched
+    pub pattern: Option<String>,
+    /// `nativeTools.shell` list declaring the pattern
+    pub list: Option<String>,
+    /// Agent or ancestor declaring the pattern
+    pub source: Option<String>,
+    pub path: Option<String>,
+    /// Why the command is decided without a pattern
+    pub reason: Option<String>,
+}
+
+/// Where `pattern` of a `nativeTools` list was declared
+fn declared<'a>(entries: &'a [ExplainEntry], pattern: &str) -> Option<&'a ExplainEntry> {
+    entries
+        .iter()
+        .find(|e| e.value == pattern && matches!(e.status, Provenance::Added | Provenance::Forced))
+}
+
+/// Rule of a `nativeTools` list and the agent and file declaring it
+pub(super) struct Attribution {
+    pub field: String,
+    pub source: String,
+    pub path: String,
+}
+
+impl Generator {
+    /// Where `value` of the list or map `field` of `agent` was declared,
+    /// `None` when no ancestor adds it
+    pub(super) fn declaration(
+        &self,
+        agent: &str,
+        field: &str,
+        value: &str,
+    ) -> Result<Option<Attribution>> {
+        let report = explain(self, agent, field)?;
+        Ok(declared(&report.entries, value).map(|entry| Attribution {
+            source: entry.agent.clone(),
+            path: format!("{}://{}", entry.source_type, entry.path),
+            field: field.to_string(),
+        }))
+    }
+
+    /// Source slot declaring `value` of `field` for `agent`, formatted for
+    /// error messages
+    pub(super) fn declared_in(&self, agent: &str, field: &str, value: &str) -> String {
+        match self.declaration(agent, field, value) {
+            Ok(Some(declaration)) => format!(" ({})", declaration.path),
+            _ => String::new(),
+        }
+    }
+
+    /// Find which of the `lists` of `tool` declares `pattern` for `agent`
+    pub(super) fn attribute(
+        &self,
+        agent: &str,
+        tool: &str,
+        lists: &[&str],
+        pattern: &str,
+    ) -> Result<Option<Attribution>> {
+        for list in lists {
+            let field = format!("nativeTools.{tool}.{list}");
+            if let Some(attribution) = self.declaration(agent, &field, pattern)? {
+                return Ok(Some(attribution));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Generator {
+    /// Fail when a shell `allow`, `deny` or `forceAllow` entry of the merged
+    /// agent is not a valid regex, naming the file declaring it
+    pub(super) fn validate_shell_patterns(&self, agent: &Manifest) -> Result<()> {
+        let shell = agent.get_tool_shell();
+        let mut invalid = Vec::new();
+        for (list, patterns) in [
+            ("allow", &shell.allows),
+            ("deny", &shell.denies),
+            ("forceAllow", &shell.force_allow),
+        ] {
+            let mut patterns: Vec<&String> = patterns.iter().collect();
+            patterns.sort();
+            for pattern in patterns {
+                let Err(e) = shell_pattern(pattern) else {
+                    continue;
+                };
+                let field = format!("nativeTools.shell.{list}");
+                let source = self
+                    .attribute(&agent.name, "shell", &[list], pattern)
+                    .ok()
+                    .flatten()
+                    .map(|rule| format!(" ({})", rule.path))
+                    .unwrap_or_default();
+                let reason = e.to_string();
+                let reason = reason.lines().last().unwrap_or_default().trim();
+                invalid.push(format!("  {field} '{pattern}'{source}: {reason}"));
+            }
+        }
+        if !invalid.is_empty() {
+            crate::bail!(
+                "Agent '{}': invalid shell pattern\n{}",
+                agent.name,
+                invalid.join("\n")
+            );
+        }
+        Ok(())
+    }
+
+    /// Decide whether `agent` may run `command` under its merged shell
+    /// settings: deny patterns win, then allow patterns, then
+    /// `denyByDefault`, otherwise the user is asked
+    pub fn check_command(&self, agent: &str, command: &str) -> Result<CommandCheck> {
+        let merged = self
+            .merge()?
+            .into_iter()
+            .find(|a| a.name == agent)
+            .ok_or_else(|| crate::format_err!("Agent '{agent}' not found"))?;
+        self.validate_shell_patterns(&merged)?;
+        let shell = merged.get_tool_shell();
+        let kiro = KiroShellTool::from(&merged.native_tools);
+
+        let mut check = CommandCheck {
+            agent: agent.to_string(),
+            command: command.to_string(),
+            decision: Decision::Prompted,
+            pattern: None,
+            list: None,
+            source: None,
+            path: None,
+            reason: None,
+        };
+        let matching = |patterns: &std::collections::BTreeSet<String>| -> Result<Option<String>> {
+            let mut patterns: Vec<&String> = patterns.iter().collect();
+            patterns.sort();
+            for pattern in patterns {
+                if shell_pattern(pattern)?.is_match(command) {
+                    return Ok(Some(pattern.clone()));
+                }
+            }
+            Ok(None)
+        };
+
+        let (decision, pattern, lists): (_, _, &[&str]) =
+            if let Some(p) = matching(&kiro.denied_commands)? {
+                (Decision::Denied, p, &["deny"])
+            } else if let Some(p) = matching(&kiro.allowed_commands)? {
+                (Decision::Allowed, p, &["forceAllow", "allow"])
+            } else {
+                check.decision = match kiro.deny_by_default {
+                    true => Decision::Denied,
+                    false => Decision::Prompted,
+                };
+                check.reason = Some(
+                    match (kiro.deny_by_default, shell.auto_allow_readonly) {
+                        (true, _) => "no pattern matches and denyByDefault is set",
+                        (false, Some(true)) => {
+                            "no pattern matches, Kiro runs it without asking only if it is \
+                             read-only (autoAllowReadonly)"
+                        }
+                        (false, _) => "no pattern matches",
+                    }
+                    .to_string(),
+                );
+                return Ok(check);
+            };
+
+        check.decision = decision;
+        if let Some(rule) = self.attribute(agent, "shell", lists, &pattern)? {
+            check.list = Some(rule.field);
+            check.source = Some(rule.source);
+            check.path = Some(rule.path);
+        }
+        check.pattern = Some(pattern);
+        Ok(check)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_pattern_matches_whole_command() -> Result<()> {
+        let re = shell_pattern("git push .*")?;
+        assert!(re.is_match("git push origin main"));
+        assert!(!re.is_match("echo && git push origin main"));
+        assert!(shell_pattern("git push (.*").is_err());
+        // Alternation stays anchored on both sides
+        let re = shell_pattern("ls|pwd")?;
+        assert!(!re.is_match("ls; rm -rf /"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_check_command() -> Result<()> {
+        let generator = manifest_generator(
+            Fs::new(),
+            "shell",
+            r#"
+            [agents.shell-base]
+            template = true
+            nativeTools.shell.allow = ["git .*"]
+            nativeTools.shell.deny = ["git push .*"]
+
+            [agents.shell-child]
+            inherits = ["shell-base"]
+            nativeTools.shell.allow = ["cargo (build|test)"]
+            "#,
+        )
+        .await?;
+
+        let check = generator.check_command("shell-child", "git push origin main")?;
+        assert_eq!(check.decision, Decision::Denied);
+        assert_eq!(check.pattern.as_deref(), Some("git push .*"));
+        assert_eq!(check.source.as_deref(), Some("shell-base"));
+        assert_eq!(check.list.as_deref(), Some("nativeTools.shell.deny"));
+
+        let check = generator.check_command("shell-child", "cargo test")?;
+        assert_eq!(check.decision, Decision::Allowed);
+        assert_eq!(check.source.as_deref(), Some("shell-child"));
+
+        let check = generator.check_command("shell-child", "git status")?;
+        assert_eq!(check.decision, Decision::Allowed);
+        assert_eq!(check.source.as_deref(), Some("shell-base"));
+
+        let check = generator.check_command("shell-child", "rm -rf target")?;
+        assert_eq!(check.decision, Decision::Prompted);
+        assert!(check.pattern.is_none());
+        assert!(generator.check_command("missing", "ls").is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_invalid_shell_pattern_fails_validation() -> Result<()> {
+        let generator = manifest_generator(
+            Fs::new(),
+            "shell",
+            r#"
+            [agents.shell-invalid]
+            nativeTools.shell.allow = ["git push (.*"]
+            "#,
+        )
+        .await?;
+        let err = generator
+            .write_all(true, false, false)
+            .await
+            .err()
+            .expect("invalid pattern accepted");
+        let err = format!("{err:?}");
+        assert!(err.contains("shell-invalid"), "{err}");
+        assert!(err.contains("git push (.*"), "{err}");
+        assert!(err.contains("shell.toml"), "{err}");
+        Ok(())
+    }
+}
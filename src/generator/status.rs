@@ -0,0 +1,251 @@
+use {
+    super::{
+        import::KG_HELPER_AGENT,
+        lock::{Orphan, content_hash},
+        *,
+    },
+    facet_value::Value,
+    rediff::{LeafChange, LeafChangeKind},
+    std::collections::BTreeSet,
+};
+
+/// Whether the deployed agent JSON matches the TOML sources
+#[derive(Facet, Debug, Clone, Copy, PartialEq, Eq)]
+#[facet(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum SyncState {
+    /// Not generated yet
+    New,
+    Same,
+    Changed,
+}
+
+impl Display for SyncState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::New => write!(f, "new"),
+            Self::Same => write!(f, "same"),
+            Self::Changed => write!(f, "changed"),
+        }
+    }
+}
+
+#[derive(Facet, Debug, Clone)]
+pub struct AgentStatus {
+    pub name: String,
+    pub destination: String,
+    pub exists: bool,
+    pub state: SyncState,
+    /// The JSON changed since `kg generate` wrote it
+    pub edited: bool,
+}
+
+#[derive(Facet, Debug, Clone, Default)]
+pub struct StatusReport {
+    pub agents: Vec<AgentStatus>,
+    /// Generated JSON of agents which no longer exist
+    pub orphans: Vec<Orphan>,
+    /// Agent JSON in the destination directory not generated by kg
+    pub unmanaged: Vec<String>,
+}
+
+impl StatusReport {
+    /// Nothing to generate, no hand edits and no stale files
+    pub fn in_sync(&self) -> bool {
+        self.orphans.is_empty()
+            && self
+                .agents
+                .iter()
+                .all(|a| a.state == SyncState::Same && !a.edited)
+    }
+}
+
+/// How a field changed between the deployed and the generated agent
+#[derive(Facet, Debug, Clone, Copy, PartialEq, Eq)]
+#[facet(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// One changed field of an agent, `old` is the deployed value
+#[derive(Facet, Debug, Clone)]
+pub struct FieldChange {
+    /// Dot path of the field in the normalized agent
+    pub path: String,
+    pub kind: ChangeKind,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+impl TryFrom<&LeafChange<'_, '_>> for FieldChange {
+    type Error = color_eyre::Report;
+
+    fn try_from(change: &LeafChange<'_, '_>) -> Result<Self> {
+        let value = |peek| {
+            facet_value::peek_to_value(peek)
+                .map_err(|e| crate::format_err!("failed to convert {}: {e}", change.path))
+        };
+        let (kind, old, new) = match &change.kind {
+            LeafChangeKind::Insert { value: v } => (ChangeKind::Added, None, Some(value(*v)?)),
+            LeafChangeKind::Delete { value: v } => (ChangeKind::Removed, Some(value(*v)?), None),
+            LeafChangeKind::Replace { from, to } => {
+                (ChangeKind::Modified, Some(value(*from)?), Some(value(*to)?))
+            }
+        };
+        Ok(Self {
+            path: change.path.to_string(),
+            kind,
+            old,
+            new,
+        })
+    }
+}
+
+/// Result of `kg diff` for one agent
+#[derive(Facet, Debug, Clone)]
+pub struct AgentDiffReport {
+    pub name: String,
+    pub destination: String,
+    pub state: SyncState,
+    /// One entry per changed field, `path: old → new`
+    pub changes: Vec<String>,
+    /// The same changes with their old and new values
+    pub fields: Vec<FieldChange>,
+}
+
+/// Machine-readable report of `kg diff --check` and `kg diff --format json`
+#[derive(Facet, Debug, Clone, Default)]
+pub struct DiffReport {
+    pub agents: Vec<AgentDiffReport>,
+    /// Requested agents not found in the current scope
+    pub missing: Vec<String>,
+}
+
+impl DiffReport {
+    /// No new or changed agents and every requested agent was found
+    pub fn in_sync(&self) -> bool {
+        self.missing.is_empty() && self.agents.iter().all(|a| a.state == SyncState::Same)
+    }
+}
+
+impl Generator {
+    /// Compare every concrete agent in scope with its deployed JSON
+    #[tracing::instrument(level = "info", skip(self))]
+    pub fn status(&self) -> Result<StatusReport> {
+        let all_agents = !self.contains_local_agents();
+        let agents: Vec<Manifest> = self
+            .merge()?
+            .into_iter()
+            .filter(|a| !a.template && (all_agents || self.is_local(&a.name)))
+            .collect();
+        let dir = self.managed_dir();
+        let lock = self.read_lock(&dir)?;
+
+        let mut report = StatusReport::default();
+        for agent in &agents {
+            let destination = self
+                .destination_dir(&agent.name)
+                .join(format!("{}.json", agent.name));
+            let exists = self.fs.exists(&destination);
+            let generated = KiroAgent::try_from(agent)?;
+            let state = match self.compute_diff(
+                &agent.name,
+                &generated,
+                crate::output::DiffFormatArg::Agent,
+            )? {
+                AgentDiff::New => SyncState::New,
+                AgentDiff::Same => SyncState::Same,
+                AgentDiff::Changed(_) => SyncState::Changed,
+            };
+            let edited = match lock.agents.get(&agent.name) {
+                Some(entry) if exists => {
+                    content_hash(&self.fs.read_to_string_sync(&destination)?) != entry.hash
+                }
+                _ => false,
+            };
+            report.agents.push(AgentStatus {
+                name: agent.name.clone(),
+                destination: destination.display().to_string(),
+                exists,
+                state,
+                edited,
+            });
+        }
+        report.orphans = self.orphans()?;
+
+        if self.fs.exists(&dir) {
+            let known: BTreeSet<&str> = agents
+                .iter()
+                .map(|a| a.name.as_str())
+                .chain(lock.agents.keys().map(String::as_str))
+                .chain([KG_HELPER_AGENT])
+                .collect();
+            for entry in self.fs.read_dir_sync(&dir)? {
+                let path = dir.join(entry?.file_name());
+                if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                    continue;
+                }
+                if let Some(name) = path.file_stem().and_then(|s| s.to_str())
+                    && !known.contains(name)
+                {
+                    report.unmanaged.push(path.display().to_string());
+                }
+            }
+            report.unmanaged.sort();
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::output::OutputFormat};
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_status_reports_drift() -> Result<()> {
+        let fs = Fs::new();
+        let manifest = ".kiro/generators/manifests/status.toml";
+        fs.write(manifest, "[agents.status-a]\ndescription = \"a\"\n")
+            .await?;
+        let generator = Generator::new(fs.clone(), ConfigLocation::Local, OutputFormat::Json)?;
+        let report = generator.status()?;
+        let a = report
+            .agents
+            .iter()
+            .find(|a| a.name == "status-a")
+            .expect("status-a not found");
+        assert_eq!(a.state, SyncState::New);
+        assert!(!a.exists);
+        assert!(!report.in_sync());
+
+        generator.write_all(false, false, false).await?;
+        let report = generator.status()?;
+        assert!(report.in_sync(), "{report:?}");
+
+        let dir = PathBuf::from(".kiro").join("agents");
+        fs.write(dir.join("mine.json"), "{}").await?;
+        let json = fs.read_to_string_sync(dir.join("status-a.json"))?;
+        fs.write(dir.join("status-a.json"), json.replace("  ", "    "))
+            .await?;
+        fs.write(manifest, "[agents.status-a]\ndescription = \"changed\"\n")
+            .await?;
+        let generator = Generator::new(fs, ConfigLocation::Local, OutputFormat::Json)?;
+        let report = generator.status()?;
+        let a = report
+            .agents
+            .iter()
+            .find(|a| a.name == "status-a")
+            .expect("status-a not found");
+        assert_eq!(a.state, SyncState::Changed);
+        assert!(a.edited);
+        assert_eq!(report.unmanaged, vec![String::from(
+            ".kiro/agents/mine.json"
+        )]);
+        assert!(!report.in_sync());
+        Ok(())
+    }
+}
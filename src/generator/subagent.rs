@@ -0,0 +1,99 @@
+use {
+    super::*,
+    crate::{glob_match, sorted},
+};
+
+impl Generator {
+    /// Names of the concrete agents kg knows about
+    fn known_agents(&self) -> Vec<&str> {
+        let mut known: Vec<&str> = self
+            .agents
+            .iter()
+            .filter(|(_, slots)| !slots.merged.template)
+            .map(|(name, _)| name.as_str())
+            .collect();
+        known.sort();
+        known
+    }
+
+    /// Subtract `subagents.deny` from the allowed and trusted globs it
+    /// overlaps, see [`crate::SubagentConfig::expand_denied_globs`]
+    pub(super) fn expand_subagent_globs(&self, agent: &mut Manifest) {
+        for glob in agent.subagents.expand_denied_globs(&self.known_agents()) {
+            tracing::debug!(
+                agent = %agent.name,
+                "subagents '{glob}' expanded to the known agents it matches without the denied ones"
+            );
+        }
+    }
+
+    /// Subagent entries of the merged agent which match no agent kg knows
+    /// about
+    pub(super) fn subagent_warnings(&self, agent: &Manifest) -> Vec<String> {
+        let known = self.known_agents();
+        let subagents = &agent.subagents;
+        let mut warnings = Vec::new();
+        for (list, entries) in [
+            ("allow", subagents.available()),
+            ("trusted", subagents.trusted()),
+        ] {
+            for entry in sorted(entries) {
+                if !known.iter().any(|name| glob_match(&entry, name)) {
+                    warnings.push(format!(
+                        "subagents.{list} '{entry}' does not match any agent"
+                    ));
+                }
+            }
+        }
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::output::OutputFormat};
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_subagent_warnings() -> Result<()> {
+        let fs = Fs::new();
+        fs.write(
+            ".kiro/generators/manifests/subagents.toml",
+            r#"
+            [agents.docs-api]
+            [agents.docs-internal]
+            [agents.docs-template]
+            template = true
+
+            [agents.orchestrator]
+            subagents.allow = ["docs-*", "pr-review", "docs-template"]
+            subagents.deny = ["docs-internal"]
+            subagents.trusted = ["docs-api"]
+            "#,
+        )
+        .await?;
+        let generator = Generator::new(fs, ConfigLocation::Local, OutputFormat::Json)?;
+        let merged = generator.merge()?;
+        let agent = merged
+            .iter()
+            .find(|a| a.name == "orchestrator")
+            .expect("orchestrator");
+
+        assert_eq!(generator.subagent_warnings(agent), vec![
+            "subagents.allow 'docs-template' does not match any agent",
+            "subagents.allow 'pr-review' does not match any agent",
+        ]);
+
+        let kiro = KiroAgent::try_from(agent)?;
+        let subagent = kiro.tools_settings.get("subagent").expect("subagent");
+        let json = facet_json::to_string(subagent)?;
+        // The denied agent is subtracted from the glob
+        assert!(
+            json.contains("\"availableAgents\":[\"docs-api\",\"docs-template\",\"pr-review\"]"),
+            "{json}"
+        );
+        assert!(json.contains("\"trustedAgents\":[\"docs-api\"]"), "{json}");
+        assert!(!json.contains("allowedAgents"), "{json}");
+        Ok(())
+    }
+}
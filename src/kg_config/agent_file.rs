@@ -3,7 +3,9 @@ use {
         KgCustomToolConfig,
         KgFileResource,
         KgKnowledge,
+        KgPrompt,
         KgSkillResource,
+        LintConfig,
         Result,
         SubagentConfig,
         manifest::*,
@@ -35,7 +37,7 @@ pub struct KgAgentFileDoc {
                        agent. This should be seen as the same category of context as a system \
                        prompt."
     )]
-    pub prompt: Option<String>,
+    pub prompt: Option<KgPrompt>,
     #[kg_mapping(
         kiro_schema_path = "#/properties/resources",
         description = "File resources to include in the agent's context"
@@ -129,10 +131,50 @@ pub struct KgAgentFileDoc {
     pub welcome_message: Option<String>,
     #[kg_mapping(
         kiro_schema_path = "#/properties/toolsSettings",
-        description = "Allow/deny lists for subagents, emitted into toolsSettings.subagent."
+        description = "Subagents the agent may run (allow, deny) and run without asking \
+                       (trusted), emitted into toolsSettings.subagent as availableAgents and \
+                       trustedAgents."
     )]
     #[facet(default)]
     pub subagents: SubagentConfig,
+    // Variables for ${NAME} interpolation. kg only, no kg_mapping.
+    #[facet(default)]
+    pub vars: HashMap<String, String>,
+    // Rules suppressed by `kg lint`. kg only, no kg_mapping.
+    #[facet(default)]
+    pub lint: LintConfig,
+}
+
+/// `$schema` written at the top of generated agent files
+pub const AGENT_SCHEMA_URL: &str = "https://kiro-generator.io/agent.json";
+
+impl From<Manifest> for KgAgentFileDoc {
+    /// Inverse of [`Manifest::from_file_source`]. `name`, `template` and
+    /// `inherits` only live in manifests and are dropped.
+    fn from(manifest: Manifest) -> Self {
+        Self {
+            schema: Some(AGENT_SCHEMA_URL.to_string()),
+            description: manifest.description,
+            prompt: manifest.prompt,
+            resources: manifest.resources,
+            skills: manifest.skills,
+            knowledge: manifest.knowledge,
+            include_mcp_json: manifest.include_mcp_json,
+            tools: manifest.tools,
+            allowed_tools: manifest.allowed_tools,
+            model: manifest.model,
+            hooks: manifest.hooks,
+            mcp_servers: manifest.mcp_servers,
+            tool_aliases: manifest.tool_aliases,
+            native_tools: manifest.native_tools,
+            tool_settings: manifest.tool_settings,
+            keyboard_shortcut: manifest.keyboard_shortcut,
+            welcome_message: manifest.welcome_message,
+            subagents: manifest.subagents,
+            vars: manifest.vars,
+            lint: manifest.lint,
+        }
+    }
 }
 
 impl Manifest {
@@ -142,11 +184,13 @@ impl Manifest {
         path: impl AsRef<Path>,
         template: bool,
     ) -> Option<Result<Self>> {
+        let path = path.as_ref();
         if let Some(result) = super::toml_parse_path::<KgAgentFileDoc>(fs, path) {
             match result {
                 Err(e) => return Some(Err(e)),
                 Ok(file_source) => {
-                    return Some(Ok(Self::from_file_source(name, file_source, template)));
+                    let mut manifest = Self::from_file_source(name, file_source, template);
+                    return Some(manifest.load_prompt(fs, path).map(|_| manifest));
                 }
             }
         };
@@ -179,6 +223,8 @@ impl Manifest {
             keyboard_shortcut: file_source.keyboard_shortcut,
             welcome_message: file_source.welcome_message,
             subagents: file_source.subagents,
+            vars: file_source.vars,
+            lint: file_source.lint,
         }
     }
 }
@@ -0,0 +1,390 @@
+use {
+    super::{
+        KgAgentFileDoc,
+        KgCustomToolConfig,
+        KgFileResource,
+        KgKnowledge,
+        KgPrompt,
+        KgSkillResource,
+        McpServerState,
+        McpValue,
+        agent_file::AGENT_SCHEMA_URL,
+        native::NativeTools,
+    },
+    crate::kiro::{
+        AwsTool,
+        ExecuteShellTool,
+        GlobTool,
+        GrepTool,
+        KgHook,
+        KiroAgent,
+        Knowledge,
+        ReadTool,
+        SubagentTool,
+        ToolTarget,
+        WebFetchTool,
+        WriteTool,
+    },
+    facet::Facet,
+    std::collections::{BTreeMap, HashMap},
+};
+
+/// Name of the resource and skill tables created for imported locations
+const IMPORTED_RESOURCE: &str = "default";
+
+/// Decode a toolsSettings entry into its typed Kiro representation.
+///
+/// Returns `None` when the value has unknown fields or is empty, in which case
+/// the caller keeps the raw value in `toolSettings`.
+fn decode_tool<T>(value: &facet_value::Value) -> Option<T>
+where
+    T: for<'a> Facet<'a> + Default + PartialEq,
+{
+    let json = facet_json::to_string(value).ok()?;
+    facet_json::from_str::<T>(&json)
+        .ok()
+        .filter(|tool| *tool != T::default())
+}
+
+fn true_or_none(value: bool) -> Option<bool> {
+    value.then_some(true)
+}
+
+fn literals(map: &BTreeMap<String, String>) -> HashMap<String, McpValue> {
+    map.iter()
+        .map(|(k, v)| (k.clone(), McpValue::from(v.as_str())))
+        .collect()
+}
+
+/// Derive a stable hook name from its position in the trigger and its
+/// command. The zero-padded index keeps the hooks in their original order,
+/// as `kg generate` writes them sorted by name
+fn hook_name(index: usize, count: usize, command: &str) -> String {
+    let slug: String = command
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .take(4)
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase();
+    let width = count.to_string().len().max(2);
+    match slug.is_empty() {
+        true => format!("{:0width$}-hook", index + 1),
+        false => format!("{:0width$}-{slug}", index + 1),
+    }
+}
+
+impl NativeTools {
+    /// Reverse of the `From<&NativeTools>` conversions. Consumes the known
+    /// entries from `settings`, leaving anything it cannot represent.
+    fn from_tools_settings(settings: &mut BTreeMap<String, facet_value::Value>) -> Self {
+        let mut native = Self::default();
+        if let Some(raw) = settings.remove(ToolTarget::Shell.as_ref()) {
+            match decode_tool::<ExecuteShellTool>(&raw) {
+                Some(tool) => {
+                    native.shell.allows = tool.allowed_commands.into_iter().collect();
+                    native.shell.denies = tool.denied_commands.into_iter().collect();
+                    native.shell.deny_by_default = true_or_none(tool.deny_by_default);
+                    native.shell.auto_allow_readonly = tool.auto_allow_readonly;
+                }
+                None => _ = settings.insert(ToolTarget::Shell.to_string(), raw),
+            }
+        }
+        if let Some(raw) = settings.remove(ToolTarget::Aws.as_ref()) {
+            match decode_tool::<AwsTool>(&raw) {
+                Some(tool) => {
+                    native.aws.allows = tool.allowed_services.into_iter().collect();
+                    native.aws.denies = tool.denied_services.into_iter().collect();
+                    native.aws.auto_allow_readonly = tool.auto_allow_readonly;
+                }
+                None => _ = settings.insert(ToolTarget::Aws.to_string(), raw),
+            }
+        }
+        if let Some(raw) = settings.remove(ToolTarget::Read.as_ref()) {
+            match decode_tool::<ReadTool>(&raw) {
+                Some(tool) => {
+                    native.read.allows = tool.allowed_paths.into_iter().collect();
+                    native.read.denies = tool.denied_paths.into_iter().collect();
+                }
+                None => _ = settings.insert(ToolTarget::Read.to_string(), raw),
+            }
+        }
+        if let Some(raw) = settings.remove(ToolTarget::Write.as_ref()) {
+            match decode_tool::<WriteTool>(&raw) {
+                Some(tool) => {
+                    native.write.allows = tool.allowed_paths.into_iter().collect();
+                    native.write.denies = tool.denied_paths.into_iter().collect();
+                }
+                None => _ = settings.insert(ToolTarget::Write.to_string(), raw),
+            }
+        }
+        if let Some(raw) = settings.remove(ToolTarget::Glob.as_ref()) {
+            match decode_tool::<GlobTool>(&raw) {
+                Some(tool) => {
+                    native.glob.allows = tool.allowed_paths.into_iter().collect();
+                    native.glob.denies = tool.denied_paths.into_iter().collect();
+                    native.glob.auto_allow_readonly = true_or_none(tool.allow_read_only);
+                }
+                None => _ = settings.insert(ToolTarget::Glob.to_string(), raw),
+            }
+        }
+        if let Some(raw) = settings.remove(ToolTarget::Grep.as_ref()) {
+            match decode_tool::<GrepTool>(&raw) {
+                Some(tool) => {
+                    native.grep.allows = tool.allowed_paths.into_iter().collect();
+                    native.grep.denies = tool.denied_paths.into_iter().collect();
+                    native.grep.auto_allow_readonly = true_or_none(tool.allow_read_only);
+                }
+                None => _ = settings.insert(ToolTarget::Grep.to_string(), raw),
+            }
+        }
+        if let Some(raw) = settings.remove(ToolTarget::WebFetch.as_ref()) {
+            match decode_tool::<WebFetchTool>(&raw) {
+                Some(tool) => {
+                    native.web_fetch.allows = tool.trusted.into_iter().collect();
+                    native.web_fetch.denies = tool.blocked.into_iter().collect();
+                }
+                None => _ = settings.insert(ToolTarget::WebFetch.to_string(), raw),
+            }
+        }
+        native
+    }
+}
+
+impl From<&KiroAgent> for KgAgentFileDoc {
+    /// Reverse the kg -> Kiro mapping so that generating the returned document
+    /// reproduces `agent`.
+    fn from(agent: &KiroAgent) -> Self {
+        let mut tools_settings = agent.tools_settings.clone();
+        let native_tools = NativeTools::from_tools_settings(&mut tools_settings);
+
+        let mut subagents = super::SubagentConfig::default();
+        if let Some(raw) = tools_settings.remove("subagent") {
+            match decode_tool::<SubagentTool>(&raw) {
+                Some(tool) => {
+                    subagents.allow = tool.available_agents.into_iter().collect();
+                    subagents.allow.extend(tool.allowed_agents);
+                    subagents.trusted = tool.trusted_agents.into_iter().collect();
+                }
+                None => _ = tools_settings.insert("subagent".to_string(), raw),
+            }
+        }
+
+        let mut resources = KgFileResource::default();
+        let mut skills = KgSkillResource::default();
+        let mut knowledge = HashMap::new();
+        for resource in &agent.resources {
+            if let Some(s) = resource.as_string() {
+                let s = s.as_str();
+                if let Some(location) = s.strip_prefix("file://") {
+                    resources.locations.insert(location.to_string());
+                } else if let Some(location) = s.strip_prefix("skill://") {
+                    skills.locations.insert(location.to_string());
+                } else {
+                    tracing::warn!(agent = %agent.name, "skipping unsupported resource '{s}'");
+                }
+                continue;
+            }
+            let json = facet_json::to_string(resource).unwrap_or_default();
+            match facet_json::from_str::<Knowledge>(&json) {
+                Ok(k) => {
+                    knowledge.insert(k.name, KgKnowledge {
+                        source: k.source,
+                        description: k.description,
+                        index_type: k.index_type,
+                        auto_update: k.auto_update,
+                    });
+                }
+                Err(e) => tracing::warn!(agent = %agent.name, "skipping resource '{json}'\n{e}"),
+            }
+        }
+
+        let hooks = agent
+            .hooks
+            .iter()
+            .map(|(trigger, entries)| {
+                let mut named: HashMap<String, KgHook> = HashMap::new();
+                for (index, h) in entries.iter().enumerate() {
+                    named.insert(hook_name(index, entries.len(), &h.command), KgHook {
+                        command: h.command.clone(),
+                        timeout_ms: h.timeout_ms,
+                        max_output_size: h.max_output_size,
+                        cache_ttl_seconds: h.cache_ttl_seconds,
+                        matcher: h.matcher.clone(),
+                    });
+                }
+                (trigger.clone(), named)
+            })
+            .collect();
+
+        let mcp_servers = agent
+            .mcp_servers
+            .iter()
+            .map(|(name, mcp)| {
+                (name.clone(), KgCustomToolConfig {
+                    url: mcp.url.clone(),
+                    headers: literals(&mcp.headers),
+                    command: mcp.command.clone(),
+                    args: mcp.args.clone(),
+                    env: literals(&mcp.env),
+                    timeout: mcp.timeout,
+                    state: mcp.disabled.map(|disabled| match disabled {
+                        true => McpServerState::Disabled,
+                        false => McpServerState::Enabled,
+                    }),
+                    auto_approve: mcp.auto_approve.iter().cloned().collect(),
+                    disabled_tools: mcp.disabled_tools.iter().cloned().collect(),
+                })
+            })
+            .collect();
+
+        if agent.tools.is_empty() {
+            tracing::warn!(
+                agent = %agent.name,
+                "agent has no tools, kg will generate it with all tools (\"*\")"
+            );
+        }
+
+        Self {
+            schema: Some(AGENT_SCHEMA_URL.to_string()),
+            description: agent.description.clone(),
+            prompt: agent.prompt.clone().map(KgPrompt::from),
+            resources: if resources.locations.is_empty() {
+                Default::default()
+            } else {
+                HashMap::from([(IMPORTED_RESOURCE.to_string(), resources)])
+            },
+            skills: if skills.locations.is_empty() {
+                Default::default()
+            } else {
+                HashMap::from([(IMPORTED_RESOURCE.to_string(), skills)])
+            },
+            knowledge,
+            include_mcp_json: true_or_none(agent.include_mcp_json),
+            tools: agent.tools.iter().cloned().collect(),
+            allowed_tools: agent.allowed_tools.iter().cloned().collect(),
+            model: agent.model.clone(),
+            hooks,
+            mcp_servers,
+            tool_aliases: agent.tool_aliases.clone().into_iter().collect(),
+            native_tools,
+            tool_settings: tools_settings.into_iter().collect(),
+            keyboard_shortcut: agent.keyboard_shortcut.clone(),
+            welcome_message: agent.welcome_message.clone(),
+            subagents,
+            vars: Default::default(),
+            lint: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::Manifest, rediff::FacetDiff};
+
+    const RUST_AGENT: &str = include_str!("../../fixtures/kiro/bootstrap/rust.json");
+
+    #[test_log::test]
+    fn test_import_round_trips_kiro_agent() -> crate::Result<()> {
+        let agent: KiroAgent = facet_json::from_str(RUST_AGENT)?;
+        let doc = KgAgentFileDoc::from(&agent);
+
+        assert_eq!(doc.native_tools.shell.denies.len(), 18);
+        assert!(doc.native_tools.shell.deny_by_default.is_none());
+        assert_eq!(doc.native_tools.write.allows.len(), 2);
+        assert!(doc.subagents.allow.contains("jina"));
+        assert!(doc.tool_settings.is_empty());
+        assert_eq!(doc.resources["default"].locations.len(), 3);
+        assert_eq!(doc.skills["default"].locations.len(), 1);
+        assert_eq!(doc.knowledge.len(), 10);
+        assert_eq!(
+            doc.mcp_servers["context7"].state,
+            Some(McpServerState::Disabled)
+        );
+        assert!(doc.hooks["agentSpawn"].contains_key("01-hook-git-status"));
+
+        let manifest = Manifest::from_file_source("rust", doc, false);
+        let generated = KiroAgent::try_from(&manifest)?;
+        let existing = agent.normalize();
+        assert!(existing.diff(&generated.normalize()).is_equal());
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_import_subagent_settings() -> crate::Result<()> {
+        let agent: KiroAgent = facet_json::from_str(
+            r#"{
+                "name": "subagents",
+                "toolsSettings": {
+                    "subagent": {
+                        "availableAgents": ["docs-*"],
+                        "trustedAgents": ["pr-review"],
+                        "allowedAgents": ["legacy"]
+                    }
+                }
+            }"#,
+        )?;
+        let doc = KgAgentFileDoc::from(&agent);
+        assert!(doc.subagents.allow.contains("docs-*"));
+        assert!(doc.subagents.allow.contains("legacy"));
+        assert!(doc.subagents.trusted.contains("pr-review"));
+        assert!(doc.tool_settings.is_empty());
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_import_keeps_unknown_tool_settings_raw() -> crate::Result<()> {
+        let agent: KiroAgent = facet_json::from_str(
+            r#"{
+                "name": "raw",
+                "tools": ["*"],
+                "toolsSettings": {
+                    "shell": { "allowedCommands": ["ls"], "somethingNew": true },
+                    "glob": { "allowedPaths": ["src/**"], "allowReadOnly": true },
+                    "custom": { "level": 1 }
+                }
+            }"#,
+        )?;
+        let doc = KgAgentFileDoc::from(&agent);
+        assert!(doc.tool_settings.contains_key("shell"));
+        assert!(doc.tool_settings.contains_key("custom"));
+        assert!(doc.native_tools.shell.allows.is_empty());
+        assert_eq!(doc.native_tools.glob.auto_allow_readonly, Some(true));
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_import_keeps_hook_order() -> crate::Result<()> {
+        let agent: KiroAgent = facet_json::from_str(
+            r#"{
+                "name": "hooks",
+                "tools": ["*"],
+                "hooks": {
+                    "stop": [
+                        { "command": "zz-format" },
+                        { "command": "cargo test" },
+                        { "command": "aa-notify" }
+                    ]
+                }
+            }"#,
+        )?;
+        let doc = KgAgentFileDoc::from(&agent);
+        let manifest = Manifest::from_file_source("hooks", doc, false);
+        let generated = KiroAgent::try_from(&manifest)?;
+        let commands: Vec<&str> = generated.hooks["stop"]
+            .iter()
+            .map(|h| h.command.as_str())
+            .collect();
+        assert_eq!(commands, vec!["zz-format", "cargo test", "aa-notify"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hook_name_keeps_order() {
+        assert_eq!(hook_name(0, 2, "echo hello"), "01-echo-hello");
+        assert_eq!(hook_name(1, 2, "echo hello"), "02-echo-hello");
+        assert_eq!(hook_name(0, 1, "   "), "01-hook");
+        assert_eq!(hook_name(99, 120, "ls"), "100-ls");
+        assert_eq!(hook_name(8, 120, "ls"), "009-ls");
+    }
+}
@@ -0,0 +1,20 @@
+use {super::removal::merge_set, facet::Facet, std::collections::HashSet};
+
+/// `kg lint` rules suppressed for an agent and the agents inheriting from it
+#[derive(Facet, Clone, Debug, Default, PartialEq, Eq)]
+#[facet(default, deny_unknown_fields)]
+pub struct LintConfig {
+    /// Rule IDs which are not reported, e.g. `hook-without-timeout`
+    #[facet(default)]
+    pub allow: HashSet<String>,
+}
+
+impl LintConfig {
+    pub fn merge(mut self, other: Self) -> Self {
+        if !other.allow.is_empty() {
+            tracing::trace!(count = other.allow.len(), "merging lint allow");
+            merge_set(&mut self.allow, other.allow);
+        }
+        self
+    }
+}
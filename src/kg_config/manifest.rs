@@ -3,7 +3,9 @@ use {
         KgCustomToolConfig,
         KgFileResource,
         KgKnowledge,
+        KgPrompt,
         KgSkillResource,
+        LintConfig,
         SubagentConfig,
         native::{AwsTool, ExecuteShellTool, NativeTools, ReadTool, WriteTool},
     },
@@ -11,7 +13,7 @@ use {
     color_eyre::eyre::WrapErr,
     facet::Facet,
     std::{
-        collections::{BTreeSet, HashMap, HashSet},
+        collections::{BTreeMap, BTreeSet, HashMap, HashSet},
         fmt::{Debug, Display},
     },
 };
@@ -30,10 +32,11 @@ pub struct Manifest {
     #[facet(default)]
     pub template: bool,
     pub description: Option<String>,
-    /// List of parent agents to inherit configuration from
+    /// Parent agents to inherit configuration from, merged left to right so
+    /// later parents override earlier ones
     #[facet(default)]
-    pub inherits: HashSet<String>,
-    pub prompt: Option<String>,
+    pub inherits: Vec<String>,
+    pub prompt: Option<KgPrompt>,
     #[facet(default)]
     pub resources: HashMap<String, KgFileResource>,
     #[facet(default)]
@@ -63,6 +66,13 @@ pub struct Manifest {
     pub welcome_message: Option<String>,
     #[facet(default)]
     pub subagents: SubagentConfig,
+    /// Variables available to `${NAME}` references, inherited like other
+    /// fields with the child winning
+    #[facet(default)]
+    pub vars: HashMap<String, String>,
+    /// `kg lint` rules suppressed for this agent, inherited
+    #[facet(default)]
+    pub lint: LintConfig,
 }
 
 impl Debug for Manifest {
@@ -86,11 +96,14 @@ impl Manifest {
         }
     }
 
-    pub fn hooks(&self) -> HashMap<String, Vec<AgentHook>> {
-        let mut result: HashMap<String, Vec<AgentHook>> = HashMap::new();
+    /// Kiro hooks per trigger, in the order of their names
+    pub fn hooks(&self) -> BTreeMap<String, Vec<AgentHook>> {
+        let mut result: BTreeMap<String, Vec<AgentHook>> = BTreeMap::new();
         for (hook_type, hooks_map) in &self.hooks {
+            let mut named: Vec<_> = hooks_map.iter().collect();
+            named.sort_by(|a, b| a.0.cmp(b.0));
             let mut hooks_vec = Vec::new();
-            for hook in hooks_map.values() {
+            for (_, hook) in named {
                 hooks_vec.push(AgentHook {
                     command: hook.command.clone(),
                     matcher: hook.matcher.clone(),
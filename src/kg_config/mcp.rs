@@ -1,7 +1,7 @@
 use {
-    crate::kg_config::{SearchQuery, Searchable},
+    crate::kg_config::{McpValue, SearchQuery, Searchable},
     facet::Facet,
-    std::collections::HashMap,
+    std::collections::{HashMap, HashSet},
 };
 
 /// The operational state of an MCP server.
@@ -32,7 +32,7 @@ pub struct KgCustomToolConfig {
     pub url: String,
     /// HTTP headers to include when communicating with HTTP-based MCP servers
     #[facet(default)]
-    pub headers: HashMap<String, String>,
+    pub headers: HashMap<String, McpValue>,
     /// The command string used to initialize the MCP server
     #[facet(default)]
     pub command: String,
@@ -41,12 +41,18 @@ pub struct KgCustomToolConfig {
     pub args: Vec<String>,
     /// A list of environment variables to run the command with
     #[facet(default)]
-    pub env: HashMap<String, String>,
+    pub env: HashMap<String, McpValue>,
     /// Timeout for each MCP request in milliseconds
     pub timeout: Option<u64>,
     /// MCP server operational state ("enabled", "disabled", "hide", maps to
     /// disabled boolean in JSON)
     pub state: Option<McpServerState>,
+    /// Tools of this server Kiro runs without asking for permission
+    #[facet(default, rename = "autoApprove")]
+    pub auto_approve: HashSet<String>,
+    /// Tools of this server Kiro does not load
+    #[facet(default, rename = "disabledTools")]
+    pub disabled_tools: HashSet<String>,
 }
 
 impl KgCustomToolConfig {
@@ -75,8 +81,10 @@ impl KgCustomToolConfig {
 
         if !other.args.is_empty() {
             tracing::trace!(count = other.args.len(), "args: extended");
-            self.args.extend(other.args);
+            super::removal::merge_list(&mut self.args, other.args);
         }
+        super::removal::merge_set(&mut self.auto_approve, other.auto_approve);
+        super::removal::merge_set(&mut self.disabled_tools, other.disabled_tools);
 
         let parent_env_count = other.env.len();
         let child_env_count = self.env.len();
@@ -106,6 +114,17 @@ impl KgCustomToolConfig {
 
         self
     }
+
+    /// `autoApprove` tools which are also in `disabledTools`, sorted. Kiro
+    /// never loads them, so approving them has no effect
+    pub fn approved_and_disabled(&self) -> Vec<&String> {
+        let mut tools: Vec<&String> = self
+            .auto_approve
+            .intersection(&self.disabled_tools)
+            .collect();
+        tools.sort();
+        tools
+    }
 }
 
 impl Searchable for KgCustomToolConfig {
@@ -113,14 +132,16 @@ impl Searchable for KgCustomToolConfig {
         query.matches(&self.url)
             || query.matches(&self.command)
             || self.args.iter().any(|arg| query.matches(arg))
+            || self.auto_approve.iter().any(|tool| query.matches(tool))
+            || self.disabled_tools.iter().any(|tool| query.matches(tool))
             || self
                 .env
                 .iter()
-                .any(|(key, value)| query.matches(key) || query.matches(value))
+                .any(|(key, value)| query.matches(key) || query.matches(&value.to_string()))
             || self
                 .headers
                 .iter()
-                .any(|(key, value)| query.matches(key) || query.matches(value))
+                .any(|(key, value)| query.matches(key) || query.matches(&value.to_string()))
             || self.state.as_ref().is_some_and(|state| {
                 query.matches(match state {
                     McpServerState::Enabled => "enabled",
@@ -228,10 +249,13 @@ command = "ctx-mcp"
     fn search_matches_meaningful_string_fields() {
         let mcp = KgCustomToolConfig {
             url: "https://example.test/mcp".into(),
-            headers: HashMap::from([(String::from("Authorization"), String::from("Bearer token"))]),
+            headers: HashMap::from([(
+                String::from("Authorization"),
+                McpValue::from("Bearer token"),
+            )]),
             command: "ctx-mcp".into(),
             args: vec![String::from("--profile"), String::from("sandbox")],
-            env: HashMap::from([(String::from("LOG_LEVEL"), String::from("debug"))]),
+            env: HashMap::from([(String::from("LOG_LEVEL"), McpValue::from("debug"))]),
             state: Some(McpServerState::Disabled),
             ..Default::default()
         };
@@ -241,5 +265,45 @@ command = "ctx-mcp"
         assert!(mcp.search(&"sandbox".into()));
         assert!(mcp.search(&"disabled".into()));
         assert!(!mcp.search(&"missing".into()));
+
+        let mcp = KgCustomToolConfig {
+            auto_approve: HashSet::from([String::from("search_docs")]),
+            disabled_tools: HashSet::from([String::from("delete_page")]),
+            ..Default::default()
+        };
+        assert!(mcp.search(&"search_docs".into()));
+        assert!(mcp.search(&"delete".into()));
+    }
+
+    #[test_log::test]
+    fn tool_lists_merge_with_removal() -> Result<()> {
+        let doc: McpDoc = toml_parse(
+            r#"
+[mcpServers.parent]
+command = "docs-mcp"
+autoApprove = ["search", "read_page", "write_page"]
+disabledTools = ["delete_page"]
+
+[mcpServers.child]
+autoApprove = ["!write_page", "list"]
+disabledTools = ["write_page"]
+"#,
+        )?;
+        let parent = doc.mcp_servers["parent"].clone();
+        let mut merged = doc.mcp_servers["child"].clone().merge(parent);
+        merged.auto_approve.retain(|t| !t.starts_with('!'));
+        assert_eq!(
+            merged.auto_approve,
+            HashSet::from(["search", "read_page", "list"].map(String::from))
+        );
+        assert_eq!(
+            merged.disabled_tools,
+            HashSet::from(["delete_page", "write_page"].map(String::from))
+        );
+        assert!(merged.approved_and_disabled().is_empty());
+
+        merged.auto_approve.insert(String::from("delete_page"));
+        assert_eq!(merged.approved_and_disabled(), vec!["delete_page"]);
+        Ok(())
     }
 }
@@ -1,4 +1,4 @@
-use super::*;
+use {super::*, removal::merge_set};
 
 impl Manifest {
     pub fn merge(mut self, other: Manifest) -> Self {
@@ -23,9 +23,9 @@ impl Manifest {
             self.description = other.description;
         }
 
-        if self.prompt.is_none() && other.prompt.is_some() {
-            tracing::trace!("prompt: merged from other");
-            self.prompt = other.prompt;
+        if other.prompt.is_some() {
+            tracing::trace!("prompt: composed with other");
+            self.prompt = KgPrompt::compose(self.prompt, other.prompt);
         }
 
         if self.model.is_none() && other.model.is_some() {
@@ -94,12 +94,12 @@ impl Manifest {
 
         if !other.tools.is_empty() {
             tracing::trace!(count = other.tools.len(), "tools: extended");
-            self.tools.extend(other.tools);
+            merge_set(&mut self.tools, other.tools);
         }
 
         if !other.allowed_tools.is_empty() {
             tracing::trace!(count = other.allowed_tools.len(), "allowed_tools: extended");
-            self.allowed_tools.extend(other.allowed_tools);
+            merge_set(&mut self.allowed_tools, other.allowed_tools);
         }
 
         if !other.tool_aliases.is_empty() {
@@ -109,7 +109,18 @@ impl Manifest {
 
         if !other.inherits.is_empty() {
             tracing::trace!(count = other.inherits.len(), "inherits: extended");
-            self.inherits.extend(other.inherits);
+            // Later parents win, so the lower-precedence parents go first
+            let mut inherits: Vec<String> = other
+                .inherits
+                .into_iter()
+                .filter(|parent| !self.inherits.contains(parent))
+                .collect();
+            inherits.append(&mut self.inherits);
+            self.inherits = inherits;
+        }
+
+        for (key, value) in other.vars {
+            self.vars.entry(key).or_insert(value);
         }
 
         if !other.tool_settings.is_empty() {
@@ -119,6 +130,7 @@ impl Manifest {
 
         tracing::trace!("subagents: merging");
         self.subagents = self.subagents.merge(other.subagents);
+        self.lint = self.lint.merge(other.lint);
 
         // Merge hooks - child overrides parent for same hook_type.hook_name
         for (hook_type, parent_hooks_map) in other.hooks {
@@ -202,9 +214,9 @@ mod tests {
         assert!(merged.include_mcp_json.unwrap_or_default());
 
         assert_eq!(merged.inherits.len(), 1);
-        assert!(merged.inherits.contains("parent"));
+        assert_eq!(merged.inherits, vec!["parent"]);
 
-        assert_eq!(merged.prompt, Some("i tell you what to do".to_string()));
+        assert_eq!(merged.prompt, Some(KgPrompt::from("i tell you what to do")));
         let tools = &merged.tools;
         assert_eq!(tools.len(), 3);
         assert!(tools.contains("@awsdocs"));
@@ -1,11 +1,19 @@
 mod agent_file;
+mod import;
+mod lint;
 mod manifest;
 mod mcp;
 mod merge;
 mod native;
+mod policy;
+mod prompt;
+mod removal;
 mod resources;
 mod search;
+mod secret;
 mod subagent;
+mod vars;
+mod writer;
 
 use {
     crate::{Fs, Result},
@@ -15,11 +23,18 @@ use {
 };
 pub use {
     agent_file::KgAgentFileDoc,
+    lint::LintConfig,
     manifest::Manifest,
     mcp::{KgCustomToolConfig, McpServerState},
+    policy::Policy,
+    prompt::{KgPrompt, PromptMode, PromptSection},
+    removal::{is_removed, removed_entry},
     resources::{KgFileResource, KgKnowledge, KgSkillResource},
     search::{SearchQuery, Searchable},
+    secret::{McpValue, REDACTED},
     subagent::SubagentConfig,
+    vars::VarError,
+    writer::{toml_key, toml_to_string},
 };
 
 pub fn toml_parse_path<T>(fs: &Fs, path: impl AsRef<Path>) -> Option<Result<T>>
@@ -38,6 +53,28 @@ where
     }
 }
 
+/// `values` as a sorted list
+pub fn sorted<T: Ord>(values: impl IntoIterator<Item = T>) -> Vec<T> {
+    let mut values: Vec<T> = values.into_iter().collect();
+    values.sort();
+    values
+}
+
+/// Minimal glob matching where `*` matches any run of characters
+pub fn glob_match(pattern: &str, value: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == value,
+        Some((prefix, rest)) => {
+            let Some(value) = value.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=value.len())
+                .filter(|i| value.is_char_boundary(*i))
+                .any(|i| glob_match(rest, &value[i..]))
+        }
+    }
+}
+
 #[cfg(test)]
 pub fn toml_parse<T>(content: &str) -> Result<T>
 where
@@ -80,6 +117,14 @@ impl GeneratorConfig {
 mod tests {
     use {super::*, crate::KgAgentFileDoc};
 
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("@git/*", "@git/status"));
+        assert!(glob_match("fs_*", "fs_read"));
+        assert!(!glob_match("fs_*", "shell"));
+        assert!(glob_match("read", "read"));
+    }
+
     #[test_log::test]
     fn test_agent_decoding() -> Result<()> {
         let toml_agents = include_str!("../../fixtures/manifest-test/test-decoding.toml");
@@ -12,7 +12,7 @@ use {
         },
     },
     facet::Facet,
-    std::collections::HashSet,
+    std::collections::{BTreeSet, HashSet},
 };
 
 macro_rules! define_tool {
@@ -40,7 +40,7 @@ macro_rules! define_tool {
                         count = other.allows.len(),
                         "merging allows"
                     );
-                    self.allows.extend(other.allows);
+                    super::removal::merge_set(&mut self.allows, other.allows);
                 }
                 if !other.denies.is_empty() {
                     tracing::trace!(
@@ -48,7 +48,7 @@ macro_rules! define_tool {
                         count = other.denies.len(),
                         "merging denies"
                     );
-                    self.denies.extend(other.denies);
+                    super::removal::merge_set(&mut self.denies, other.denies);
                 }
                 if !other.force_allow.is_empty() {
                     tracing::trace!(
@@ -56,7 +56,7 @@ macro_rules! define_tool {
                         count = other.force_allow.len(),
                         "merging force_allow"
                     );
-                    self.force_allow.extend(other.force_allow);
+                    super::removal::merge_set(&mut self.force_allow, other.force_allow);
                 }
                 self.auto_allow_readonly = self.auto_allow_readonly.or(other.auto_allow_readonly);
                 self.deny_by_default = self.deny_by_default.or(other.deny_by_default);
@@ -101,7 +101,47 @@ pub struct NativeTools {
     pub web_fetch: WebFetchTool,
 }
 
+macro_rules! native_lists {
+    ($tools:expr, $($borrow:tt)+) => {
+        [
+            (("shell", "allow"), $($borrow)+ $tools.shell.allows),
+            (("shell", "deny"), $($borrow)+ $tools.shell.denies),
+            (("shell", "forceAllow"), $($borrow)+ $tools.shell.force_allow),
+            (("aws", "allow"), $($borrow)+ $tools.aws.allows),
+            (("aws", "deny"), $($borrow)+ $tools.aws.denies),
+            (("aws", "forceAllow"), $($borrow)+ $tools.aws.force_allow),
+            (("read", "allow"), $($borrow)+ $tools.read.allows),
+            (("read", "deny"), $($borrow)+ $tools.read.denies),
+            (("read", "forceAllow"), $($borrow)+ $tools.read.force_allow),
+            (("write", "allow"), $($borrow)+ $tools.write.allows),
+            (("write", "deny"), $($borrow)+ $tools.write.denies),
+            (("write", "forceAllow"), $($borrow)+ $tools.write.force_allow),
+            (("glob", "allow"), $($borrow)+ $tools.glob.allows),
+            (("glob", "deny"), $($borrow)+ $tools.glob.denies),
+            (("glob", "forceAllow"), $($borrow)+ $tools.glob.force_allow),
+            (("grep", "allow"), $($borrow)+ $tools.grep.allows),
+            (("grep", "deny"), $($borrow)+ $tools.grep.denies),
+            (("grep", "forceAllow"), $($borrow)+ $tools.grep.force_allow),
+            (("web-fetch", "allow"), $($borrow)+ $tools.web_fetch.allows),
+            (("web-fetch", "deny"), $($borrow)+ $tools.web_fetch.denies),
+            (("web-fetch", "forceAllow"), $($borrow)+ $tools.web_fetch.force_allow),
+        ]
+    };
+}
+
+/// A `(tool, list)` pair using the TOML names, e.g. `("shell", "allow")`
+pub type NativeListKey = (&'static str, &'static str);
+
 impl NativeTools {
+    /// Every allow, deny and forceAllow list of every tool
+    pub fn lists(&self) -> [(NativeListKey, &HashSet<String>); 21] {
+        native_lists!(self, &)
+    }
+
+    pub fn lists_mut(&mut self) -> [(NativeListKey, &mut HashSet<String>); 21] {
+        native_lists!(self, &mut)
+    }
+
     pub fn merge(mut self, other: Self) -> Self {
         self.shell = self.shell.merge(other.shell);
         self.aws = self.aws.merge(other.aws);
@@ -118,8 +158,8 @@ impl From<&NativeTools> for KiroAwsTool {
     fn from(value: &NativeTools) -> Self {
         let aws = &value.aws;
         KiroAwsTool {
-            allowed_services: aws.allows.clone(),
-            denied_services: aws.denies.clone(),
+            allowed_services: aws.allows.iter().cloned().collect(),
+            denied_services: aws.denies.iter().cloned().collect(),
             auto_allow_readonly: aws.auto_allow_readonly,
         }
     }
@@ -128,8 +168,8 @@ impl From<&NativeTools> for KiroAwsTool {
 impl From<&NativeTools> for KiroWriteTool {
     fn from(value: &NativeTools) -> Self {
         let write = &value.write;
-        let mut allows: HashSet<String> = write.allows.clone();
-        let mut denies: HashSet<String> = write.denies.clone();
+        let mut allows: BTreeSet<String> = write.allows.iter().cloned().collect();
+        let mut denies: BTreeSet<String> = write.denies.iter().cloned().collect();
         if !write.force_allow.is_empty() {
             tracing::trace!(
                 "Override/Forcing write: {:?}",
@@ -153,8 +193,8 @@ impl From<&NativeTools> for KiroWriteTool {
 impl From<&NativeTools> for KiroReadTool {
     fn from(value: &NativeTools) -> Self {
         let read = &value.read;
-        let mut allows: HashSet<String> = read.allows.clone();
-        let mut denies: HashSet<String> = read.denies.clone();
+        let mut allows: BTreeSet<String> = read.allows.iter().cloned().collect();
+        let mut denies: BTreeSet<String> = read.denies.iter().cloned().collect();
         if !read.force_allow.is_empty() {
             tracing::trace!(
                 "Override/Forcing read: {:?}",
@@ -178,8 +218,8 @@ impl From<&NativeTools> for KiroReadTool {
 impl From<&NativeTools> for KiroShellTool {
     fn from(value: &NativeTools) -> Self {
         let shell = &value.shell;
-        let mut allows: HashSet<String> = shell.allows.clone();
-        let mut denies: HashSet<String> = shell.denies.clone();
+        let mut allows: BTreeSet<String> = shell.allows.iter().cloned().collect();
+        let mut denies: BTreeSet<String> = shell.denies.iter().cloned().collect();
 
         if !shell.force_allow.is_empty() {
             tracing::trace!(
@@ -205,8 +245,8 @@ impl From<&NativeTools> for KiroShellTool {
 impl From<&NativeTools> for KiroGlobTool {
     fn from(value: &NativeTools) -> Self {
         let glob = &value.glob;
-        let mut allows: HashSet<String> = glob.allows.clone();
-        let mut denies: HashSet<String> = glob.denies.clone();
+        let mut allows: BTreeSet<String> = glob.allows.iter().cloned().collect();
+        let mut denies: BTreeSet<String> = glob.denies.iter().cloned().collect();
         if !glob.force_allow.is_empty() {
             tracing::trace!(
                 "Override/Forcing glob paths: {:?}",
@@ -231,8 +271,8 @@ impl From<&NativeTools> for KiroGlobTool {
 impl From<&NativeTools> for KiroGrepTool {
     fn from(value: &NativeTools) -> Self {
         let grep = &value.grep;
-        let mut allows: HashSet<String> = grep.allows.clone();
-        let mut denies: HashSet<String> = grep.denies.clone();
+        let mut allows: BTreeSet<String> = grep.allows.iter().cloned().collect();
+        let mut denies: BTreeSet<String> = grep.denies.iter().cloned().collect();
         if !grep.force_allow.is_empty() {
             tracing::trace!(
                 "Override/Forcing grep paths: {:?}",
@@ -257,8 +297,8 @@ impl From<&NativeTools> for KiroGrepTool {
 impl From<&NativeTools> for KiroWebFetchTool {
     fn from(value: &NativeTools) -> Self {
         let web_fetch = &value.web_fetch;
-        let mut allows: HashSet<String> = web_fetch.allows.clone();
-        let mut denies: HashSet<String> = web_fetch.denies.clone();
+        let mut allows: BTreeSet<String> = web_fetch.allows.iter().cloned().collect();
+        let mut denies: BTreeSet<String> = web_fetch.denies.iter().cloned().collect();
         if !web_fetch.force_allow.is_empty() {
             tracing::trace!(
                 "Override/Forcing web_fetch trusted: {:?}",
@@ -282,8 +322,8 @@ impl From<&NativeTools> for KiroWebFetchTool {
 #[cfg(test)]
 mod tests {
     use {super::*, crate::Result, std::fmt::Display};
-    fn into_set<T: Display>(v: Vec<T>) -> HashSet<String> {
-        HashSet::from_iter(v.into_iter().map(|t| t.to_string()))
+    fn into_set<T: Display, S: FromIterator<String>>(v: Vec<T>) -> S {
+        v.into_iter().map(|t| t.to_string()).collect()
     }
     #[test_log::test]
     fn parse_shell_tool() -> Result<()> {
@@ -0,0 +1,116 @@
+use {
+    facet::Facet,
+    std::collections::{HashMap, HashSet},
+};
+
+/// Deny entries every agent must keep for a native tool
+#[derive(Facet, Clone, Debug, Default, PartialEq, Eq)]
+#[facet(default, deny_unknown_fields)]
+pub struct PolicyTool {
+    #[facet(default)]
+    pub deny: HashSet<String>,
+}
+
+/// Shell deny entries every agent must keep, and whether `denyByDefault` is
+/// required
+#[derive(Facet, Clone, Debug, Default, PartialEq, Eq)]
+#[facet(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct PolicyShellTool {
+    #[facet(default)]
+    pub deny: HashSet<String>,
+    #[facet(default)]
+    pub deny_by_default: Option<bool>,
+}
+
+#[derive(Facet, Clone, Debug, Default, PartialEq, Eq)]
+#[facet(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct PolicyNativeTools {
+    #[facet(default)]
+    pub shell: PolicyShellTool,
+    #[facet(default)]
+    pub aws: PolicyTool,
+    #[facet(default)]
+    pub read: PolicyTool,
+    #[facet(default)]
+    pub write: PolicyTool,
+    #[facet(default)]
+    pub glob: PolicyTool,
+    #[facet(default)]
+    pub grep: PolicyTool,
+    #[facet(default)]
+    pub web_fetch: PolicyTool,
+}
+
+impl PolicyNativeTools {
+    /// Required deny entries per tool, using the TOML tool names
+    pub fn denies(&self) -> [(&'static str, &HashSet<String>); 7] {
+        [
+            ("shell", &self.shell.deny),
+            ("aws", &self.aws.deny),
+            ("read", &self.read.deny),
+            ("write", &self.write.deny),
+            ("glob", &self.glob.deny),
+            ("grep", &self.grep.deny),
+            ("web-fetch", &self.web_fetch.deny),
+        ]
+    }
+}
+
+/// MCP servers agents may not declare
+#[derive(Facet, Clone, Debug, Default, PartialEq, Eq)]
+#[facet(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct PolicyMcp {
+    /// Commands, matched against the full command and its file name, `*`
+    /// matches any run of characters
+    #[facet(default)]
+    pub forbidden_commands: HashSet<String>,
+    /// URLs, `*` matches any run of characters
+    #[facet(default)]
+    pub forbidden_urls: HashSet<String>,
+}
+
+/// Organisation-wide constraints from `policy.toml`, enforced on every
+/// generated agent after inheritance. Agents cannot override them.
+#[derive(Facet, Clone, Debug, Default, PartialEq, Eq)]
+#[facet(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct Policy {
+    #[facet(default, rename = "$schema")]
+    pub schema: Option<String>,
+    #[facet(default)]
+    pub native_tools: PolicyNativeTools,
+    #[facet(default)]
+    pub mcp: PolicyMcp,
+    /// Hook commands every agent must run, per trigger, e.g.
+    /// `preToolUse = ["audit-log"]`
+    #[facet(default)]
+    pub required_hooks: HashMap<String, HashSet<String>>,
+    /// Models agents may select, `*` matches any run of characters. Empty
+    /// allows every model, agents without a model use the Kiro default
+    #[facet(default)]
+    pub models: HashSet<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::Result};
+
+    #[test]
+    fn test_parse_policy() -> Result<()> {
+        let policy: Policy = crate::toml_parse(
+            r#"
+            models = ["claude-sonnet-*"]
+            nativeTools.shell.deny = ["rm -rf .*"]
+            nativeTools.shell.denyByDefault = true
+            nativeTools.web-fetch.deny = ["http://.*"]
+            mcp.forbiddenCommands = ["curl"]
+            requiredHooks.preToolUse = ["audit-log"]
+            "#,
+        )?;
+        assert_eq!(policy.native_tools.shell.deny_by_default, Some(true));
+        assert!(policy.native_tools.web_fetch.deny.contains("http://.*"));
+        assert!(policy.mcp.forbidden_commands.contains("curl"));
+        assert!(policy.required_hooks["preToolUse"].contains("audit-log"));
+        assert!(crate::toml_parse::<Policy>("nativeTools.shell.allow = [\"ls\"]").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,248 @@
+use {
+    super::{Manifest, secret::expand_home},
+    crate::{Fs, Result},
+    color_eyre::eyre::Context,
+    facet::Facet,
+    std::path::Path,
+};
+
+/// Separator between composed prompt sections
+const SECTION_SEPARATOR: &str = "\n\n";
+
+/// How a prompt section combines with the prompt inherited from parents
+#[derive(Facet, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[facet(rename_all = "lowercase")]
+#[repr(C)]
+pub enum PromptMode {
+    /// Replace the inherited prompt
+    #[default]
+    Replace,
+    /// Add the section after the inherited prompt
+    Append,
+    /// Add the section before the inherited prompt
+    Prepend,
+}
+
+/// Prompt section with inline `text` or a `file` relative to the file
+/// defining it
+#[derive(Facet, Clone, Debug, Default, PartialEq, Eq)]
+#[facet(default, deny_unknown_fields)]
+pub struct PromptSection {
+    /// Inline prompt text
+    #[facet(default)]
+    pub text: Option<String>,
+    /// Markdown file holding the prompt, relative to the file defining it
+    #[facet(default)]
+    pub file: Option<String>,
+    /// How the section combines with the inherited prompt (default replace)
+    #[facet(default)]
+    pub mode: PromptMode,
+    /// Content of `file`, or of the sections composed into this one
+    #[facet(skip, default)]
+    pub resolved: Option<String>,
+}
+
+/// Agent prompt, either an inline string or a [`PromptSection`]
+#[derive(Facet, Clone, Debug, PartialEq, Eq)]
+#[facet(untagged)]
+#[repr(C)]
+pub enum KgPrompt {
+    Inline(String),
+    Section(PromptSection),
+}
+
+impl From<&str> for KgPrompt {
+    fn from(value: &str) -> Self {
+        Self::Inline(value.to_string())
+    }
+}
+
+impl From<String> for KgPrompt {
+    fn from(value: String) -> Self {
+        Self::Inline(value)
+    }
+}
+
+impl KgPrompt {
+    /// The prompt text written to the agent
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            Self::Inline(text) => Some(text),
+            Self::Section(section) => section.resolved.as_deref().or(section.text.as_deref()),
+        }
+    }
+
+    pub fn text_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Self::Inline(text) => Some(text),
+            Self::Section(section) => section.resolved.as_mut().or(section.text.as_mut()),
+        }
+    }
+
+    pub fn mode(&self) -> PromptMode {
+        match self {
+            Self::Inline(_) => PromptMode::Replace,
+            Self::Section(section) => section.mode,
+        }
+    }
+
+    /// Read the prompt `file`, relative to `dir`
+    pub fn load(&mut self, fs: &Fs, dir: &Path) -> Result<()> {
+        let Self::Section(section) = self else {
+            return Ok(());
+        };
+        let Some(file) = &section.file else {
+            return Ok(());
+        };
+        if section.text.is_some() {
+            crate::bail!("prompt sets both 'text' and 'file', use one of them");
+        }
+        let path = dir.join(expand_home(file));
+        if !fs.exists(&path) {
+            crate::bail!("prompt file {} not found", path.display());
+        }
+        section.resolved = Some(
+            fs.read_to_string_sync(&path)
+                .wrap_err_with(|| format!("failed to read prompt file {}", path.display()))?,
+        );
+        Ok(())
+    }
+
+    /// Combine the `child` prompt with the one inherited from `parent`
+    pub fn compose(child: Option<Self>, parent: Option<Self>) -> Option<Self> {
+        let (child, parent) = match (child, parent) {
+            (Some(child), Some(parent)) => (child, parent),
+            (child, parent) => return child.or(parent),
+        };
+        let (first, second) = match child.mode() {
+            PromptMode::Replace => return Some(child),
+            PromptMode::Append => (&parent, &child),
+            PromptMode::Prepend => (&child, &parent),
+        };
+        let text = [first.text(), second.text()]
+            .into_iter()
+            .flatten()
+            .map(str::trim_end)
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join(SECTION_SEPARATOR);
+        Some(Self::Section(PromptSection {
+            mode: parent.mode(),
+            resolved: Some(text),
+            ..Default::default()
+        }))
+    }
+}
+
+impl Manifest {
+    /// Read the prompt file of a manifest defined in `source`
+    pub fn load_prompt(&mut self, fs: &Fs, source: &Path) -> Result<()> {
+        let dir = source.parent().unwrap_or(Path::new(""));
+        match self.prompt.as_mut() {
+            Some(prompt) => prompt
+                .load(fs, dir)
+                .wrap_err_with(|| format!("Agent '{}' ({})", self.name, source.display())),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(text: &str, mode: PromptMode) -> KgPrompt {
+        KgPrompt::Section(PromptSection {
+            text: Some(text.to_string()),
+            mode,
+            ..Default::default()
+        })
+    }
+
+    #[derive(Facet, Debug)]
+    struct Doc {
+        prompt: KgPrompt,
+    }
+
+    #[test]
+    fn test_parse_prompt() -> Result<()> {
+        let doc: Doc = crate::toml_parse(r#"prompt = "inline""#)?;
+        assert_eq!(doc.prompt, KgPrompt::from("inline"));
+        let doc: Doc =
+            crate::toml_parse(r#"prompt = { file = "prompts/reviewer.md", mode = "append" }"#)?;
+        assert_eq!(
+            doc.prompt,
+            KgPrompt::Section(PromptSection {
+                file: Some(String::from("prompts/reviewer.md")),
+                mode: PromptMode::Append,
+                ..Default::default()
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_compose_prompts() {
+        let parent = Some(KgPrompt::from("base"));
+        let replaced = KgPrompt::compose(Some(KgPrompt::from("child")), parent.clone());
+        assert_eq!(replaced.as_ref().and_then(|p| p.text()), Some("child"));
+
+        let appended =
+            KgPrompt::compose(Some(section("child", PromptMode::Append)), parent.clone());
+        assert_eq!(
+            appended.as_ref().and_then(|p| p.text()),
+            Some("base\n\nchild")
+        );
+        assert_eq!(
+            appended.as_ref().map(|p| p.mode()),
+            Some(PromptMode::Replace)
+        );
+
+        // A composed section keeps the parent mode for further ancestors
+        let middle = KgPrompt::compose(
+            Some(section("child", PromptMode::Append)),
+            Some(section("middle", PromptMode::Prepend)),
+        );
+        assert_eq!(middle.as_ref().map(|p| p.mode()), Some(PromptMode::Prepend));
+        let full = KgPrompt::compose(middle, parent);
+        assert_eq!(
+            full.as_ref().and_then(|p| p.text()),
+            Some("middle\n\nchild\n\nbase")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_prompt_file() -> Result<()> {
+        let fs = Fs::new();
+        fs.create_dir_all("cfg/prompts").await?;
+        fs.write("cfg/prompts/reviewer.md", "Review carefully")
+            .await?;
+        let mut manifest = Manifest {
+            name: String::from("reviewer"),
+            prompt: Some(KgPrompt::Section(PromptSection {
+                file: Some(String::from("prompts/reviewer.md")),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        manifest.load_prompt(&fs, Path::new("cfg/kg.toml"))?;
+        assert_eq!(
+            manifest.prompt.as_ref().and_then(|p| p.text()),
+            Some("Review carefully")
+        );
+
+        manifest.prompt = Some(KgPrompt::Section(PromptSection {
+            file: Some(String::from("prompts/missing.md")),
+            ..Default::default()
+        }));
+        let err = format!(
+            "{:?}",
+            manifest
+                .load_prompt(&fs, Path::new("cfg/kg.toml"))
+                .unwrap_err()
+        );
+        assert!(err.contains("prompts/missing.md not found"), "{err}");
+        assert!(err.contains("Agent 'reviewer' (cfg/kg.toml)"), "{err}");
+        Ok(())
+    }
+}
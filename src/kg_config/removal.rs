@@ -0,0 +1,170 @@
+use {
+    super::Manifest,
+    std::collections::{HashMap, HashSet},
+};
+
+/// Prefix marking an entry as removed from the inherited collection
+pub const REMOVE_PREFIX: char = '!';
+/// Drops every inherited entry, replacing the collection instead of extending
+/// it
+pub const REMOVE_ALL: &str = "!*";
+
+/// Entry removed by a `!entry` marker, `None` for regular entries
+pub fn removed_entry(entry: &str) -> Option<&str> {
+    entry.strip_prefix(REMOVE_PREFIX)
+}
+
+/// Whether the markers in `child` drop `entry` inherited from a parent
+pub fn is_removed<'a>(mut child: impl Iterator<Item = &'a String>, entry: &str) -> bool {
+    removed_entry(entry).is_none()
+        && child.any(|c| c == REMOVE_ALL || removed_entry(c) == Some(entry))
+}
+
+/// Extend `child` with `parent`, skipping the entries `child` removes. Markers
+/// are kept so they also apply to ancestors merged later.
+pub fn merge_set(child: &mut HashSet<String>, parent: HashSet<String>) {
+    if child.contains(REMOVE_ALL) {
+        return;
+    }
+    let kept: Vec<String> = parent
+        .into_iter()
+        .filter(|e| !is_removed(child.iter(), e))
+        .collect();
+    child.extend(kept);
+}
+
+/// Same as [`merge_set`] for ordered lists such as MCP `args`
+pub fn merge_list(child: &mut Vec<String>, parent: Vec<String>) {
+    if child.iter().any(|c| c == REMOVE_ALL) {
+        return;
+    }
+    let kept: Vec<String> = parent
+        .into_iter()
+        .filter(|e| !is_removed(child.iter(), e))
+        .collect();
+    child.extend(kept);
+}
+
+fn resolve_set(set: &mut HashSet<String>) {
+    set.retain(|e| removed_entry(e).is_none());
+}
+
+impl Manifest {
+    /// Drop `!entry` markers once inheritance is resolved
+    pub fn resolve_removals(&mut self) {
+        resolve_set(&mut self.tools);
+        resolve_set(&mut self.allowed_tools);
+        resolve_set(&mut self.subagents.allow);
+        resolve_set(&mut self.subagents.deny);
+        resolve_set(&mut self.subagents.trusted);
+        resolve_set(&mut self.lint.allow);
+        for (_, list) in self.native_tools.lists_mut() {
+            resolve_set(list);
+        }
+        for mcp in self.mcp_servers.values_mut() {
+            mcp.args.retain(|e| removed_entry(e).is_none());
+            resolve_set(&mut mcp.auto_approve);
+            resolve_set(&mut mcp.disabled_tools);
+        }
+    }
+
+    /// Collections supporting `!entry` removal, keyed by their TOML path
+    pub fn removable_fields(&self) -> HashMap<String, Vec<&String>> {
+        let mut fields: HashMap<String, Vec<&String>> = HashMap::from([
+            (String::from("tools"), self.tools.iter().collect()),
+            (
+                String::from("allowedTools"),
+                self.allowed_tools.iter().collect(),
+            ),
+            (
+                String::from("subagents.allow"),
+                self.subagents.allow.iter().collect(),
+            ),
+            (
+                String::from("subagents.deny"),
+                self.subagents.deny.iter().collect(),
+            ),
+            (
+                String::from("subagents.trusted"),
+                self.subagents.trusted.iter().collect(),
+            ),
+        ]);
+        for ((tool, list), entries) in self.native_tools.lists() {
+            fields.insert(
+                format!("nativeTools.{tool}.{list}"),
+                entries.iter().collect(),
+            );
+        }
+        for (name, mcp) in &self.mcp_servers {
+            fields.insert(format!("mcpServers.{name}.args"), mcp.args.iter().collect());
+            fields.insert(
+                format!("mcpServers.{name}.autoApprove"),
+                mcp.auto_approve.iter().collect(),
+            );
+            fields.insert(
+                format!("mcpServers.{name}.disabledTools"),
+                mcp.disabled_tools.iter().collect(),
+            );
+        }
+        fields.retain(|_, entries| !entries.is_empty());
+        fields
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(entries: &[&str]) -> HashSet<String> {
+        entries.iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn test_merge_set_removes_marked_entries() {
+        let mut child = set(&["!git push", "cargo build"]);
+        merge_set(&mut child, set(&["git push", "git status", "!rm"]));
+        assert_eq!(
+            child,
+            set(&["!git push", "cargo build", "git status", "!rm"])
+        );
+        // A marker from the parent also applies to its own ancestors
+        merge_set(&mut child, set(&["rm", "ls"]));
+        assert!(!child.contains("rm"));
+        assert!(child.contains("ls"));
+    }
+
+    #[test]
+    fn test_merge_set_remove_all_replaces() {
+        let mut child = set(&["!*", "read"]);
+        merge_set(&mut child, set(&["write", "shell"]));
+        assert_eq!(child, set(&["!*", "read"]));
+    }
+
+    #[test]
+    fn test_merge_list_keeps_order() {
+        let mut child = vec![String::from("--child"), String::from("!--verbose")];
+        merge_list(&mut child, vec![
+            String::from("--verbose"),
+            String::from("--parent"),
+        ]);
+        assert_eq!(child, vec!["--child", "!--verbose", "--parent"]);
+
+        let mut child = vec![String::from("!*"), String::from("--only")];
+        merge_list(&mut child, vec![String::from("--parent")]);
+        assert_eq!(child, vec!["!*", "--only"]);
+    }
+
+    #[test]
+    fn test_resolve_removals_drops_markers() {
+        let mut manifest = Manifest {
+            tools: set(&["!*", "read"]),
+            ..Default::default()
+        };
+        manifest.native_tools.shell.allows = set(&["!rm", "ls"]);
+        manifest.subagents.deny = set(&["!admin"]);
+        manifest.resolve_removals();
+        assert_eq!(manifest.tools, set(&["read"]));
+        assert_eq!(manifest.native_tools.shell.allows, set(&["ls"]));
+        assert!(manifest.subagents.deny.is_empty());
+    }
+}
@@ -0,0 +1,257 @@
+use {
+    super::Manifest,
+    crate::{Fs, Result},
+    facet::Facet,
+    std::{collections::HashMap, fmt::Display, path::PathBuf},
+};
+
+/// Shown instead of a secret reference until `kg generate` resolves it
+pub const REDACTED: &str = "<redacted>";
+/// Scheme of secret references read from a file
+const FILE_SCHEME: &str = "file:";
+
+/// Value of an MCP `env` or `headers` entry.
+///
+/// References are only resolved when `kg generate` writes the agent, every
+/// other command shows them redacted.
+#[derive(Facet, Clone, Debug, Eq, PartialEq)]
+#[facet(untagged)]
+#[repr(C)]
+pub enum McpValue {
+    /// Plain value, written as is
+    Literal(String),
+    /// Secret read from a file, `{ secret = "file:~/.config/kg/secrets/gh" }`
+    Secret { secret: String },
+    /// Environment variable of the `kg generate` process, `{ env = "TOKEN" }`
+    Env { env: String },
+}
+
+impl Default for McpValue {
+    fn default() -> Self {
+        Self::Literal(String::new())
+    }
+}
+
+impl From<&str> for McpValue {
+    fn from(value: &str) -> Self {
+        Self::Literal(value.to_string())
+    }
+}
+
+impl From<String> for McpValue {
+    fn from(value: String) -> Self {
+        Self::Literal(value)
+    }
+}
+
+impl Display for McpValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Literal(value) => write!(f, "{value}"),
+            Self::Secret { .. } | Self::Env { .. } => write!(f, "{REDACTED}"),
+        }
+    }
+}
+
+impl PartialEq<&str> for McpValue {
+    fn eq(&self, other: &&str) -> bool {
+        matches!(self, Self::Literal(value) if value == other)
+    }
+}
+
+pub(super) fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+impl McpValue {
+    pub fn is_reference(&self) -> bool {
+        !matches!(self, Self::Literal(_))
+    }
+
+    /// The value written to the agent JSON
+    pub fn resolve(&self, fs: &Fs) -> Result<String> {
+        match self {
+            Self::Literal(value) => Ok(value.clone()),
+            Self::Env { env } => std::env::var(env)
+                .map_err(|_| crate::format_err!("environment variable '{env}' is not set")),
+            Self::Secret { secret } => {
+                let Some(path) = secret.strip_prefix(FILE_SCHEME) else {
+                    crate::bail!("unsupported secret '{secret}', expected '{FILE_SCHEME}<path>'");
+                };
+                let path = expand_home(path);
+                let value = fs.read_to_string_sync(&path).map_err(|e| {
+                    crate::format_err!("failed to read secret {}: {e}", path.display())
+                })?;
+                Ok(value.trim_end_matches(['\r', '\n']).to_string())
+            }
+        }
+    }
+}
+
+/// Known credential prefixes
+const CREDENTIAL_PREFIXES: &[&str] = &[
+    "ghp_",
+    "gho_",
+    "ghs_",
+    "ghu_",
+    "github_pat_",
+    "glpat-",
+    "sk-",
+    "xoxb-",
+    "xoxp-",
+    "AKIA",
+    "ASIA",
+];
+/// Entry names which usually hold a credential
+const CREDENTIAL_NAMES: &[&str] = &["TOKEN", "SECRET", "PASSWORD", "API_KEY", "APIKEY"];
+
+/// Whether a literal `env` or `headers` value looks like a credential
+fn looks_like_credential(name: &str, value: &str) -> bool {
+    let token = value
+        .strip_prefix("Bearer ")
+        .or_else(|| value.strip_prefix("token "))
+        .unwrap_or(value)
+        .trim();
+    if token.is_empty() || token.contains("${") {
+        return false;
+    }
+    if CREDENTIAL_PREFIXES.iter().any(|p| token.starts_with(p)) {
+        return true;
+    }
+    let name = name.to_uppercase();
+    if CREDENTIAL_NAMES.iter().any(|n| name.contains(n)) || name == "AUTHORIZATION" {
+        return true;
+    }
+    // Long opaque strings mixing letters and digits
+    token.len() >= 32
+        && !token.contains(char::is_whitespace)
+        && token.chars().any(|c| c.is_ascii_digit())
+        && token.chars().any(|c| c.is_ascii_alphabetic())
+        && !token.contains('/')
+}
+
+fn resolve_map(fs: &Fs, map: &mut HashMap<String, McpValue>, field: &str) -> Result<()> {
+    for (key, value) in map.iter_mut() {
+        if value.is_reference() {
+            *value = McpValue::Literal(
+                value
+                    .resolve(fs)
+                    .map_err(|e| crate::format_err!("{field}.{key}: {e}"))?,
+            );
+        }
+    }
+    Ok(())
+}
+
+impl Manifest {
+    /// Replace secret references in MCP `env` and `headers` with their values
+    pub fn resolve_secrets(&mut self, fs: &Fs) -> Result<()> {
+        for (name, mcp) in &mut self.mcp_servers {
+            resolve_map(fs, &mut mcp.env, &format!("mcpServers.{name}.env"))
+                .map_err(|e| crate::format_err!("Agent '{}': {e}", self.name))?;
+            resolve_map(fs, &mut mcp.headers, &format!("mcpServers.{name}.headers"))
+                .map_err(|e| crate::format_err!("Agent '{}': {e}", self.name))?;
+        }
+        Ok(())
+    }
+
+    /// MCP `env` and `headers` entries holding a literal that looks like a
+    /// credential, by TOML path
+    pub fn plaintext_secrets(&self) -> Vec<String> {
+        let mut found = Vec::new();
+        for (name, mcp) in &self.mcp_servers {
+            for (field, map) in [("env", &mcp.env), ("headers", &mcp.headers)] {
+                for (key, value) in map {
+                    if let McpValue::Literal(value) = value
+                        && looks_like_credential(key, value)
+                    {
+                        found.push(format!("mcpServers.{name}.{field}.{key}"));
+                    }
+                }
+            }
+        }
+        found.sort();
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::KgCustomToolConfig};
+
+    #[derive(Facet, Debug)]
+    struct Doc {
+        env: HashMap<String, McpValue>,
+    }
+
+    #[test]
+    fn test_parse_mcp_values() -> Result<()> {
+        let doc: Doc = crate::toml_parse(
+            r#"
+[env]
+PLAIN = "value"
+TOKEN = { secret = "file:~/.config/kg/secrets/gh" }
+FROM_ENV = { env = "GITHUB_TOKEN" }
+"#,
+        )?;
+        assert_eq!(doc.env["PLAIN"], McpValue::from("value"));
+        assert_eq!(doc.env["TOKEN"], McpValue::Secret {
+            secret: String::from("file:~/.config/kg/secrets/gh")
+        });
+        assert_eq!(doc.env["FROM_ENV"], McpValue::Env {
+            env: String::from("GITHUB_TOKEN")
+        });
+        assert_eq!(doc.env["TOKEN"].to_string(), REDACTED);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resolve_secrets() -> Result<()> {
+        let fs = Fs::new();
+        fs.create_dir_all("secrets").await?;
+        fs.write("secrets/gh", "ghp_filesecret\n").await?;
+        let mut manifest = Manifest {
+            name: String::from("rust"),
+            mcp_servers: HashMap::from([(String::from("github"), KgCustomToolConfig {
+                env: HashMap::from([(String::from("TOKEN"), McpValue::Secret {
+                    secret: String::from("file:secrets/gh"),
+                })]),
+                ..Default::default()
+            })]),
+            ..Default::default()
+        };
+        manifest.resolve_secrets(&fs)?;
+        assert_eq!(
+            manifest.mcp_servers["github"].env["TOKEN"],
+            "ghp_filesecret"
+        );
+
+        manifest.mcp_servers.get_mut("github").unwrap().headers =
+            HashMap::from([(String::from("Authorization"), McpValue::Env {
+                env: String::from("KG_TEST_UNSET_SECRET"),
+            })]);
+        let err = manifest.resolve_secrets(&fs).unwrap_err().to_string();
+        assert!(
+            err.contains("mcpServers.github.headers.Authorization"),
+            "{err}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_looks_like_credential() {
+        assert!(looks_like_credential("GH", "ghp_0123456789abcdef"));
+        assert!(looks_like_credential("Authorization", "Bearer abc"));
+        assert!(looks_like_credential("API_TOKEN", "hunter2"));
+        assert!(looks_like_credential(
+            "X",
+            "a1b2c3d4e5f6a7b8c9d0e1f2a3b4c5d6e7f8"
+        ));
+        assert!(!looks_like_credential("API_TOKEN", "${API_TOKEN}"));
+        assert!(!looks_like_credential("RUST_LOG", "debug"));
+        assert!(!looks_like_credential("PATH", "/usr/local/bin:/usr/bin"));
+    }
+}
@@ -1,26 +1,92 @@
-use {facet::Facet, std::collections::HashSet};
+use {
+    super::{glob_match, removal::merge_set},
+    facet::Facet,
+    std::collections::HashSet,
+};
 
+/// Agents the `subagent` tool may run. Entries are agent names or globs such
+/// as `docs-*`
 #[derive(Facet, Clone, Debug, Default, PartialEq, Eq)]
 #[facet(default, deny_unknown_fields)]
 pub struct SubagentConfig {
+    /// Agents the subagent tool may run, emitted as `availableAgents`
     #[facet(default)]
     pub allow: HashSet<String>,
+    /// Agents removed from `allow` and `trusted`, exactly or by glob
     #[facet(default)]
     pub deny: HashSet<String>,
+    /// Agents run without asking, emitted as `trustedAgents`
+    #[facet(default)]
+    pub trusted: HashSet<String>,
 }
 
 impl SubagentConfig {
     pub fn merge(mut self, other: Self) -> Self {
         if !other.allow.is_empty() {
             tracing::trace!(count = other.allow.len(), "merging subagent allow");
-            self.allow.extend(other.allow);
+            merge_set(&mut self.allow, other.allow);
         }
         if !other.deny.is_empty() {
             tracing::trace!(count = other.deny.len(), "merging subagent deny");
-            self.deny.extend(other.deny);
+            merge_set(&mut self.deny, other.deny);
+        }
+        if !other.trusted.is_empty() {
+            tracing::trace!(count = other.trusted.len(), "merging subagent trusted");
+            merge_set(&mut self.trusted, other.trusted);
         }
         self
     }
+
+    fn is_denied(&self, agent: &str) -> bool {
+        self.deny.iter().any(|deny| glob_match(deny, agent))
+    }
+
+    /// `allow` without the entries `deny` matches
+    pub fn available(&self) -> HashSet<String> {
+        self.allow
+            .iter()
+            .filter(|a| !self.is_denied(a))
+            .cloned()
+            .collect()
+    }
+
+    /// `trusted` without the entries `deny` matches
+    pub fn trusted(&self) -> HashSet<String> {
+        self.trusted
+            .iter()
+            .filter(|a| !self.is_denied(a))
+            .cloned()
+            .collect()
+    }
+
+    /// Kiro has no deny list, so an `allow` or `trusted` glob matching one of
+    /// the `known` agents a deny matches is replaced by the known agents it
+    /// matches minus the denied ones. Returns the expanded globs
+    pub fn expand_denied_globs(&mut self, known: &[&str]) -> Vec<String> {
+        let deny = &self.deny;
+        let denied = |agent: &str| deny.iter().any(|d| glob_match(d, agent));
+        let mut expanded = Vec::new();
+        for list in [&mut self.allow, &mut self.trusted] {
+            let globs: Vec<String> = list
+                .iter()
+                .filter(|e| e.contains('*') && known.iter().any(|n| glob_match(e, n) && denied(n)))
+                .cloned()
+                .collect();
+            for glob in globs {
+                list.remove(&glob);
+                list.extend(
+                    known
+                        .iter()
+                        .filter(|n| glob_match(&glob, n) && !denied(n))
+                        .map(|n| n.to_string()),
+                );
+                expanded.push(glob);
+            }
+        }
+        expanded.sort();
+        expanded.dedup();
+        expanded
+    }
 }
 
 #[cfg(test)]
@@ -76,4 +142,37 @@ mod tests {
         assert!(merged.deny.contains("admin"));
         assert!(merged.deny.contains("root"));
     }
+
+    #[test]
+    fn test_deny_subtraction() {
+        let config = SubagentConfig {
+            allow: HashSet::from(["docs-*".into(), "pr-review".into(), "admin-tool".into()]),
+            deny: HashSet::from(["admin-*".into()]),
+            trusted: HashSet::from(["pr-review".into(), "admin-tool".into()]),
+        };
+        let mut available: Vec<String> = config.available().into_iter().collect();
+        available.sort();
+        assert_eq!(available, vec!["docs-*", "pr-review"]);
+        assert_eq!(config.trusted(), HashSet::from(["pr-review".into()]));
+
+        let merged = SubagentConfig::default().merge(config);
+        assert_eq!(merged.trusted.len(), 2);
+    }
+
+    #[test]
+    fn test_expand_denied_globs() {
+        let mut config = SubagentConfig {
+            allow: HashSet::from(["docs-*".into(), "pr-*".into()]),
+            deny: HashSet::from(["docs-internal".into()]),
+            trusted: HashSet::from(["docs-*".into()]),
+        };
+        let known = ["docs-api", "docs-internal", "pr-review", "orchestrator"];
+        assert_eq!(config.expand_denied_globs(&known), vec!["docs-*"]);
+        assert_eq!(
+            config.allow,
+            HashSet::from(["docs-api".into(), "pr-*".into()])
+        );
+        assert_eq!(config.trusted, HashSet::from(["docs-api".into()]));
+        assert!(config.expand_denied_globs(&known).is_empty());
+    }
 }
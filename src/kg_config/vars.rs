@@ -0,0 +1,259 @@
+use {
+    super::{Manifest, McpValue},
+    std::fmt::Display,
+};
+
+/// `$${NAME}` is written as a literal `${NAME}`
+const ESCAPED: &str = "$${";
+const OPEN: &str = "${";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VarError {
+    Undefined(String),
+    Unterminated,
+}
+
+impl Display for VarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Undefined(name) => write!(f, "undefined variable '{name}'"),
+            Self::Unterminated => write!(f, "unterminated '${{'"),
+        }
+    }
+}
+
+/// Expand `${NAME}` references in `input`.
+///
+/// When `keep_undefined` is set unknown references are left as they are,
+/// otherwise they are an error.
+pub fn interpolate(
+    input: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+    keep_undefined: bool,
+) -> Result<String, VarError> {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if rest.starts_with(ESCAPED) {
+            out.push_str(OPEN);
+            rest = &rest[ESCAPED.len()..];
+            continue;
+        }
+        if !rest.starts_with(OPEN) {
+            out.push('$');
+            rest = &rest[1..];
+            continue;
+        }
+        let Some(end) = rest.find('}') else {
+            return Err(VarError::Unterminated);
+        };
+        let name = &rest[OPEN.len()..end];
+        match lookup(name) {
+            Some(value) => out.push_str(&value),
+            None if keep_undefined => out.push_str(&rest[..=end]),
+            None => return Err(VarError::Undefined(name.to_string())),
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Built-in variables, these cannot be overridden
+fn builtin(name: &str, agent: &str) -> Option<String> {
+    match name {
+        "agent.name" => Some(agent.to_string()),
+        "project_root" => std::env::current_dir()
+            .ok()
+            .map(|p| p.display().to_string()),
+        "home" => dirs::home_dir().map(|p| p.display().to_string()),
+        _ => None,
+    }
+}
+
+fn interpolate_set<S>(
+    set: &mut S,
+    f: &mut impl FnMut(&mut String) -> Result<(), VarError>,
+) -> Result<(), VarError>
+where
+    S: Default + IntoIterator<Item = String> + FromIterator<String>,
+{
+    *set = std::mem::take(set)
+        .into_iter()
+        .map(|mut s| f(&mut s).map(|_| s))
+        .collect::<Result<_, _>>()?;
+    Ok(())
+}
+
+impl Manifest {
+    /// Visit every string supporting `${NAME}` interpolation with its TOML
+    /// path. The flag marks MCP `env` and `headers`, which Kiro expands
+    /// itself at runtime.
+    fn visit_strings(
+        &mut self,
+        f: &mut impl FnMut(&str, &mut String, bool) -> Result<(), VarError>,
+    ) -> Result<(), (String, VarError)> {
+        let at = |field: String| move |e| (field, e);
+        for (field, value) in [
+            ("description", self.description.as_mut()),
+            ("prompt", self.prompt.as_mut().and_then(|p| p.text_mut())),
+        ] {
+            if let Some(value) = value {
+                f(field, value, false).map_err(at(field.to_string()))?;
+            }
+        }
+        for (name, resource) in &mut self.resources {
+            let field = format!("resources.{name}.locations");
+            interpolate_set(&mut resource.locations, &mut |s| f(&field, s, false))
+                .map_err(at(field.clone()))?;
+        }
+        for (name, skill) in &mut self.skills {
+            let field = format!("skills.{name}.locations");
+            interpolate_set(&mut skill.locations, &mut |s| f(&field, s, false))
+                .map_err(at(field.clone()))?;
+        }
+        for (name, knowledge) in &mut self.knowledge {
+            if let Some(source) = &mut knowledge.source {
+                let field = format!("knowledge.{name}.source");
+                f(&field, source, false).map_err(at(field.clone()))?;
+            }
+        }
+        for (name, mcp) in &mut self.mcp_servers {
+            for (key, value) in [("command", &mut mcp.command), ("url", &mut mcp.url)] {
+                let field = format!("mcpServers.{name}.{key}");
+                f(&field, value, false).map_err(at(field.clone()))?;
+            }
+            let field = format!("mcpServers.{name}.args");
+            for arg in &mut mcp.args {
+                f(&field, arg, false).map_err(at(field.clone()))?;
+            }
+            for (key, map) in [("env", &mut mcp.env), ("headers", &mut mcp.headers)] {
+                for (k, value) in map.iter_mut() {
+                    let field = format!("mcpServers.{name}.{key}.{k}");
+                    match value {
+                        McpValue::Literal(value) => f(&field, value, true),
+                        McpValue::Secret { secret } => f(&field, secret, false),
+                        McpValue::Env { .. } => Ok(()),
+                    }
+                    .map_err(at(field.clone()))?;
+                }
+            }
+        }
+        for (trigger, hooks) in &mut self.hooks {
+            for (name, hook) in hooks.iter_mut() {
+                let field = format!("hooks.{trigger}.{name}.command");
+                f(&field, &mut hook.command, false).map_err(at(field.clone()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Expand `${NAME}` references using the built-in variables, `vars` and
+    /// the environment, in that order. Returns the field of the first
+    /// reference that cannot be expanded.
+    pub fn interpolate_vars(&mut self) -> Result<(), (String, VarError)> {
+        let vars = self.vars.clone();
+        let agent = self.name.clone();
+        let kg_lookup = |name: &str| builtin(name, &agent).or_else(|| vars.get(name).cloned());
+        let lookup = |name: &str| kg_lookup(name).or_else(|| std::env::var(name).ok());
+        self.visit_strings(&mut |_, value, runtime| {
+            if !value.contains('$') {
+                return Ok(());
+            }
+            *value = if runtime {
+                interpolate(value, &kg_lookup, true)?
+            } else {
+                interpolate(value, &lookup, false)?
+            };
+            Ok(())
+        })
+    }
+
+    /// Same as [`Self::interpolate_vars`] using the variables of `agent`,
+    /// used to find which source file declared a failing reference
+    pub fn interpolate_vars_as(&self, agent: &Manifest) -> Result<(), (String, VarError)> {
+        let mut copy = self.clone();
+        copy.name = agent.name.clone();
+        copy.vars = agent.vars.clone();
+        copy.interpolate_vars()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{KgCustomToolConfig, KgPrompt},
+        std::collections::HashMap,
+    };
+
+    fn lookup(name: &str) -> Option<String> {
+        (name == "user").then(|| String::from("ferris"))
+    }
+
+    #[test]
+    fn test_interpolate() {
+        assert_eq!(
+            interpolate("/home/${user}/src", &lookup, false),
+            Ok(String::from("/home/ferris/src"))
+        );
+        assert_eq!(
+            interpolate("cost $5 $${user} ${user}", &lookup, false),
+            Ok(String::from("cost $5 ${user} ferris"))
+        );
+        assert_eq!(
+            interpolate("${nope}", &lookup, false),
+            Err(VarError::Undefined(String::from("nope")))
+        );
+        assert_eq!(
+            interpolate("Bearer ${TOKEN}", &lookup, true),
+            Ok(String::from("Bearer ${TOKEN}"))
+        );
+        assert_eq!(
+            interpolate("${user", &lookup, false),
+            Err(VarError::Unterminated)
+        );
+    }
+
+    #[test]
+    fn test_interpolate_vars() {
+        let mut manifest = Manifest {
+            name: String::from("rust"),
+            prompt: Some(KgPrompt::from("You are ${agent.name} at ${org}")),
+            vars: HashMap::from([(String::from("org"), String::from("acme"))]),
+            mcp_servers: HashMap::from([(String::from("api"), KgCustomToolConfig {
+                command: String::from("${org}-mcp"),
+                env: HashMap::from([
+                    (String::from("ORG"), McpValue::from("${org}")),
+                    (
+                        String::from("TOKEN"),
+                        McpValue::from("${KG_TEST_UNSET_TOKEN}"),
+                    ),
+                ]),
+                ..Default::default()
+            })]),
+            ..Default::default()
+        };
+        assert_eq!(manifest.interpolate_vars(), Ok(()));
+        assert_eq!(
+            manifest.prompt.as_ref().and_then(|p| p.text()),
+            Some("You are rust at acme")
+        );
+        let api = &manifest.mcp_servers["api"];
+        assert_eq!(api.command, "acme-mcp");
+        assert_eq!(api.env["ORG"], "acme");
+        // Left for Kiro to expand at runtime
+        assert_eq!(api.env["TOKEN"], "${KG_TEST_UNSET_TOKEN}");
+
+        manifest.prompt = Some(KgPrompt::from("${KG_TEST_UNSET_VAR}"));
+        assert_eq!(
+            manifest.interpolate_vars(),
+            Err((
+                String::from("prompt"),
+                VarError::Undefined(String::from("KG_TEST_UNSET_VAR"))
+            ))
+        );
+    }
+}
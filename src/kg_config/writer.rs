@@ -0,0 +1,249 @@
+use {
+    crate::Result,
+    facet::Facet,
+    facet_value::{DestructuredRef, VArray, VObject, Value},
+};
+
+/// Keys whose array values are ordered and must not be sorted
+const ORDERED_KEYS: [&str; 1] = ["args"];
+/// Keys holding raw, user supplied values which are written as-is
+const RAW_KEYS: [&str; 1] = ["toolSettings"];
+/// Inline arrays longer than this are split one element per line
+const MAX_INLINE_WIDTH: usize = 80;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Drop empty values, sort keys and arrays
+    Canonical,
+    /// Sort keys only
+    Raw,
+}
+
+/// Serialize a kg document to TOML.
+///
+/// `facet_toml` refuses to write `None` fields, so the document is converted
+/// to a [`Value`] first. Nulls and empty strings, arrays and tables are
+/// dropped, keys are sorted and set-like arrays are sorted, which keeps the
+/// output stable between runs. Nested tables are written with `[a.b]`
+/// headers.
+pub fn toml_to_string<'a, T: Facet<'a>>(doc: &T) -> Result<String> {
+    let value = facet_value::to_value(doc)?;
+    write_document(&value, Mode::Canonical)
+}
+
+fn write_document(value: &Value, mode: Mode) -> Result<String> {
+    let root = canonicalize(value, mode).unwrap_or_else(|| VObject::new().into_value());
+    let Some(root) = root.as_object() else {
+        return Err(crate::format_err!("only tables can be written as TOML"));
+    };
+    let mut out = String::new();
+    write_table(&mut out, &mut Vec::new(), root);
+    Ok(out)
+}
+
+/// Format a TOML key, quoting it when it is not a valid bare key
+pub fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        toml_string(key)
+    }
+}
+
+fn canonicalize(value: &Value, mode: Mode) -> Option<Value> {
+    let prune = mode == Mode::Canonical;
+    match value.destructure_ref() {
+        DestructuredRef::Null => None,
+        DestructuredRef::String(s) if prune && s.is_empty() => None,
+        DestructuredRef::Array(array) => {
+            let mut items: Vec<Value> =
+                array.iter().filter_map(|v| canonicalize(v, mode)).collect();
+            if prune {
+                if items.is_empty() {
+                    return None;
+                }
+                items.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            }
+            Some(items.into_iter().collect::<VArray>().into_value())
+        }
+        DestructuredRef::Object(object) => {
+            let mut entries: Vec<(String, Value)> = object
+                .iter()
+                .filter_map(|(k, v)| {
+                    let child = match mode {
+                        Mode::Canonical if RAW_KEYS.contains(&k.as_str()) => {
+                            canonicalize(v, Mode::Raw)
+                        }
+                        Mode::Canonical if ORDERED_KEYS.contains(&k.as_str()) => {
+                            canonicalize_ordered(v)
+                        }
+                        _ => canonicalize(v, mode),
+                    };
+                    child.map(|c| (k.to_string(), c))
+                })
+                .collect();
+            if prune && entries.is_empty() {
+                return None;
+            }
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Some(entries.into_iter().collect::<VObject>().into_value())
+        }
+        _ => Some(value.clone()),
+    }
+}
+
+/// Like [`canonicalize`] but keeps element order
+fn canonicalize_ordered(value: &Value) -> Option<Value> {
+    match value.as_array() {
+        Some(array) if array.is_empty() => None,
+        Some(array) => Some(
+            array
+                .iter()
+                .filter_map(|v| canonicalize(v, Mode::Canonical))
+                .collect::<VArray>()
+                .into_value(),
+        ),
+        None => canonicalize(value, Mode::Canonical),
+    }
+}
+
+fn write_table(out: &mut String, path: &mut Vec<String>, table: &VObject) {
+    let (tables, entries): (Vec<_>, Vec<_>) = table.iter().partition(|(_, v)| v.is_object());
+
+    if !path.is_empty() && (!entries.is_empty() || table.is_empty()) {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        let header: Vec<String> = path.iter().map(|k| toml_key(k)).collect();
+        out.push_str(&format!("[{}]\n", header.join(".")));
+    }
+
+    for (key, value) in entries {
+        let key = toml_key(key.as_str());
+        let inline = inline_value(value);
+        match value.as_array() {
+            Some(array) if key.len() + inline.len() + 3 > MAX_INLINE_WIDTH => {
+                out.push_str(&format!("{key} = [\n"));
+                for item in array.iter() {
+                    out.push_str(&format!("    {},\n", inline_value(item)));
+                }
+                out.push_str("]\n");
+            }
+            _ => out.push_str(&format!("{key} = {inline}\n")),
+        }
+    }
+
+    for (key, value) in tables {
+        if let Some(child) = value.as_object() {
+            path.push(key.to_string());
+            write_table(out, path, child);
+            path.pop();
+        }
+    }
+}
+
+fn inline_value(value: &Value) -> String {
+    match value.destructure_ref() {
+        DestructuredRef::Bool(b) => b.to_string(),
+        DestructuredRef::Number(n) if n.is_integer() => n
+            .to_i64()
+            .map(|i| i.to_string())
+            .or_else(|| n.to_u64().map(|u| u.to_string()))
+            .unwrap_or_default(),
+        DestructuredRef::Number(n) => {
+            let f = n.to_f64_lossy();
+            if f.fract() == 0.0 && f.is_finite() {
+                format!("{f:.1}")
+            } else {
+                f.to_string()
+            }
+        }
+        DestructuredRef::String(s) => toml_string(s.as_str()),
+        DestructuredRef::Array(array) => {
+            let items: Vec<String> = array.iter().map(inline_value).collect();
+            format!("[{}]", items.join(", "))
+        }
+        DestructuredRef::Object(object) => {
+            let items: Vec<String> = object
+                .iter()
+                .map(|(k, v)| format!("{} = {}", toml_key(k.as_str()), inline_value(v)))
+                .collect();
+            if items.is_empty() {
+                "{}".to_string()
+            } else {
+                format!("{{ {} }}", items.join(", "))
+            }
+        }
+        _ => toml_string(&facet_value::format_value(value)),
+    }
+}
+
+fn toml_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{KgAgentFileDoc, KgCustomToolConfig, toml_parse},
+    };
+
+    #[test_log::test]
+    fn test_toml_to_string_round_trip() -> Result<()> {
+        let agent_str = include_str!("../../fixtures/agent-test/agent-test.toml");
+        let doc: KgAgentFileDoc = toml_parse(agent_str)?;
+        let out = toml_to_string(&doc)?;
+        let parsed: KgAgentFileDoc = toml_parse(&out)?;
+        assert_eq!(toml_to_string(&parsed)?, out);
+        assert_eq!(parsed.native_tools, doc.native_tools);
+        assert_eq!(parsed.subagents, doc.subagents);
+        assert_eq!(parsed.description, doc.description);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_toml_to_string_keeps_arg_order_and_drops_empty() -> Result<()> {
+        let mut doc = KgAgentFileDoc::default();
+        doc.mcp_servers
+            .insert("srv".to_string(), KgCustomToolConfig {
+                command: "server".to_string(),
+                args: vec!["--z".to_string(), "--a".to_string()],
+                ..Default::default()
+            });
+        doc.tool_settings
+            .insert("custom".to_string(), facet_value::value!({}));
+        let out = toml_to_string(&doc)?;
+        assert!(out.contains("[mcpServers.srv]"));
+        assert!(out.contains(r#"args = ["--z", "--a"]"#));
+        assert!(!out.contains("url"));
+        assert!(out.contains("[toolSettings.custom]"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_toml_key_quotes_when_needed() {
+        assert_eq!(toml_key("agent-1_x"), "agent-1_x");
+        assert_eq!(toml_key("$schema"), "\"$schema\"");
+        assert_eq!(toml_key("a.b"), "\"a.b\"");
+        assert_eq!(toml_string("say \"hi\"\n"), r#""say \"hi\"\n""#);
+    }
+}
@@ -1,4 +1,8 @@
-use {crate::kg_config::KgCustomToolConfig, facet::Facet, std::collections::HashMap};
+use {
+    crate::kg_config::{KgCustomToolConfig, sorted},
+    facet::Facet,
+    std::collections::BTreeMap,
+};
 
 #[derive(Facet, Default, Clone, Debug, Eq, PartialEq)]
 #[facet(default, skip_all_unless_truthy, deny_unknown_fields)]
@@ -6,29 +10,45 @@ pub struct CustomToolConfig {
     /// The URL for HTTP-based MCP server communication
     pub url: String,
     /// HTTP headers to include when communicating with HTTP-based MCP servers
-    pub headers: HashMap<String, String>,
+    pub headers: BTreeMap<String, String>,
     /// The command string used to initialize the mcp server
     pub command: String,
     /// A list of arguments to be used to run the command with
     pub args: Vec<String>,
     /// A list of environment variables to run the command with
-    pub env: HashMap<String, String>,
+    pub env: BTreeMap<String, String>,
     /// Timeout for each mcp request in ms
     pub timeout: Option<u64>,
     /// A boolean flag to denote whether or not to load this mcp server
     pub disabled: Option<bool>,
+    /// Tools of this server to run without asking for permission
+    #[facet(rename = "autoApprove")]
+    pub auto_approve: Vec<String>,
+    /// Tools of this server not to load
+    #[facet(rename = "disabledTools")]
+    pub disabled_tools: Vec<String>,
 }
 
 impl From<KgCustomToolConfig> for CustomToolConfig {
     fn from(kg: KgCustomToolConfig) -> Self {
         Self {
             url: kg.url,
-            headers: kg.headers,
+            headers: kg
+                .headers
+                .into_iter()
+                .map(|(k, v)| (k, v.to_string()))
+                .collect(),
             command: kg.command,
             args: kg.args,
-            env: kg.env,
+            env: kg
+                .env
+                .into_iter()
+                .map(|(k, v)| (k, v.to_string()))
+                .collect(),
             timeout: kg.timeout,
             disabled: kg.state.map(|s| s.is_disabled()),
+            auto_approve: sorted(kg.auto_approve),
+            disabled_tools: sorted(kg.disabled_tools),
         }
     }
 }
@@ -38,12 +58,13 @@ mod tests {
     use {
         super::*,
         crate::{Result, toml_parse},
+        std::collections::HashSet,
     };
 
     #[derive(Facet, Debug)]
     struct McpDoc {
         #[facet(default, rename = "mcpServers")]
-        mcp_servers: HashMap<String, CustomToolConfig>,
+        mcp_servers: BTreeMap<String, CustomToolConfig>,
     }
 
     #[test]
@@ -128,4 +149,27 @@ timeout  =1000
         assert_eq!(mcp.disabled, Some(true));
         Ok(())
     }
+
+    #[test]
+    fn tool_lists_to_json() -> Result<()> {
+        let kg = KgCustomToolConfig {
+            command: "docs-mcp".into(),
+            auto_approve: HashSet::from(["search", "list"].map(String::from)),
+            disabled_tools: HashSet::from([String::from("delete_page")]),
+            ..Default::default()
+        };
+        let json = facet_json::to_string(&CustomToolConfig::from(kg))?;
+        assert!(
+            json.contains(r#""autoApprove":["list","search"]"#),
+            "{json}"
+        );
+        assert!(
+            json.contains(r#""disabledTools":["delete_page"]"#),
+            "{json}"
+        );
+
+        let json = facet_json::to_string(&CustomToolConfig::from(KgCustomToolConfig::default()))?;
+        assert!(!json.contains("autoApprove"), "{json}");
+        Ok(())
+    }
 }
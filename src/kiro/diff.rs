@@ -1,8 +1,9 @@
 use {
-    super::{CustomToolConfig, KiroAgent, Knowledge, tools::*},
-    crate::kg_config::McpServerState,
+    super::{CustomToolConfig, KiroAgent, Knowledge, sort_value, tools::*},
+    crate::kg_config::{McpServerState, REDACTED},
     facet::Facet,
-    std::collections::{BTreeSet, HashMap, HashSet},
+    facet_value::Value,
+    std::collections::{BTreeMap, BTreeSet, HashMap, HashSet},
 };
 
 #[derive(Facet, Debug, Clone, Default)]
@@ -22,6 +23,10 @@ pub struct NormalizedMcpServer {
     pub timeout: Option<u64>,
     #[facet(default, skip_serializing_if = Option::is_none)]
     pub state: Option<McpServerState>,
+    #[facet(default, skip_serializing_if = Vec::is_empty)]
+    pub auto_approve: Vec<String>,
+    #[facet(default, skip_serializing_if = Vec::is_empty)]
+    pub disabled_tools: Vec<String>,
 }
 
 impl NormalizedMcpServer {
@@ -38,6 +43,10 @@ impl NormalizedMcpServer {
             .map(|(k, v)| format!("{k}={v}"))
             .collect();
         headers.sort();
+        let mut auto_approve = config.auto_approve;
+        auto_approve.sort();
+        let mut disabled_tools = config.disabled_tools;
+        disabled_tools.sort();
         let state = match config.disabled {
             Some(true) => Some(McpServerState::Disabled),
             Some(false) => Some(McpServerState::Enabled),
@@ -52,6 +61,8 @@ impl NormalizedMcpServer {
             headers,
             timeout: config.timeout,
             state,
+            auto_approve,
+            disabled_tools,
         }
     }
 }
@@ -73,20 +84,15 @@ pub struct NormalizedHook {
 /// This struct addresses the instability issues in `Agent` by:
 /// - Using concrete types instead of `facet_value::Value` for native tools
 /// - Sorting all collections (resources, knowledge) for consistent ordering
-/// - Separating known native tools from custom MCP tools
+/// - Separating known native tools from other `toolsSettings` entries
 ///
 /// # Stability Guarantees
 /// - `resources` and `knowledge` are sorted, ensuring identical agents produce
 ///   identical diffs
-/// - Native tools (shell, aws, read, write) have concrete types for precise
-///   field-level diffs
-/// - `other_tools` only tracks presence/absence of custom MCP tools (not their
-///   detailed settings)
-///
-/// # Trade-offs
-/// - Custom MCP tool settings changes are not detected (only
-///   additions/removals)
-/// - This is acceptable as custom tools are rare (~1% use case)
+/// - Native tools (shell, aws, read, write, glob, grep, web_fetch, subagent)
+///   have concrete types for precise field-level diffs
+/// - `other_tools` keeps the full settings of every other tool, with arrays and
+///   object keys sorted, so any change to them is detected
 #[derive(Facet, Debug, Clone, Default)]
 #[facet(default, skip_all_unless_truthy)]
 pub struct NormalizedAgent {
@@ -101,55 +107,72 @@ pub struct NormalizedAgent {
     pub aws: Option<NormalizedAwsTool>,
     pub read: Option<NormalizedReadTool>,
     pub write: Option<NormalizedWriteTool>,
+    pub glob: Option<NormalizedGlobTool>,
+    pub grep: Option<NormalizedGrepTool>,
+    pub web_fetch: Option<NormalizedWebFetchTool>,
     pub subagent: Option<NormalizedSubagentTool>,
-    pub other_tools: BTreeSet<String>,
+    pub other_tools: BTreeMap<String, Value>,
     pub model: Option<String>,
     pub mcp_servers: HashMap<String, NormalizedMcpServer>,
     pub tool_aliases: HashMap<String, String>,
     pub hooks: Vec<NormalizedHook>,
     pub include_mcp_json: bool,
+    pub keyboard_shortcut: Option<String>,
+    pub welcome_message: Option<String>,
 }
 
 impl KiroAgent {
+    /// Redact the MCP `env` and `headers` values which are secret references
+    /// in `generated`, so diffs neither show them nor report them as changed
+    pub fn redact_like(mut self, generated: &KiroAgent) -> Self {
+        for (name, mcp) in &mut self.mcp_servers {
+            let Some(other) = generated.mcp_servers.get(name) else {
+                continue;
+            };
+            for (map, other) in [
+                (&mut mcp.env, &other.env),
+                (&mut mcp.headers, &other.headers),
+            ] {
+                for (key, value) in map.iter_mut() {
+                    if other.get(key).is_some_and(|v| v == REDACTED) {
+                        *value = REDACTED.to_string();
+                    }
+                }
+            }
+        }
+        self
+    }
+
     pub fn normalize(self) -> NormalizedAgent {
         let mut shell = None;
         let mut aws = None;
         let mut read = None;
         let mut write = None;
+        let mut glob = None;
+        let mut grep = None;
+        let mut web_fetch = None;
         let mut subagent = None;
-        let mut other_tools = BTreeSet::new();
+        let mut other_tools = BTreeMap::new();
 
         for (tool_name, value) in self.tools_settings {
             let json = facet_json::to_string(&value).unwrap_or_default();
-            match tool_name.as_str() {
-                "shell" => {
-                    if let Ok(tool) = facet_json::from_str::<ExecuteShellTool>(&json) {
-                        shell = Some(tool.into());
-                    }
-                }
-                "aws" => {
-                    if let Ok(tool) = facet_json::from_str::<AwsTool>(&json) {
-                        aws = Some(tool.into());
-                    }
-                }
-                "read" => {
-                    if let Ok(tool) = facet_json::from_str::<ReadTool>(&json) {
-                        read = Some(tool.into());
-                    }
-                }
-                "write" => {
-                    if let Ok(tool) = facet_json::from_str::<WriteTool>(&json) {
-                        write = Some(tool.into());
-                    }
-                }
-                "subagent" => {
-                    if let Ok(tool) = facet_json::from_str::<SubagentTool>(&json) {
-                        subagent = Some(tool.into());
-                    }
-                }
-                _ => {
-                    other_tools.insert(tool_name);
-                }
+            let known = match tool_name.as_str() {
+                "shell" => decode::<ExecuteShellTool, _>(&json, &mut shell),
+                "aws" => decode::<AwsTool, _>(&json, &mut aws),
+                "read" => decode::<ReadTool, _>(&json, &mut read),
+                "write" => decode::<WriteTool, _>(&json, &mut write),
+                "glob" => decode::<GlobTool, _>(&json, &mut glob),
+                "grep" => decode::<GrepTool, _>(&json, &mut grep),
+                "web_fetch" => decode::<WebFetchTool, _>(&json, &mut web_fetch),
+                "subagent" => decode::<SubagentTool, _>(&json, &mut subagent),
+                _ => false,
+            };
+            // Settings which do not decode are compared as they are
+            if !known {
+                let mut value = value;
+                // Settings which only differ in order compare equal
+                sort_value(&mut value, true);
+                other_tools.insert(tool_name, value);
             }
         }
 
@@ -195,7 +218,9 @@ impl KiroAgent {
                 })
             })
             .collect();
-        hooks.sort_by(|a, b| a.trigger.cmp(&b.trigger).then(a.command.cmp(&b.command)));
+        // Kiro runs the hooks of a trigger in order, so only the triggers are
+        // sorted
+        hooks.sort_by(|a, b| a.trigger.cmp(&b.trigger));
 
         NormalizedAgent {
             name: self.name,
@@ -209,17 +234,36 @@ impl KiroAgent {
             aws,
             read,
             write,
+            glob,
+            grep,
+            web_fetch,
             subagent,
             other_tools,
             model: self.model,
             mcp_servers,
-            tool_aliases: self.tool_aliases,
+            tool_aliases: self.tool_aliases.into_iter().collect(),
             hooks,
             include_mcp_json: self.include_mcp_json,
+            keyboard_shortcut: self.keyboard_shortcut,
+            welcome_message: self.welcome_message,
         }
     }
 }
 
+/// Decode the settings of a native tool into its normalized form
+fn decode<T, N>(json: &str, slot: &mut Option<N>) -> bool
+where
+    T: Facet<'static> + Into<N>,
+{
+    match facet_json::from_str::<T>(json) {
+        Ok(tool) => {
+            *slot = Some(tool.into());
+            true
+        }
+        Err(_) => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use {super::*, rediff::FacetDiff};
@@ -239,6 +283,36 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_shortcut_and_welcome_carried_to_kiro_agent() -> crate::Result<()> {
+        let manifest = crate::Manifest {
+            name: "test".to_string(),
+            keyboard_shortcut: Some("ctrl+shift+a".to_string()),
+            welcome_message: Some("hello there".to_string()),
+            ..Default::default()
+        };
+        let agent = KiroAgent::try_from(&manifest)?;
+        assert_eq!(agent.keyboard_shortcut.as_deref(), Some("ctrl+shift+a"));
+        assert_eq!(agent.welcome_message.as_deref(), Some("hello there"));
+        agent.validate()?;
+
+        let json = facet_json::to_string(&agent)?;
+        assert!(json.contains(r#""keyboardShortcut":"ctrl+shift+a""#));
+        assert!(json.contains(r#""welcomeMessage":"hello there""#));
+
+        let normalized = agent.normalize();
+        assert_eq!(
+            normalized.keyboard_shortcut.as_deref(),
+            Some("ctrl+shift+a")
+        );
+        assert_eq!(normalized.welcome_message.as_deref(), Some("hello there"));
+
+        let json = facet_json::to_string(&KiroAgent::try_from(&crate::Manifest::default())?)?;
+        assert!(!json.contains("keyboardShortcut"));
+        assert!(!json.contains("welcomeMessage"));
+        Ok(())
+    }
+
     #[test]
     fn test_normalized_agent_diff_identical() {
         let agent1 = NormalizedAgent {
@@ -351,7 +425,10 @@ mod tests {
         let normalized = agent.normalize();
         assert_eq!(
             normalized.other_tools,
-            BTreeSet::from(["my-custom-tool".to_string()])
+            BTreeMap::from([(
+                "my-custom-tool".to_string(),
+                facet_value::Value::from("enabled")
+            )])
         );
     }
 
@@ -518,12 +595,32 @@ mod tests {
         assert!(!agent1.diff(&agent2).is_equal());
     }
 
+    #[test]
+    fn test_normalized_agent_diff_shortcut_and_welcome_changed() {
+        let agent1 = NormalizedAgent {
+            name: "test".to_string(),
+            keyboard_shortcut: Some("ctrl+a".to_string()),
+            welcome_message: Some("hello".to_string()),
+            ..Default::default()
+        };
+        let agent2 = NormalizedAgent {
+            keyboard_shortcut: Some("ctrl+b".to_string()),
+            ..agent1.clone()
+        };
+        assert!(!agent1.diff(&agent2).is_equal());
+
+        let agent3 = NormalizedAgent {
+            welcome_message: None,
+            ..agent1.clone()
+        };
+        assert!(!agent1.diff(&agent3).is_equal());
+    }
+
     #[test]
     fn test_normalize_mcp_servers_sorted() {
-        use std::collections::HashMap;
         let agent = KiroAgent {
             name: "test".to_string(),
-            mcp_servers: HashMap::from([
+            mcp_servers: BTreeMap::from([
                 ("z-server".to_string(), CustomToolConfig {
                     command: "z-cmd".to_string(),
                     ..Default::default()
@@ -542,10 +639,10 @@ mod tests {
 
     #[test]
     fn test_normalize_hooks_sorted() {
-        use {crate::kiro::hook::AgentHook, std::collections::HashMap};
+        use crate::kiro::hook::AgentHook;
         let agent = KiroAgent {
             name: "test".to_string(),
-            hooks: HashMap::from([
+            hooks: BTreeMap::from([
                 ("stop".to_string(), vec![AgentHook {
                     command: "echo stop".to_string(),
                     ..Default::default()
@@ -562,6 +659,25 @@ mod tests {
         assert_eq!(normalized.hooks[1].trigger, "stop");
     }
 
+    #[test]
+    fn test_normalize_hooks_keeps_order_within_trigger() {
+        use crate::kiro::hook::AgentHook;
+        let hook = |command: &str| AgentHook {
+            command: command.to_string(),
+            ..Default::default()
+        };
+        let agent1 = KiroAgent {
+            name: "test".to_string(),
+            hooks: BTreeMap::from([("stop".to_string(), vec![hook("fmt"), hook("test")])]),
+            ..Default::default()
+        };
+        let agent2 = KiroAgent {
+            hooks: BTreeMap::from([("stop".to_string(), vec![hook("test"), hook("fmt")])]),
+            ..agent1.clone()
+        };
+        assert!(!agent1.normalize().diff(&agent2.normalize()).is_equal());
+    }
+
     #[test]
     fn test_normalize_malformed_knowledge() {
         use facet_value::Value;
@@ -580,4 +696,63 @@ mod tests {
         assert!(normalized.resources.contains("file://valid.md"));
         assert!(normalized.knowledge.is_empty());
     }
+
+    fn with_settings(settings: &[(&str, Value)]) -> NormalizedAgent {
+        KiroAgent {
+            name: "test".to_string(),
+            tools_settings: settings
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect(),
+            ..Default::default()
+        }
+        .normalize()
+    }
+
+    #[test]
+    fn test_normalize_other_tools_compared_deeply() {
+        let before = with_settings(&[(
+            "@git",
+            facet_value::value!({"allowed": ["status", "log"], "mode": "ro"}),
+        )]);
+        let reordered = with_settings(&[(
+            "@git",
+            facet_value::value!({"mode": "ro", "allowed": ["log", "status"]}),
+        )]);
+        assert!(before.diff(&reordered).is_equal());
+
+        let changed = with_settings(&[(
+            "@git",
+            facet_value::value!({"allowed": ["status"], "mode": "ro"}),
+        )]);
+        assert!(!before.diff(&changed).is_equal());
+    }
+
+    #[test]
+    fn test_normalize_glob_grep_web_fetch() {
+        let before = with_settings(&[
+            (
+                "glob",
+                facet_value::value!({"allowedPaths": ["src/**", "docs/**"]}),
+            ),
+            ("grep", facet_value::value!({"allowReadOnly": true})),
+            ("web_fetch", facet_value::value!({"trusted": ["docs.rs"]})),
+        ]);
+        assert_eq!(
+            before.glob.as_ref().map(|g| g.allowed_paths.clone()),
+            Some(vec!["docs/**".to_string(), "src/**".to_string()])
+        );
+        assert!(before.grep.as_ref().is_some_and(|g| g.allow_read_only));
+        assert!(before.other_tools.is_empty());
+
+        let after = with_settings(&[
+            (
+                "glob",
+                facet_value::value!({"allowedPaths": ["src/**", "docs/**"]}),
+            ),
+            ("grep", facet_value::value!({"allowReadOnly": true})),
+            ("web_fetch", facet_value::value!({"trusted": ["crates.io"]})),
+        ]);
+        assert!(!before.diff(&after).is_equal());
+    }
 }
@@ -5,10 +5,9 @@ pub mod tools;
 pub const DEFAULT_APPROVE: [&str; 0] = [];
 use {
     crate::{Manifest, Result, kiro::hook::AgentHook},
-    color_eyre::eyre::WrapErr,
     facet::Facet,
     std::{
-        collections::{HashMap, HashSet},
+        collections::{BTreeMap, BTreeSet},
         fmt::Display,
     },
 };
@@ -28,36 +27,43 @@ pub struct KiroAgent {
     #[facet(default, skip_serializing_if = Option::is_none)]
     pub prompt: Option<String>,
     /// Configuration for Model Context Protocol (MCP) servers
-    #[facet(default, rename = "mcpServers", skip_serializing_if = HashMap::is_empty)]
-    pub mcp_servers: HashMap<String, CustomToolConfig>,
+    #[facet(default, rename = "mcpServers", skip_serializing_if = BTreeMap::is_empty)]
+    pub mcp_servers: BTreeMap<String, CustomToolConfig>,
     /// List of tools the agent can see. Use \"@{MCP_SERVER_NAME}/tool_name\" to
     /// specify tools from mcp servers. To include all tools from a server,
     /// use \"@{MCP_SERVER_NAME}\"
-    #[facet(default, skip_serializing_if = HashSet::is_empty)]
-    pub tools: HashSet<String>,
+    #[facet(default, skip_serializing_if = BTreeSet::is_empty)]
+    pub tools: BTreeSet<String>,
     /// Tool aliases for remapping tool names
-    #[facet(default, rename = "toolAliases", skip_serializing_if = HashMap::is_empty)]
-    pub tool_aliases: HashMap<String, String>,
+    #[facet(default, rename = "toolAliases", skip_serializing_if = BTreeMap::is_empty)]
+    pub tool_aliases: BTreeMap<String, String>,
     /// List of tools the agent is explicitly allowed to use
-    #[facet(default, rename = "allowedTools", skip_serializing_if = HashSet::is_empty)]
-    pub allowed_tools: HashSet<String>,
+    #[facet(default, rename = "allowedTools", skip_serializing_if = BTreeSet::is_empty)]
+    pub allowed_tools: BTreeSet<String>,
     /// Files to include in the agent's context
     #[facet(default, skip_serializing_if = Vec::is_empty)]
     pub resources: Vec<facet_value::Value>,
     /// Commands to run when a chat session is created
-    #[facet(default, skip_serializing_if = HashMap::is_empty)]
-    pub hooks: HashMap<String, Vec<AgentHook>>,
+    #[facet(default, skip_serializing_if = BTreeMap::is_empty)]
+    pub hooks: BTreeMap<String, Vec<AgentHook>>,
     /// Settings for specific tools. These are mostly for native tools. The
     /// actual schema differs by tools and is documented in detail in our
     /// documentation
-    #[facet(default, rename = "toolsSettings", skip_serializing_if = HashMap::is_empty)]
-    pub tools_settings: HashMap<String, facet_value::Value>,
+    #[facet(default, rename = "toolsSettings", skip_serializing_if = BTreeMap::is_empty)]
+    pub tools_settings: BTreeMap<String, facet_value::Value>,
     /// The model ID to use for this agent. If not specified, uses the default
     /// model.
     #[facet(default, skip_serializing_if = Option::is_none)]
     pub model: Option<String>,
     #[facet(default, rename = "useLegacyMcpJson")]
     pub include_mcp_json: bool,
+    /// Keyboard shortcut for swapping to this agent (e.g., "ctrl+shift+a",
+    /// "shift+tab")
+    #[facet(default, rename = "keyboardShortcut", skip_serializing_if = Option::is_none)]
+    pub keyboard_shortcut: Option<String>,
+    /// Welcome message displayed when switching to this agent
+    #[facet(default, rename = "welcomeMessage", skip_serializing_if = Option::is_none)]
+    pub welcome_message: Option<String>,
 }
 
 impl Display for KiroAgent {
@@ -88,7 +94,7 @@ impl TryFrom<&Manifest> for KiroAgent {
 
     fn try_from(value: &Manifest) -> std::result::Result<Self, Self::Error> {
         let native_tools = &value.native_tools;
-        let mut tools_settings = HashMap::new();
+        let mut tools_settings = BTreeMap::new();
 
         let tool: AwsTool = native_tools.into();
         let tool_name = ToolTarget::Aws.to_string();
@@ -133,32 +139,29 @@ impl TryFrom<&Manifest> for KiroAgent {
             tools_settings.insert(tool_name.to_string(), v);
         }
         let default_agent = Self::default();
-        let tools = value.tools.clone();
-        let allowed_tools = value.allowed_tools.clone();
+        let tools: BTreeSet<String> = value.tools.iter().cloned().collect();
+        let allowed_tools: BTreeSet<String> = value.allowed_tools.iter().cloned().collect();
         let resources = value.resources()?;
 
-        let extra_tool_settings = value.tool_settings.clone();
-        tools_settings.extend(extra_tool_settings);
+        // Settings written by hand keep the key order of their TOML, sort it
+        tools_settings.extend(value.tool_settings.iter().map(|(name, settings)| {
+            let mut settings = settings.clone();
+            sort_value(&mut settings, false);
+            (name.clone(), settings)
+        }));
 
         // Convert subagents to toolsSettings.subagent format
-        if !value.subagents.allow.is_empty() || !value.subagents.deny.is_empty() {
-            let final_allow: Vec<String> = value
-                .subagents
-                .allow
-                .difference(&value.subagents.deny)
-                .cloned()
-                .collect();
-
-            if !final_allow.is_empty() {
-                let mut subagent_map = HashMap::new();
-                subagent_map.insert("allowedAgents".to_string(), final_allow);
-                let subagent_value = facet_value::to_value(&subagent_map)
-                    .wrap_err("Failed to serialize subagent settings")?;
-                tools_settings.insert("subagent".to_string(), subagent_value);
-            }
+        let tool = SubagentTool {
+            available_agents: value.subagents.available().into_iter().collect(),
+            trusted_agents: value.subagents.trusted().into_iter().collect(),
+            ..Default::default()
+        };
+        if tool != SubagentTool::default() {
+            let v: facet_value::Value = facet_json::from_str(&facet_json::to_string(&tool)?)?;
+            tools_settings.insert("subagent".to_string(), v);
         }
 
-        let mcp_servers: HashMap<String, CustomToolConfig> = value
+        let mcp_servers: BTreeMap<String, CustomToolConfig> = value
             .mcp_servers
             .clone()
             .into_iter()
@@ -169,14 +172,22 @@ impl TryFrom<&Manifest> for KiroAgent {
         Ok(Self {
             name: value.name.clone(),
             description: value.description.clone(),
-            prompt: value.prompt.clone(),
+            prompt: value
+                .prompt
+                .as_ref()
+                .and_then(|p| p.text())
+                .map(String::from),
             mcp_servers,
             tools: if tools.is_empty() {
                 default_agent.tools
             } else {
                 tools
             },
-            tool_aliases: value.tool_aliases.clone(),
+            tool_aliases: value
+                .tool_aliases
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
             allowed_tools: if allowed_tools.is_empty() {
                 default_agent.allowed_tools
             } else {
@@ -191,17 +202,38 @@ impl TryFrom<&Manifest> for KiroAgent {
             tools_settings,
             model: value.model.clone(),
             include_mcp_json: value.include_mcp_json.is_some_and(|f| f),
+            keyboard_shortcut: value.keyboard_shortcut.clone(),
+            welcome_message: value.welcome_message.clone(),
         })
     }
 }
 
+/// Sort object keys recursively, and arrays too with `sort_arrays`, so
+/// values which only differ in order serialize the same
+fn sort_value(value: &mut facet_value::Value, sort_arrays: bool) {
+    if let Some(array) = value.as_array_mut() {
+        array.iter_mut().for_each(|v| sort_value(v, sort_arrays));
+        if sort_arrays {
+            array.sort_by_cached_key(|v| facet_json::to_string(v).unwrap_or_default());
+        }
+    } else if let Some(object) = value.as_object_mut() {
+        let mut entries: Vec<(facet_value::VString, facet_value::Value)> =
+            std::mem::take(object).into_iter().collect();
+        entries
+            .iter_mut()
+            .for_each(|(_, v)| sort_value(v, sort_arrays));
+        entries.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+        *object = entries.into_iter().collect();
+    }
+}
+
 impl Default for KiroAgent {
     fn default() -> Self {
         Self {
             name: "kiro_default".to_string(),
             description: Some("Default agent".to_string()),
             tools: {
-                let mut set = HashSet::new();
+                let mut set = BTreeSet::new();
                 set.insert("*".to_string());
                 set
             },
@@ -209,7 +241,7 @@ impl Default for KiroAgent {
             mcp_servers: Default::default(),
             tool_aliases: Default::default(),
             allowed_tools: {
-                let mut set = HashSet::<String>::new();
+                let mut set = BTreeSet::<String>::new();
                 let default_approve = DEFAULT_APPROVE.iter().copied().map(str::to_string);
                 set.extend(default_approve);
                 set
@@ -219,6 +251,8 @@ impl Default for KiroAgent {
             tools_settings: Default::default(),
             include_mcp_json: true,
             model: None,
+            keyboard_shortcut: None,
+            welcome_message: None,
         }
     }
 }
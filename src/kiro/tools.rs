@@ -1,6 +1,6 @@
 use {
     facet::Facet,
-    std::{collections::HashSet, fmt::Display},
+    std::{collections::BTreeSet, fmt::Display},
 };
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, enum_iterator::Sequence)]
@@ -46,10 +46,10 @@ impl AsRef<str> for ToolTarget {
 #[derive(Debug, Default, Clone, Facet, PartialEq, Eq)]
 #[facet(rename_all = "camelCase", deny_unknown_fields)]
 pub struct AwsTool {
-    #[facet(default, skip_serializing_if = HashSet::is_empty)]
-    pub allowed_services: HashSet<String>,
-    #[facet(default, skip_serializing_if = HashSet::is_empty)]
-    pub denied_services: HashSet<String>,
+    #[facet(default, skip_serializing_if = BTreeSet::is_empty)]
+    pub allowed_services: BTreeSet<String>,
+    #[facet(default, skip_serializing_if = BTreeSet::is_empty)]
+    pub denied_services: BTreeSet<String>,
     #[facet(default)]
     pub auto_allow_readonly: Option<bool>,
 }
@@ -58,10 +58,10 @@ pub struct AwsTool {
 #[derive(Debug, Default, Facet, PartialEq, Eq, Clone)]
 #[facet(rename_all = "camelCase", deny_unknown_fields)]
 pub struct ExecuteShellTool {
-    #[facet(default, skip_serializing_if = HashSet::is_empty)]
-    pub allowed_commands: HashSet<String>,
-    #[facet(default, skip_serializing_if = HashSet::is_empty)]
-    pub denied_commands: HashSet<String>,
+    #[facet(default, skip_serializing_if = BTreeSet::is_empty)]
+    pub allowed_commands: BTreeSet<String>,
+    #[facet(default, skip_serializing_if = BTreeSet::is_empty)]
+    pub denied_commands: BTreeSet<String>,
     #[facet(default)]
     pub deny_by_default: bool,
     #[facet(default, skip_serializing_if = Option::is_none)]
@@ -72,30 +72,30 @@ pub struct ExecuteShellTool {
 #[derive(Debug, Facet, Default, PartialEq, Eq, Clone)]
 #[facet(rename_all = "camelCase", deny_unknown_fields)]
 pub struct ReadTool {
-    #[facet(default, skip_serializing_if = HashSet::is_empty)]
-    pub allowed_paths: HashSet<String>,
-    #[facet(default, skip_serializing_if = HashSet::is_empty)]
-    pub denied_paths: HashSet<String>,
+    #[facet(default, skip_serializing_if = BTreeSet::is_empty)]
+    pub allowed_paths: BTreeSet<String>,
+    #[facet(default, skip_serializing_if = BTreeSet::is_empty)]
+    pub denied_paths: BTreeSet<String>,
 }
 
 #[allow(dead_code)]
 #[derive(Debug, Facet, Default, PartialEq, Eq, Clone)]
 #[facet(rename_all = "camelCase", deny_unknown_fields)]
 pub struct WriteTool {
-    #[facet(default, skip_serializing_if = HashSet::is_empty)]
-    pub allowed_paths: HashSet<String>,
-    #[facet(default, skip_serializing_if = HashSet::is_empty)]
-    pub denied_paths: HashSet<String>,
+    #[facet(default, skip_serializing_if = BTreeSet::is_empty)]
+    pub allowed_paths: BTreeSet<String>,
+    #[facet(default, skip_serializing_if = BTreeSet::is_empty)]
+    pub denied_paths: BTreeSet<String>,
 }
 
 #[allow(dead_code)]
 #[derive(Debug, Facet, Default, PartialEq, Eq, Clone)]
 #[facet(rename_all = "camelCase", deny_unknown_fields)]
 pub struct GlobTool {
-    #[facet(default, skip_serializing_if = HashSet::is_empty)]
-    pub allowed_paths: HashSet<String>,
-    #[facet(default, skip_serializing_if = HashSet::is_empty)]
-    pub denied_paths: HashSet<String>,
+    #[facet(default, skip_serializing_if = BTreeSet::is_empty)]
+    pub allowed_paths: BTreeSet<String>,
+    #[facet(default, skip_serializing_if = BTreeSet::is_empty)]
+    pub denied_paths: BTreeSet<String>,
     #[facet(default)]
     pub allow_read_only: bool,
 }
@@ -104,10 +104,10 @@ pub struct GlobTool {
 #[derive(Debug, Facet, Default, PartialEq, Eq, Clone)]
 #[facet(rename_all = "camelCase", deny_unknown_fields)]
 pub struct GrepTool {
-    #[facet(default, skip_serializing_if = HashSet::is_empty)]
-    pub allowed_paths: HashSet<String>,
-    #[facet(default, skip_serializing_if = HashSet::is_empty)]
-    pub denied_paths: HashSet<String>,
+    #[facet(default, skip_serializing_if = BTreeSet::is_empty)]
+    pub allowed_paths: BTreeSet<String>,
+    #[facet(default, skip_serializing_if = BTreeSet::is_empty)]
+    pub denied_paths: BTreeSet<String>,
     #[facet(default)]
     pub allow_read_only: bool,
 }
@@ -116,20 +116,26 @@ pub struct GrepTool {
 #[derive(Debug, Facet, Default, PartialEq, Eq, Clone)]
 #[facet(rename_all = "camelCase", deny_unknown_fields)]
 pub struct WebFetchTool {
-    #[facet(default, skip_serializing_if = HashSet::is_empty)]
-    pub trusted: HashSet<String>,
-    #[facet(default, skip_serializing_if = HashSet::is_empty)]
-    pub blocked: HashSet<String>,
+    #[facet(default, skip_serializing_if = BTreeSet::is_empty)]
+    pub trusted: BTreeSet<String>,
+    #[facet(default, skip_serializing_if = BTreeSet::is_empty)]
+    pub blocked: BTreeSet<String>,
 }
 
 #[derive(Debug, Clone, Facet, Default, PartialEq, Eq)]
 #[facet(rename_all = "camelCase", deny_unknown_fields)]
 pub struct SubagentTool {
-    #[facet(default, skip_serializing_if = HashSet::is_empty)]
-    pub allowed_agents: HashSet<String>,
+    #[facet(default, skip_serializing_if = BTreeSet::is_empty)]
+    pub available_agents: BTreeSet<String>,
+    #[facet(default, skip_serializing_if = BTreeSet::is_empty)]
+    pub trusted_agents: BTreeSet<String>,
+    /// Written by kg before `availableAgents` was supported, read for import
+    /// and diff
+    #[facet(default, skip_serializing_if = BTreeSet::is_empty)]
+    pub allowed_agents: BTreeSet<String>,
 }
 
-// Normalized variants for stable diffing (Vec instead of HashSet)
+// Normalized variants for stable diffing (Vec instead of BTreeSet)
 
 #[derive(Debug, Clone, Facet, PartialEq, Eq)]
 #[facet(rename_all = "camelCase", deny_unknown_fields)]
@@ -230,20 +236,93 @@ impl From<WriteTool> for NormalizedWriteTool {
 #[derive(Debug, Clone, Facet, Default, PartialEq, Eq)]
 #[facet(rename_all = "camelCase", deny_unknown_fields)]
 pub struct NormalizedSubagentTool {
+    #[facet(default, skip_serializing_if = Vec::is_empty)]
+    pub available_agents: Vec<String>,
+    #[facet(default, skip_serializing_if = Vec::is_empty)]
+    pub trusted_agents: Vec<String>,
     #[facet(default, skip_serializing_if = Vec::is_empty)]
     pub allowed_agents: Vec<String>,
 }
 
 impl From<SubagentTool> for NormalizedSubagentTool {
     fn from(tool: SubagentTool) -> Self {
-        let mut allowed: Vec<_> = tool.allowed_agents.into_iter().collect();
+        Self {
+            available_agents: tool.available_agents.into_iter().collect(),
+            trusted_agents: tool.trusted_agents.into_iter().collect(),
+            allowed_agents: tool.allowed_agents.into_iter().collect(),
+        }
+    }
+}
+
+#[derive(Debug, Facet, Default, PartialEq, Eq, Clone)]
+#[facet(rename_all = "camelCase", deny_unknown_fields)]
+pub struct NormalizedGlobTool {
+    #[facet(default, skip_serializing_if = Vec::is_empty)]
+    pub allowed_paths: Vec<String>,
+    #[facet(default, skip_serializing_if = Vec::is_empty)]
+    pub denied_paths: Vec<String>,
+    #[facet(default)]
+    pub allow_read_only: bool,
+}
+
+impl From<GlobTool> for NormalizedGlobTool {
+    fn from(tool: GlobTool) -> Self {
+        let mut allowed: Vec<_> = tool.allowed_paths.into_iter().collect();
+        let mut denied: Vec<_> = tool.denied_paths.into_iter().collect();
+        allowed.sort();
+        denied.sort();
+        Self {
+            allowed_paths: allowed,
+            denied_paths: denied,
+            allow_read_only: tool.allow_read_only,
+        }
+    }
+}
+
+#[derive(Debug, Facet, Default, PartialEq, Eq, Clone)]
+#[facet(rename_all = "camelCase", deny_unknown_fields)]
+pub struct NormalizedGrepTool {
+    #[facet(default, skip_serializing_if = Vec::is_empty)]
+    pub allowed_paths: Vec<String>,
+    #[facet(default, skip_serializing_if = Vec::is_empty)]
+    pub denied_paths: Vec<String>,
+    #[facet(default)]
+    pub allow_read_only: bool,
+}
+
+impl From<GrepTool> for NormalizedGrepTool {
+    fn from(tool: GrepTool) -> Self {
+        let mut allowed: Vec<_> = tool.allowed_paths.into_iter().collect();
+        let mut denied: Vec<_> = tool.denied_paths.into_iter().collect();
         allowed.sort();
+        denied.sort();
         Self {
-            allowed_agents: allowed,
+            allowed_paths: allowed,
+            denied_paths: denied,
+            allow_read_only: tool.allow_read_only,
         }
     }
 }
 
+#[derive(Debug, Facet, Default, PartialEq, Eq, Clone)]
+#[facet(rename_all = "camelCase", deny_unknown_fields)]
+pub struct NormalizedWebFetchTool {
+    #[facet(default, skip_serializing_if = Vec::is_empty)]
+    pub trusted: Vec<String>,
+    #[facet(default, skip_serializing_if = Vec::is_empty)]
+    pub blocked: Vec<String>,
+}
+
+impl From<WebFetchTool> for NormalizedWebFetchTool {
+    fn from(tool: WebFetchTool) -> Self {
+        let mut trusted: Vec<_> = tool.trusted.into_iter().collect();
+        let mut blocked: Vec<_> = tool.blocked.into_iter().collect();
+        trusted.sort();
+        blocked.sort();
+        Self { trusted, blocked }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,273 @@
+mod rules;
+use {
+    crate::{Manifest, Result, generator::Generator},
+    facet::Facet,
+    std::fmt::{self, Display},
+};
+
+/// How serious a lint finding is. `kg lint` fails when any error is found.
+#[derive(Facet, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[facet(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Info => write!(f, "info"),
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A lint rule, suppressed per agent with `lint.allow = ["<id>"]`
+#[derive(Facet, Debug, Clone, Copy)]
+pub struct Rule {
+    pub id: &'static str,
+    pub severity: Severity,
+    pub description: &'static str,
+}
+
+/// Messages of one rule for a merged agent, empty when it passes
+type Check = fn(&Generator, &Manifest) -> Vec<String>;
+
+/// Every rule `kg lint` runs
+const RULES: &[(Rule, Check)] = &[
+    (
+        Rule {
+            id: "force-allow-overrides-deny",
+            severity: Severity::Warning,
+            description: "forceAllow entry overrides a deny declared by an ancestor",
+        },
+        rules::force_allow_overrides_deny,
+    ),
+    (
+        Rule {
+            id: "broad-shell-allow",
+            severity: Severity::Error,
+            description: "shell allow pattern matches any command",
+        },
+        rules::broad_shell_allow,
+    ),
+    (
+        Rule {
+            id: "wildcard-tools-auto-allow-readonly",
+            severity: Severity::Warning,
+            description: "tools = [\"*\"] combined with autoAllowReadonly",
+        },
+        rules::wildcard_tools_auto_allow_readonly,
+    ),
+    (
+        Rule {
+            id: "write-outside-project",
+            severity: Severity::Warning,
+            description: "write allow path outside the project directory",
+        },
+        rules::write_outside_project,
+    ),
+    (
+        Rule {
+            id: "unused-mcp-server",
+            severity: Severity::Warning,
+            description: "MCP server not referenced in tools or allowedTools",
+        },
+        rules::unused_mcp_server,
+    ),
+    (
+        Rule {
+            id: "allowed-tool-not-in-tools",
+            severity: Severity::Warning,
+            description: "allowedTools entry the agent cannot see because it is not in tools",
+        },
+        rules::allowed_tool_not_in_tools,
+    ),
+    (
+        Rule {
+            id: "unused-template",
+            severity: Severity::Warning,
+            description: "template no agent inherits from",
+        },
+        rules::unused_template,
+    ),
+    (
+        Rule {
+            id: "hook-without-timeout",
+            severity: Severity::Info,
+            description: "hook without timeout_ms runs up to the 30s default",
+        },
+        rules::hook_without_timeout,
+    ),
+];
+
+/// Every rule `kg lint` runs, for `kg lint --rules`
+pub fn rules() -> Vec<Rule> {
+    RULES.iter().map(|(rule, _)| *rule).collect()
+}
+
+#[derive(Facet, Debug, Clone)]
+pub struct Finding {
+    pub rule: String,
+    pub severity: Severity,
+    pub agent: String,
+    pub message: String,
+}
+
+#[derive(Facet, Debug, Clone, Default)]
+pub struct LintReport {
+    /// Sorted by severity, most severe first, then agent and rule
+    pub findings: Vec<Finding>,
+    /// Findings hidden by `lint.allow`
+    pub suppressed: usize,
+}
+
+impl LintReport {
+    pub fn has_errors(&self) -> bool {
+        self.findings.iter().any(|f| f.severity == Severity::Error)
+    }
+}
+
+/// Run every rule over the merged agents in scope, local agents when any are
+/// defined and all agents otherwise
+#[tracing::instrument(level = "info", skip(generator))]
+pub fn lint(generator: &Generator) -> Result<LintReport> {
+    let merged = generator.merge()?;
+    let all_agents = !generator.contains_local_agents();
+
+    let mut report = LintReport::default();
+    for agent in merged
+        .iter()
+        .filter(|a| all_agents || generator.is_local(&a.name))
+    {
+        for unknown in agent
+            .lint
+            .allow
+            .iter()
+            .filter(|id| !RULES.iter().any(|(r, _)| r.id == id.as_str()))
+        {
+            tracing::warn!(agent = %agent.name, "unknown lint rule '{unknown}' in lint.allow");
+        }
+        for (rule, check) in RULES {
+            let messages = check(generator, agent);
+            if agent.lint.allow.contains(rule.id) {
+                report.suppressed += messages.len();
+                continue;
+            }
+            report
+                .findings
+                .extend(messages.into_iter().map(|message| Finding {
+                    rule: rule.id.to_string(),
+                    severity: rule.severity,
+                    agent: agent.name.clone(),
+                    message,
+                }));
+        }
+    }
+    report.findings.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then_with(|| a.agent.cmp(&b.agent))
+            .then_with(|| a.rule.cmp(&b.rule))
+            .then_with(|| a.message.cmp(&b.message))
+    });
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{ConfigLocation, os::Fs, output::OutputFormat},
+    };
+
+    async fn lint_manifest(toml: &str) -> Result<LintReport> {
+        let fs = Fs::new();
+        fs.write(".kiro/generators/manifests/lint.toml", toml)
+            .await?;
+        let generator = Generator::new(fs, ConfigLocation::Local, OutputFormat::Json)?;
+        lint(&generator)
+    }
+
+    fn rules_for<'a>(report: &'a LintReport, agent: &str) -> Vec<&'a str> {
+        report
+            .findings
+            .iter()
+            .filter(|f| f.agent == agent)
+            .map(|f| f.rule.as_str())
+            .collect()
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_lint_reports_and_suppresses() -> Result<()> {
+        let report = lint_manifest(
+            r#"
+            [agents.lint-base]
+            template = true
+            nativeTools.shell.deny = ["git push .*"]
+
+            [agents.lint-unused]
+            template = true
+
+            [agents.lint-a]
+            inherits = ["lint-base"]
+            tools = ["*"]
+            allowedTools = ["@missing/tool"]
+            nativeTools.shell.allow = [".*"]
+            nativeTools.shell.forceAllow = ["git push .*"]
+            nativeTools.shell.autoAllowReadonly = true
+            nativeTools.write.allow = ["/etc/**", "./src/**"]
+
+            [agents.lint-a.hooks.agentSpawn.hello]
+            command = "echo hello"
+
+            [agents.lint-b]
+            tools = ["read"]
+            allowedTools = ["write"]
+            mcpServers.unused.command = "unused-mcp"
+            nativeTools.shell.allow = ["bash -c .*"]
+            lint.allow = ["broad-shell-allow"]
+
+            [agents.lint-b.hooks.agentSpawn.hello]
+            command = "echo hello"
+            timeout_ms = 1000
+            "#,
+        )
+        .await?;
+
+        let a = rules_for(&report, "lint-a");
+        assert!(a.contains(&"force-allow-overrides-deny"), "{a:?}");
+        assert!(a.contains(&"broad-shell-allow"), "{a:?}");
+        assert!(a.contains(&"wildcard-tools-auto-allow-readonly"), "{a:?}");
+        assert!(a.contains(&"write-outside-project"), "{a:?}");
+        assert!(a.contains(&"hook-without-timeout"), "{a:?}");
+        // tools = ["*"] makes every tool visible
+        assert!(!a.contains(&"allowed-tool-not-in-tools"), "{a:?}");
+        assert_eq!(
+            report
+                .findings
+                .iter()
+                .filter(|f| f.agent == "lint-a" && f.rule == "write-outside-project")
+                .count(),
+            1
+        );
+
+        assert_eq!(rules_for(&report, "lint-b"), vec![
+            "allowed-tool-not-in-tools",
+            "unused-mcp-server"
+        ]);
+        assert_eq!(report.suppressed, 1);
+        assert_eq!(rules_for(&report, "lint-unused"), vec!["unused-template"]);
+        assert!(rules_for(&report, "lint-base").is_empty());
+        assert!(report.has_errors());
+        assert_eq!(
+            report.findings.first().map(|f| f.severity),
+            Some(Severity::Error)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+use {
+    crate::{Manifest, generator::Generator, glob_match},
+    std::{collections::HashSet, path::Path},
+};
+
+/// Patterns matching any input once `^` and `$` anchors are removed
+const MATCH_ANY: &[&str] = &[".*", ".+", "*", "\\S*", "\\S+"];
+/// Commands running arbitrary code passed as an argument
+const INTERPRETERS: &[&str] = &[
+    "sh", "bash", "zsh", "fish", "env", "eval", "exec", "xargs", "sudo", "python", "python3",
+    "node", "perl", "ruby",
+];
+
+/// Tools the agent can see, the generated agent gets `*` when none are set
+fn visible_tools(agent: &Manifest) -> HashSet<&str> {
+    match agent.tools.is_empty() {
+        true => HashSet::from(["*"]),
+        false => agent.tools.iter().map(String::as_str).collect(),
+    }
+}
+
+fn matches_anything(pattern: &str) -> bool {
+    let mut rest = pattern.trim();
+    rest = rest.strip_prefix('^').unwrap_or(rest);
+    rest = rest.strip_suffix('$').unwrap_or(rest);
+    while !rest.is_empty() {
+        match MATCH_ANY.iter().find(|any| rest.starts_with(**any)) {
+            Some(any) => rest = &rest[any.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// `.*`, or an interpreter followed by flags and `.*`, like `bash -c .*`
+fn is_broad_shell_pattern(pattern: &str) -> bool {
+    if matches_anything(pattern) {
+        return true;
+    }
+    let pattern = pattern.trim().trim_start_matches('^');
+    let mut words = pattern.split_whitespace();
+    let Some(command) = words.next() else {
+        return true;
+    };
+    let command = command.rsplit('/').next().unwrap_or(command);
+    if !INTERPRETERS.contains(&command) {
+        return false;
+    }
+    let args: Vec<&str> = words.filter(|w| !w.starts_with('-')).collect();
+    args.is_empty() || args.iter().all(|a| matches_anything(a))
+}
+
+fn is_outside_project(path: &str, project_root: Option<&Path>) -> bool {
+    if path.starts_with('~') || path.starts_with("$HOME") {
+        return true;
+    }
+    let p = Path::new(path);
+    if p.components()
+        .any(|c| matches!(c, std::path::Component::ParentDir))
+    {
+        return true;
+    }
+    p.is_absolute() && !project_root.is_some_and(|root| p.starts_with(root))
+}
+
+pub(super) fn force_allow_overrides_deny(generator: &Generator, agent: &Manifest) -> Vec<String> {
+    let chain = generator.inheritance_chain_safe(&agent.name);
+    let mut found = Vec::new();
+    for ((tool, list), entries) in agent.native_tools.lists() {
+        if list != "forceAllow" {
+            continue;
+        }
+        for entry in entries {
+            // Nearest ancestor first
+            let ancestor = chain.iter().rev().find(|parent| {
+                generator.agents.get(*parent).is_some_and(|slots| {
+                    slots
+                        .merged
+                        .native_tools
+                        .lists()
+                        .iter()
+                        .any(|((t, l), denies)| {
+                            *t == tool && *l == "deny" && denies.contains(entry)
+                        })
+                })
+            });
+            if let Some(ancestor) = ancestor {
+                found.push(format!(
+                    "nativeTools.{tool}.forceAllow '{entry}' overrides the deny inherited from \
+                     '{ancestor}'"
+                ));
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+pub(super) fn broad_shell_allow(_: &Generator, agent: &Manifest) -> Vec<String> {
+    let shell = agent.get_tool_shell();
+    let mut found: Vec<String> = [("allow", &shell.allows), ("forceAllow", &shell.force_allow)]
+        .into_iter()
+        .flat_map(|(list, entries)| {
+            entries
+                .iter()
+                .filter(|e| is_broad_shell_pattern(e))
+                .map(move |e| format!("nativeTools.shell.{list} '{e}' allows any command"))
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+pub(super) fn wildcard_tools_auto_allow_readonly(_: &Generator, agent: &Manifest) -> Vec<String> {
+    if !visible_tools(agent).contains("*") {
+        return Vec::new();
+    }
+    [
+        ("shell", agent.get_tool_shell().auto_allow_readonly),
+        ("aws", agent.get_tool_aws().auto_allow_readonly),
+    ]
+    .into_iter()
+    .filter(|(_, auto)| *auto == Some(true))
+    .map(|(tool, _)| {
+        format!(
+            "tools = [\"*\"] with nativeTools.{tool}.autoAllowReadonly runs read-only commands of \
+             every tool without asking"
+        )
+    })
+    .collect()
+}
+
+pub(super) fn write_outside_project(_: &Generator, agent: &Manifest) -> Vec<String> {
+    let project_root = std::env::current_dir().ok();
+    let write = agent.get_tool_write();
+    let mut found: Vec<String> = [("allow", &write.allows), ("forceAllow", &write.force_allow)]
+        .into_iter()
+        .flat_map(|(list, entries)| {
+            entries
+                .iter()
+                .filter(|e| is_outside_project(e, project_root.as_deref()))
+                .map(move |e| format!("nativeTools.write.{list} '{e}' is outside the project"))
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+pub(super) fn unused_mcp_server(_: &Generator, agent: &Manifest) -> Vec<String> {
+    let tools = visible_tools(agent);
+    if tools.contains("*") {
+        return Vec::new();
+    }
+    let mut found: Vec<String> = agent
+        .mcp_servers
+        .iter()
+        .filter(|(_, mcp)| !mcp.state.as_ref().is_some_and(|s| s.is_disabled()))
+        .filter(|(name, _)| {
+            let server = format!("@{name}");
+            let prefix = format!("{server}/");
+            !tools
+                .iter()
+                .copied()
+                .chain(agent.allowed_tools.iter().map(String::as_str))
+                .any(|t| t == server || t.starts_with(&prefix))
+        })
+        .map(|(name, _)| format!("mcpServers.{name} is not referenced as '@{name}' in tools"))
+        .collect();
+    found.sort();
+    found
+}
+
+pub(super) fn allowed_tool_not_in_tools(_: &Generator, agent: &Manifest) -> Vec<String> {
+    let tools = visible_tools(agent);
+    if tools.contains("*") {
+        return Vec::new();
+    }
+    let visible = |allowed: &str| {
+        let server = allowed.split_once('/').map(|(server, _)| server);
+        tools.iter().any(|tool| {
+            glob_match(tool, allowed)
+                || glob_match(allowed, tool)
+                || server == Some(*tool)
+                || (*tool == "@builtin" && !allowed.starts_with('@'))
+        })
+    };
+    let mut found: Vec<String> = agent
+        .allowed_tools
+        .iter()
+        .filter(|a| !visible(a))
+        .map(|a| format!("allowedTools '{a}' is not in tools"))
+        .collect();
+    found.sort();
+    found
+}
+
+pub(super) fn unused_template(generator: &Generator, agent: &Manifest) -> Vec<String> {
+    let inherited = generator
+        .agents
+        .values()
+        .any(|slots| slots.merged.inherits.contains(&agent.name));
+    match agent.template && !inherited {
+        true => vec![format!(
+            "template '{}' is not inherited by any agent",
+            agent.name
+        )],
+        false => Vec::new(),
+    }
+}
+
+pub(super) fn hook_without_timeout(_: &Generator, agent: &Manifest) -> Vec<String> {
+    let mut found: Vec<String> = agent
+        .hooks
+        .iter()
+        .flat_map(|(trigger, hooks)| {
+            hooks
+                .iter()
+                .filter(|(_, hook)| hook.timeout_ms.is_none())
+                .map(move |(name, _)| format!("hooks.{trigger}.{name} has no timeout_ms"))
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_broad_shell_patterns() {
+        for broad in [
+            ".*",
+            "^.*$",
+            ".+",
+            "*",
+            ".*.*",
+            "bash -c .*",
+            "/bin/sh -c .+",
+            "sudo .*",
+        ] {
+            assert!(is_broad_shell_pattern(broad), "{broad}");
+        }
+        for narrow in [
+            "git status",
+            "git .*",
+            "cargo (build|test) .*",
+            "bash -c 'make'",
+        ] {
+            assert!(!is_broad_shell_pattern(narrow), "{narrow}");
+        }
+    }
+
+    #[test]
+    fn test_outside_project() {
+        let root = Path::new("/work/project");
+        assert!(is_outside_project("/etc/**", Some(root)));
+        assert!(is_outside_project("~/.ssh/**", Some(root)));
+        assert!(is_outside_project("../other/**", Some(root)));
+        assert!(!is_outside_project("./src/**", Some(root)));
+        assert!(!is_outside_project("/work/project/src/**", Some(root)));
+        assert!(!is_outside_project("**/*.rs", Some(root)));
+    }
+}
@@ -2,8 +2,10 @@ mod commands;
 mod generator;
 mod kg_config;
 mod kiro;
+mod lint;
 mod os;
 pub mod output;
+mod report;
 mod schema;
 mod source;
 mod tracing_init;
@@ -17,7 +19,7 @@ use {
     crate::{generator::Generator, os::Fs, tracing_init::init_tracing},
     clap::Parser,
     color_eyre::eyre::{Context, bail},
-    std::path::Path,
+    std::{path::Path, process::ExitCode},
     tracing::enabled,
 };
 pub use {
@@ -178,13 +180,13 @@ fn reset_sigpipe() {
 fn reset_sigpipe() {}
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
     reset_sigpipe();
     color_eyre::install()?;
     let cli = commands::Cli::parse();
     if matches!(cli.command, commands::Command::Version) {
         println!("{}", clap::crate_version!());
-        return Ok(());
+        return Ok(ExitCode::SUCCESS);
     }
 
     // Extract trace option from commands that support it
@@ -210,7 +212,8 @@ async fn main() -> Result<()> {
         } else {
             dirs::home_dir().ok_or(crate::format_err!("unable to find HOME dir"))?
         };
-        return init(&fs, &init_dir, args.skeleton, args.force).await;
+        init(&fs, &init_dir, args.skeleton, args.force).await?;
+        return Ok(ExitCode::SUCCESS);
     }
 
     let home_dir = dirs::home_dir().ok_or(crate::format_err!("unable to find HOME dir"))?;
@@ -219,15 +222,18 @@ async fn main() -> Result<()> {
         if let commands::SchemaCommand::Agent(args) = &schema_cmd
             && args.mappings
         {
-            return schema::handle_schema_mappings();
+            schema::handle_schema_mappings()?;
+        } else {
+            schema::handle_schema_command(schema_cmd)?;
         }
-        return schema::handle_schema_command(schema_cmd);
+        return Ok(ExitCode::SUCCESS);
     }
 
     cli.record_span(&span);
     let location = cli.config_location(home_dir)?;
     let format = cli.format_color();
-    let kg_generator_config: Generator = Generator::new(fs, location, format)?;
+    let kg_generator_config: Generator =
+        Generator::new(fs, location, format)?.with_history_limit(cli.history_limit());
     if enabled!(tracing::Level::TRACE) {
         tracing::trace!(
             "Loaded Agent Generator Config:\n{}",
@@ -236,9 +242,7 @@ async fn main() -> Result<()> {
         );
     }
 
-    cli.execute(&kg_generator_config).await?;
-
-    Ok(())
+    cli.execute(&kg_generator_config).await
 }
 
 #[cfg(test)]
@@ -179,6 +179,14 @@ impl Fs {
                         tracing::debug!("failed to copy agents: {}", e);
                     }
 
+                    // Project files the fixture agents use as resources
+                    let project_src = std::path::Path::new("./fixtures/kiro/project");
+                    if let Err(e) =
+                        copy_agents_recursive(project_src, std::path::Path::new(""), &temp_path)
+                    {
+                        tracing::debug!("failed to copy project files: {}", e);
+                    }
+
                     // Debug: recursively list what was copied
                     fn debug_list_dir(path: &std::path::Path, indent: usize) {
                         if let Ok(entries) = std::fs::read_dir(path) {
@@ -393,7 +401,15 @@ impl Fs {
         match self {
             Self::Real => fs::remove_file(path).await,
             Self::Chroot(root) => fs::remove_file(append(root.path(), path)).await,
-            Self::Fake(_) => panic!("unimplemented"),
+            Self::Fake(map) => {
+                let Ok(mut lock) = map.lock() else {
+                    return Err(io::Error::other("poisoned lock").into());
+                };
+                match lock.remove(path) {
+                    Some(_) => Ok(()),
+                    None => Err(io::Error::from(io::ErrorKind::NotFound)),
+                }
+            }
         }
         .wrap_err_with(|| format!("Failed to remove file: {}", path.display()))
     }
@@ -426,7 +442,18 @@ impl Fs {
             Self::Chroot(root) => {
                 fs::rename(append(root.path(), from), append(root.path(), to)).await
             }
-            Self::Fake(_) => panic!("unimplemented"),
+            Self::Fake(map) => {
+                let Ok(mut lock) = map.lock() else {
+                    return Err(io::Error::other("poisoned lock").into());
+                };
+                match lock.remove(from) {
+                    Some(contents) => {
+                        lock.insert(to.to_owned(), contents);
+                        Ok(())
+                    }
+                    None => Err(io::Error::from(io::ErrorKind::NotFound)),
+                }
+            }
         }
         .wrap_err_with(|| format!("Failed to rename {} to {}", from.display(), to.display()))
     }
@@ -475,7 +502,9 @@ impl Fs {
         match self {
             Self::Real => path.as_ref().exists(),
             Self::Chroot(root) => append(root.path(), path).exists(),
-            Self::Fake(_) => panic!("unimplemented"),
+            Self::Fake(map) => map
+                .lock()
+                .is_ok_and(|lock| lock.contains_key(path.as_ref())),
         }
     }
 
@@ -521,6 +550,21 @@ mod tests {
         fs.write(dir.join("write"), b"write").await.unwrap();
         assert_eq!(fs.read(dir.join("write")).await.unwrap(), b"write");
         assert_eq!(fs.read_to_string(dir.join("write")).await.unwrap(), "write");
+
+        fs.rename(dir.join("write"), dir.join("renamed"))
+            .await
+            .unwrap();
+        assert!(!fs.exists(dir.join("write")));
+        assert_eq!(
+            fs.read_to_string(dir.join("renamed")).await.unwrap(),
+            "write"
+        );
+        let _ = fs
+            .rename(dir.join("write"), dir.join("renamed"))
+            .await
+            .unwrap_err();
+        fs.remove_file(dir.join("renamed")).await.unwrap();
+        assert!(!fs.exists(dir.join("renamed")));
     }
 
     #[tokio::test]
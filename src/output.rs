@@ -1,8 +1,19 @@
 use {
     crate::{
         Result,
-        generator::AgentResult,
+        generator::{
+            AgentResult,
+            CommandCheck,
+            Decision,
+            PathCheck,
+            Snapshot,
+            SnapshotHistory,
+            StatusReport,
+            SyncState,
+        },
         kiro::{KiroAgent, ToolTarget},
+        lint::{LintReport, Rule, Severity},
+        report::PermissionsReport,
         source::{AgentSourceSlots, KgAgentSource},
     },
     color_eyre::eyre::Context,
@@ -47,6 +58,8 @@ pub enum DiffFormatArg {
     Plain,
     /// Dot-notation paths, only changes, no color (best for agents/piping)
     Agent,
+    /// Change list per agent with path, kind, old and new value as JSON
+    Json,
 }
 
 #[derive(Copy, Clone, Default, Debug, clap::ValueEnum)]
@@ -57,11 +70,29 @@ pub enum OutputFormatArg {
     Plain,
 }
 
+/// Output of `kg report`, which adds the spreadsheet-friendly formats
+#[derive(Copy, Clone, Default, Debug, clap::ValueEnum)]
+pub enum ReportFormatArg {
+    #[default]
+    Table,
+    Json,
+    /// Tab-separated rows without a header
+    Plain,
+    /// Comma-separated values with a header row
+    Csv,
+    /// Markdown table
+    Markdown,
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum OutputFormat {
     Table(bool),
     Json,
     Plain,
+    /// Only selectable through [`ReportFormatArg`]
+    Csv,
+    /// Only selectable through [`ReportFormatArg`]
+    Markdown,
 }
 
 impl Default for OutputFormat {
@@ -77,6 +108,7 @@ impl Display for DiffFormatArg {
             Self::Compact => write!(f, "compact"),
             Self::Plain => write!(f, "plain"),
             Self::Agent => write!(f, "agent"),
+            Self::Json => write!(f, "json"),
         }
     }
 }
@@ -91,6 +123,18 @@ impl Display for OutputFormatArg {
     }
 }
 
+impl Display for ReportFormatArg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Table => write!(f, "table"),
+            Self::Json => write!(f, "json"),
+            Self::Plain => write!(f, "plain"),
+            Self::Csv => write!(f, "csv"),
+            Self::Markdown => write!(f, "markdown"),
+        }
+    }
+}
+
 pub(crate) fn agent_header() -> Cell {
     Cell::new(format!("Agent {}", emojis_rs::EMOJI_ROBOT))
 }
@@ -145,7 +189,7 @@ impl OutputFormat {
                 Ok(())
             }
             Self::Json => Ok(()),
-            Self::Plain => Ok(()),
+            Self::Plain | Self::Csv | Self::Markdown => Ok(()),
         }
     }
 
@@ -300,7 +344,7 @@ impl OutputFormat {
                 );
                 Ok(())
             }
-            Self::Plain => {
+            Self::Plain | Self::Csv | Self::Markdown => {
                 for result in &results {
                     if show_templates || !result.agent.template {
                         println!("{}", result.agent.name);
@@ -310,6 +354,408 @@ impl OutputFormat {
             }
         }
     }
+
+    /// Print the snapshots `kg generate` saved, newest first, with what
+    /// restoring each would change
+    pub fn history(&self, history: &[SnapshotHistory]) -> Result<()> {
+        match self {
+            Self::Json => println!(
+                "{}",
+                facet_json::to_string_pretty(history)
+                    .wrap_err("Failed to serialize history to JSON")?
+            ),
+            Self::Table(_) | Self::Plain | Self::Csv | Self::Markdown => {
+                if history.is_empty() {
+                    println!("No snapshots yet, `kg generate` saves the agent files it replaces");
+                    return Ok(());
+                }
+                for entry in history {
+                    let snapshot = &entry.snapshot;
+                    println!("{} ({})", snapshot.timestamp, snapshot.path);
+                    for change in &entry.changes {
+                        match (change.state, &change.diff) {
+                            (SyncState::Changed, Some(diff)) => {
+                                println!("  {}:\n{diff}", change.agent)
+                            }
+                            (SyncState::New, _) => println!("  {}: (deleted since)", change.agent),
+                            _ => println!("  {}: no changes", change.agent),
+                        }
+                    }
+                    println!();
+                }
+                println!("Run `kg rollback --to <TIMESTAMP>` to restore a snapshot");
+            }
+        }
+        Ok(())
+    }
+
+    /// Print the agents `kg rollback` restored
+    pub fn rollback(&self, snapshot: &Snapshot) -> Result<()> {
+        match self {
+            Self::Json => println!(
+                "{}",
+                facet_json::to_string_pretty(snapshot)
+                    .wrap_err("Failed to serialize snapshot to JSON")?
+            ),
+            Self::Table(_) | Self::Plain | Self::Csv | Self::Markdown => {
+                for name in &snapshot.agents {
+                    println!("✓ Restored {name} from {}", snapshot.timestamp);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn status(&self, report: &StatusReport) -> Result<()> {
+        match self {
+            Self::Table(_) => {
+                let mut table = Table::new();
+                table
+                    .load_preset(UTF8_FULL)
+                    .apply_modifier(UTF8_ROUND_CORNERS)
+                    .set_content_arrangement(ContentArrangement::Dynamic)
+                    .set_header(vec![
+                        agent_header(),
+                        Cell::new("Destination"),
+                        Cell::new("Status"),
+                        Cell::new("Edited"),
+                    ]);
+                for agent in &report.agents {
+                    let color = match agent.state {
+                        SyncState::Same => Color::Green,
+                        SyncState::New => Color::Yellow,
+                        SyncState::Changed => Color::Red,
+                    };
+                    table.add_row(vec![
+                        Cell::new(&agent.name),
+                        Cell::new(&agent.destination),
+                        self.maybe_color(Cell::new(agent.state), color),
+                        match agent.edited {
+                            true => self.maybe_color(Cell::new("yes"), Color::Red),
+                            false => Cell::new(""),
+                        },
+                    ]);
+                }
+                for orphan in &report.orphans {
+                    table.add_row(vec![
+                        Cell::new(&orphan.name),
+                        Cell::new(orphan.path.display()),
+                        self.maybe_color(Cell::new("orphan"), Color::Red),
+                        Cell::new(if orphan.modified { "yes" } else { "" }),
+                    ]);
+                }
+                for path in &report.unmanaged {
+                    table.add_row(vec![
+                        Cell::new(""),
+                        Cell::new(path),
+                        Cell::new("unmanaged"),
+                        Cell::new(""),
+                    ]);
+                }
+                println!("{table}");
+                if report.in_sync() {
+                    println!("\n{} Agents are up to date", emojis_rs::EMOJI_CHECK);
+                } else {
+                    println!(
+                        "\n{} Run {} to update agent files",
+                        "→".yellow().bold(),
+                        "kg generate".green().bold()
+                    );
+                }
+                Ok(())
+            }
+            Self::Json => {
+                println!(
+                    "{}",
+                    facet_json::to_string_pretty(report)
+                        .wrap_err("Failed to serialize status to JSON")?
+                );
+                Ok(())
+            }
+            Self::Plain | Self::Csv | Self::Markdown => {
+                for agent in &report.agents {
+                    let edited = if agent.edited { " edited" } else { "" };
+                    println!("{} {}{edited}", agent.state, agent.destination);
+                }
+                for orphan in &report.orphans {
+                    println!("orphan {}", orphan.path.display());
+                }
+                for path in &report.unmanaged {
+                    println!("unmanaged {path}");
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn severity_cell(&self, severity: Severity) -> Cell {
+        let color = match severity {
+            Severity::Error => Color::Red,
+            Severity::Warning => Color::Yellow,
+            Severity::Info => Color::Blue,
+        };
+        self.maybe_color(Cell::new(severity), color)
+    }
+
+    pub fn lint(&self, report: &LintReport) -> Result<()> {
+        match self {
+            Self::Table(_) => {
+                if !report.findings.is_empty() {
+                    let mut table = Table::new();
+                    table
+                        .load_preset(UTF8_FULL)
+                        .apply_modifier(UTF8_ROUND_CORNERS)
+                        .set_content_arrangement(ContentArrangement::Dynamic)
+                        .set_header(vec![
+                            agent_header(),
+                            Cell::new("Severity"),
+                            Cell::new("Rule"),
+                            Cell::new("Message"),
+                        ]);
+                    for finding in &report.findings {
+                        table.add_row(vec![
+                            Cell::new(&finding.agent),
+                            self.severity_cell(finding.severity),
+                            Cell::new(&finding.rule),
+                            Cell::new(&finding.message),
+                        ]);
+                    }
+                    println!("{table}\n");
+                }
+                let suppressed = match report.suppressed {
+                    0 => String::new(),
+                    n => format!(", {n} suppressed by lint.allow"),
+                };
+                match report.findings.len() {
+                    0 => println!("{} No lint findings{suppressed}", emojis_rs::EMOJI_CHECK),
+                    n => println!("{} {n} lint findings{suppressed}", "→".yellow().bold()),
+                }
+                Ok(())
+            }
+            Self::Json => {
+                println!(
+                    "{}",
+                    facet_json::to_string_pretty(report)
+                        .wrap_err("Failed to serialize lint report to JSON")?
+                );
+                Ok(())
+            }
+            Self::Plain | Self::Csv | Self::Markdown => {
+                for f in &report.findings {
+                    println!("{} {} {}: {}", f.severity, f.rule, f.agent, f.message);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Print the permission matrix, or every setting of the agents when
+    /// `detailed`. JSON always holds the full report
+    pub fn permissions_report(&self, report: &PermissionsReport, detailed: bool) -> Result<()> {
+        let (header, rows) = match detailed {
+            true => (
+                PermissionsReport::DETAIL_HEADER.as_slice(),
+                report.details(),
+            ),
+            false => (PermissionsReport::MATRIX_HEADER.as_slice(), report.matrix()),
+        };
+        match self {
+            Self::Table(_) => {
+                let mut table = Table::new();
+                table
+                    .load_preset(UTF8_FULL)
+                    .apply_modifier(UTF8_ROUND_CORNERS)
+                    .set_content_arrangement(ContentArrangement::Dynamic)
+                    .set_header(header.iter().copied().map(Cell::new).collect::<Vec<_>>());
+                for row in rows {
+                    table.add_row(row);
+                }
+                println!("{table}");
+            }
+            Self::Json => println!(
+                "{}",
+                facet_json::to_string_pretty(report)
+                    .wrap_err("Failed to serialize permissions report to JSON")?
+            ),
+            Self::Plain => {
+                for row in rows {
+                    println!("{}", row.join("\t"));
+                }
+            }
+            Self::Csv => print!("{}", csv(header, &rows)),
+            Self::Markdown => print!("{}", markdown(header, &rows)),
+        }
+        Ok(())
+    }
+
+    pub fn lint_rules(&self, rules: &[Rule]) -> Result<()> {
+        match self {
+            Self::Table(_) => {
+                let mut table = Table::new();
+                table
+                    .load_preset(UTF8_FULL)
+                    .apply_modifier(UTF8_ROUND_CORNERS)
+                    .set_content_arrangement(ContentArrangement::Dynamic)
+                    .set_header(vec![
+                        Cell::new("Rule"),
+                        Cell::new("Severity"),
+                        Cell::new("Description"),
+                    ]);
+                for rule in rules {
+                    table.add_row(vec![
+                        Cell::new(rule.id),
+                        self.severity_cell(rule.severity),
+                        Cell::new(rule.description),
+                    ]);
+                }
+                println!("{table}");
+                Ok(())
+            }
+            Self::Json => {
+                println!(
+                    "{}",
+                    facet_json::to_string_pretty(rules)
+                        .wrap_err("Failed to serialize lint rules to JSON")?
+                );
+                Ok(())
+            }
+            Self::Plain | Self::Csv | Self::Markdown => {
+                for rule in rules {
+                    println!("{} {} {}", rule.id, rule.severity, rule.description);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn decision(&self, decision: Decision) -> colored::ColoredString {
+        let text = decision.to_string();
+        match (decision, self) {
+            (Decision::Allowed, Self::Table(true)) => text.green().bold(),
+            (Decision::Denied, Self::Table(true)) => text.red().bold(),
+            (Decision::Prompted, Self::Table(true)) => text.yellow().bold(),
+            _ => text.normal(),
+        }
+    }
+
+    fn warning_label(&self) -> colored::ColoredString {
+        match self {
+            Self::Table(true) => "warning:".yellow().bold(),
+            _ => "warning:".normal(),
+        }
+    }
+
+    pub fn command_check(&self, check: &CommandCheck) -> Result<()> {
+        match self {
+            Self::Table(_) => {
+                println!("{}: {}", self.decision(check.decision), check.command);
+                if let (Some(pattern), Some(list), Some(source)) =
+                    (&check.pattern, &check.list, &check.source)
+                {
+                    println!("  pattern '{pattern}' in {list} of '{source}'");
+                }
+                if let Some(path) = &check.path {
+                    println!("  {path}");
+                }
+                if let Some(reason) = &check.reason {
+                    println!("  {reason}");
+                }
+                Ok(())
+            }
+            Self::Json => {
+                println!(
+                    "{}",
+                    facet_json::to_string_pretty(check)
+                        .wrap_err("Failed to serialize command check to JSON")?
+                );
+                Ok(())
+            }
+            Self::Plain | Self::Csv | Self::Markdown => {
+                let pattern = check.pattern.as_deref().unwrap_or_default();
+                println!("{} {pattern}", check.decision);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn path_check(&self, check: &PathCheck) -> Result<()> {
+        match self {
+            Self::Table(_) => {
+                println!(
+                    "{}: {} {} ({})",
+                    self.decision(check.decision),
+                    check.tool,
+                    check.path,
+                    check.resolved
+                );
+                match (&check.pattern, &check.list, &check.source) {
+                    (Some(pattern), Some(list), Some(source)) => {
+                        println!("  pattern '{pattern}' in {list} of '{source}'")
+                    }
+                    _ => println!("  no pattern matches"),
+                }
+                if let Some(path) = &check.source_path {
+                    println!("  {path}");
+                }
+                for warning in &check.warnings {
+                    println!("{} {warning}", self.warning_label());
+                }
+                Ok(())
+            }
+            Self::Json => {
+                println!(
+                    "{}",
+                    facet_json::to_string_pretty(check)
+                        .wrap_err("Failed to serialize path check to JSON")?
+                );
+                Ok(())
+            }
+            Self::Plain | Self::Csv | Self::Markdown => {
+                let pattern = check.pattern.as_deref().unwrap_or_default();
+                println!("{} {pattern}", check.decision);
+                for warning in &check.warnings {
+                    eprintln!("warning: {warning}");
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// RFC 4180 field, quoted when it contains a comma, quote or line break
+fn csv_field(value: &str) -> String {
+    match value.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", value.replace('"', "\"\"")),
+        false => value.to_string(),
+    }
+}
+
+fn csv(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut out = String::new();
+    let header: Vec<String> = header.iter().map(|h| csv_field(h)).collect();
+    out.push_str(&header.join(","));
+    out.push('\n');
+    for row in rows {
+        let row: Vec<String> = row.iter().map(|v| csv_field(v)).collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Markdown table cell, `|` escaped and line breaks turned into `<br>`
+fn markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', "<br>")
+}
+
+fn markdown(header: &[&str], rows: &[Vec<String>]) -> String {
+    let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+    let mut out = line(header.iter().map(|h| markdown_cell(h)).collect());
+    out.push_str(&line(header.iter().map(|_| "---".to_string()).collect()));
+    for row in rows {
+        out.push_str(&line(row.iter().map(|v| markdown_cell(v)).collect()));
+    }
+    out
 }
 
 #[cfg(test)]
@@ -328,6 +774,8 @@ mod tests {
         assert_eq!(OutputFormatArg::Table.to_string(), "table");
         assert_eq!(OutputFormatArg::Json.to_string(), "json");
         assert_eq!(OutputFormatArg::Plain.to_string(), "plain");
+        assert_eq!(ReportFormatArg::Csv.to_string(), "csv");
+        assert_eq!(ReportFormatArg::Markdown.to_string(), "markdown");
     }
 
     #[test]
@@ -342,5 +790,25 @@ mod tests {
         assert_eq!(format!("{}", DiffFormatArg::Compact), "compact");
         assert_eq!(format!("{}", DiffFormatArg::Plain), "plain");
         assert_eq!(format!("{}", DiffFormatArg::Agent), "agent");
+        assert_eq!(format!("{}", DiffFormatArg::Json), "json");
+    }
+
+    #[test]
+    fn test_csv_quotes_fields() {
+        let rows = vec![vec!["a".to_string(), "git (push|pull),.*".to_string()]];
+        assert_eq!(
+            csv(&["Agent", "Value"], &rows),
+            "Agent,Value\na,\"git (push|pull),.*\"\n"
+        );
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_markdown_escapes_pipes() {
+        let rows = vec![vec!["a".to_string(), "ls|pwd".to_string()]];
+        assert_eq!(
+            markdown(&["Agent", "Value"], &rows),
+            "| Agent | Value |\n| --- | --- |\n| a | ls\\|pwd |\n"
+        );
     }
 }
@@ -0,0 +1,2 @@
+mod permissions;
+pub use permissions::*;
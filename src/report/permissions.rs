@@ -0,0 +1,353 @@
+use {
+    crate::{
+        Manifest,
+        Result,
+        generator::Generator,
+        kiro::{
+            AwsTool as KiroAwsTool,
+            ExecuteShellTool as KiroShellTool,
+            GlobTool as KiroGlobTool,
+            GrepTool as KiroGrepTool,
+            ReadTool as KiroReadTool,
+            WebFetchTool as KiroWebFetchTool,
+            WriteTool as KiroWriteTool,
+        },
+        sorted,
+    },
+    facet::Facet,
+    std::collections::{BTreeSet, HashSet},
+};
+
+/// Effective settings of one native tool, `forceAllow` applied the way the
+/// generated agent file applies it
+#[derive(Facet, Debug, Clone, Default, PartialEq, Eq)]
+#[facet(rename_all = "camelCase")]
+pub struct ToolPermissions {
+    /// TOML name of the tool, e.g. `shell` or `web-fetch`
+    pub tool: String,
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+    /// `forceAllow` entries, already part of `allow`
+    pub force_allow: Vec<String>,
+    pub deny_by_default: bool,
+    pub auto_allow_readonly: bool,
+}
+
+/// Effective permissions of a merged agent
+#[derive(Facet, Debug, Clone, Default, PartialEq, Eq)]
+#[facet(rename_all = "camelCase")]
+pub struct AgentPermissions {
+    pub agent: String,
+    /// `tools` is `*`, or empty which the generated agent turns into `*`
+    pub wildcard_tools: bool,
+    pub tools: Vec<String>,
+    pub allowed_tools: Vec<String>,
+    /// MCP servers which are not disabled
+    pub mcp_servers: Vec<String>,
+    /// shell, read, write, glob, grep, aws and web-fetch, in that order
+    pub native_tools: Vec<ToolPermissions>,
+}
+
+#[derive(Facet, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PermissionsReport {
+    /// Sorted by agent name
+    pub agents: Vec<AgentPermissions>,
+}
+
+impl ToolPermissions {
+    fn new(tool: &str, allow: &BTreeSet<String>, deny: &BTreeSet<String>) -> Self {
+        Self {
+            tool: tool.to_string(),
+            allow: sorted(allow.iter().cloned()),
+            deny: sorted(deny.iter().cloned()),
+            ..Default::default()
+        }
+    }
+
+    fn forced(mut self, force_allow: &HashSet<String>) -> Self {
+        self.force_allow = sorted(force_allow.iter().cloned());
+        self
+    }
+}
+
+impl From<&Manifest> for AgentPermissions {
+    fn from(agent: &Manifest) -> Self {
+        let native = &agent.native_tools;
+        let shell = KiroShellTool::from(native);
+        let aws = KiroAwsTool::from(native);
+        let read = KiroReadTool::from(native);
+        let write = KiroWriteTool::from(native);
+        let glob = KiroGlobTool::from(native);
+        let grep = KiroGrepTool::from(native);
+        let web_fetch = KiroWebFetchTool::from(native);
+
+        let mut mcp_servers: Vec<String> = agent
+            .mcp_servers
+            .iter()
+            .filter(|(_, mcp)| !mcp.state.as_ref().is_some_and(|s| s.is_disabled()))
+            .map(|(name, _)| name.clone())
+            .collect();
+        mcp_servers.sort();
+
+        Self {
+            agent: agent.name.clone(),
+            wildcard_tools: agent.tools.is_empty() || agent.tools.contains("*"),
+            tools: sorted(agent.tools.iter().cloned()),
+            allowed_tools: sorted(agent.allowed_tools.iter().cloned()),
+            mcp_servers,
+            native_tools: vec![
+                ToolPermissions {
+                    deny_by_default: shell.deny_by_default,
+                    auto_allow_readonly: shell.auto_allow_readonly.unwrap_or(false),
+                    ..ToolPermissions::new("shell", &shell.allowed_commands, &shell.denied_commands)
+                        .forced(&native.shell.force_allow)
+                },
+                ToolPermissions::new("read", &read.allowed_paths, &read.denied_paths)
+                    .forced(&native.read.force_allow),
+                ToolPermissions::new("write", &write.allowed_paths, &write.denied_paths)
+                    .forced(&native.write.force_allow),
+                ToolPermissions {
+                    auto_allow_readonly: glob.allow_read_only,
+                    ..ToolPermissions::new("glob", &glob.allowed_paths, &glob.denied_paths)
+                        .forced(&native.glob.force_allow)
+                },
+                ToolPermissions {
+                    auto_allow_readonly: grep.allow_read_only,
+                    ..ToolPermissions::new("grep", &grep.allowed_paths, &grep.denied_paths)
+                        .forced(&native.grep.force_allow)
+                },
+                ToolPermissions {
+                    auto_allow_readonly: aws.auto_allow_readonly.unwrap_or(false),
+                    ..ToolPermissions::new("aws", &aws.allowed_services, &aws.denied_services)
+                        .forced(&native.aws.force_allow)
+                },
+                ToolPermissions::new("web-fetch", &web_fetch.trusted, &web_fetch.blocked)
+                    .forced(&native.web_fetch.force_allow),
+            ],
+        }
+    }
+}
+
+impl AgentPermissions {
+    /// Tools with `autoAllowReadonly` set
+    pub fn auto_allow_readonly(&self) -> Vec<&str> {
+        self.native_tools
+            .iter()
+            .filter(|t| t.auto_allow_readonly)
+            .map(|t| t.tool.as_str())
+            .collect()
+    }
+
+    /// Every setting as `(capability, setting, value)`, one entry per list
+    /// item, for the per-agent drill-down
+    pub fn details(&self) -> Vec<[String; 3]> {
+        let mut rows = Vec::new();
+        let mut push = |capability: &str, setting: &str, value: &str| {
+            rows.push([
+                capability.to_string(),
+                setting.to_string(),
+                value.to_string(),
+            ]);
+        };
+        for tool in &self.tools {
+            push("tools", "", tool);
+        }
+        if self.tools.is_empty() {
+            push("tools", "", "* (default)");
+        }
+        for tool in &self.allowed_tools {
+            push("allowedTools", "", tool);
+        }
+        for server in &self.mcp_servers {
+            push("mcpServers", "enabled", server);
+        }
+        for tool in &self.native_tools {
+            for value in &tool.allow {
+                let setting = match tool.force_allow.contains(value) {
+                    true => "forceAllow",
+                    false => "allow",
+                };
+                push(&tool.tool, setting, value);
+            }
+            for value in &tool.deny {
+                push(&tool.tool, "deny", value);
+            }
+            if tool.deny_by_default {
+                push(&tool.tool, "denyByDefault", "true");
+            }
+            if tool.auto_allow_readonly {
+                push(&tool.tool, "autoAllowReadonly", "true");
+            }
+        }
+        rows
+    }
+}
+
+impl PermissionsReport {
+    /// Column names of [`PermissionsReport::details`]
+    pub const DETAIL_HEADER: [&str; 4] = ["Agent", "Capability", "Setting", "Value"];
+    /// Column names of [`PermissionsReport::matrix`]
+    pub const MATRIX_HEADER: [&str; 13] = [
+        "Agent",
+        "shell",
+        "read",
+        "write",
+        "glob",
+        "grep",
+        "aws",
+        "web-fetch",
+        "denyByDefault",
+        "autoAllowReadonly",
+        "tools *",
+        "allowedTools",
+        "MCP",
+    ];
+
+    /// Every setting of every agent, one row each
+    pub fn details(&self) -> Vec<Vec<String>> {
+        self.agents
+            .iter()
+            .flat_map(|agent| {
+                agent.details().into_iter().map(|row| {
+                    let mut row = row.to_vec();
+                    row.insert(0, agent.agent.clone());
+                    row
+                })
+            })
+            .collect()
+    }
+
+    /// One row per agent, native tools as `<allow>/<deny>` counts
+    pub fn matrix(&self) -> Vec<Vec<String>> {
+        self.agents
+            .iter()
+            .map(|agent| {
+                let mut row = vec![agent.agent.clone()];
+                row.extend(
+                    agent
+                        .native_tools
+                        .iter()
+                        .map(|t| format!("{}/{}", t.allow.len(), t.deny.len())),
+                );
+                let deny_by_default: Vec<&str> = agent
+                    .native_tools
+                    .iter()
+                    .filter(|t| t.deny_by_default)
+                    .map(|t| t.tool.as_str())
+                    .collect();
+                row.push(deny_by_default.join(","));
+                row.push(agent.auto_allow_readonly().join(","));
+                row.push(match agent.wildcard_tools {
+                    true => "yes".to_string(),
+                    false => String::new(),
+                });
+                row.push(agent.allowed_tools.len().to_string());
+                row.push(agent.mcp_servers.join(","));
+                row
+            })
+            .collect()
+    }
+}
+
+/// Effective permissions of the concrete agents in scope, local agents when
+/// any are defined and all agents otherwise. `agents` narrows the report to
+/// the named agents, which may be templates
+#[tracing::instrument(level = "info", skip(generator))]
+pub fn permissions(generator: &Generator, agents: &[String]) -> Result<PermissionsReport> {
+    if let Some(missing) = agents.iter().find(|a| !generator.agents.contains_key(*a)) {
+        crate::bail!("Agent '{missing}' not found");
+    }
+    let all_agents = !generator.contains_local_agents();
+    let mut report = PermissionsReport {
+        agents: generator
+            .merge()?
+            .iter()
+            .filter(|a| match agents.is_empty() {
+                true => !a.template && (all_agents || generator.is_local(&a.name)),
+                false => agents.contains(&a.name),
+            })
+            .map(AgentPermissions::from)
+            .collect(),
+    };
+    report.agents.sort_by(|a, b| a.agent.cmp(&b.agent));
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{generator::manifest_generator, os::Fs},
+    };
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn test_permissions_report() -> Result<()> {
+        let generator = manifest_generator(
+            Fs::new(),
+            "report",
+            r#"
+            [agents.report-base]
+            template = true
+            nativeTools.shell.deny = ["git push .*", "rm .*"]
+            nativeTools.write.deny = ["./secrets/**"]
+
+            [agents.report-a]
+            inherits = ["report-base"]
+            tools = ["read", "@git"]
+            allowedTools = ["read"]
+            nativeTools.shell.allow = ["git .*"]
+            nativeTools.shell.forceAllow = ["git push .*"]
+            nativeTools.shell.denyByDefault = true
+            nativeTools.grep.autoAllowReadonly = true
+            mcpServers.git.command = "git-mcp"
+            mcpServers.off.command = "off-mcp"
+            mcpServers.off.state = "disabled"
+            "#,
+        )
+        .await?;
+
+        let report = permissions(&generator, &[])?;
+        assert!(report.agents.iter().all(|a| a.agent != "report-base"));
+        let a = report
+            .agents
+            .iter()
+            .find(|a| a.agent == "report-a")
+            .expect("report-a");
+        assert!(!a.wildcard_tools);
+        assert_eq!(a.mcp_servers, vec!["git"]);
+        assert_eq!(a.auto_allow_readonly(), vec!["grep"]);
+
+        let shell = &a.native_tools[0];
+        assert_eq!(shell.tool, "shell");
+        assert_eq!(shell.allow, vec!["git .*", "git push .*"]);
+        assert_eq!(shell.deny, vec!["rm .*"]);
+        assert!(shell.deny_by_default);
+
+        let matrix = report.matrix();
+        let row = matrix
+            .iter()
+            .find(|r| r[0] == "report-a")
+            .expect("report-a row");
+        assert_eq!(row.len(), PermissionsReport::MATRIX_HEADER.len());
+        assert_eq!(&row[1..4], ["2/1", "0/0", "0/1"]);
+        assert_eq!(&row[8..], ["shell", "grep", "", "1", "git"]);
+
+        let details = a.details();
+        assert!(details.contains(&[
+            "shell".to_string(),
+            "forceAllow".to_string(),
+            "git push .*".to_string()
+        ]));
+        assert!(details.contains(&[
+            "write".to_string(),
+            "deny".to_string(),
+            "./secrets/**".to_string()
+        ]));
+
+        let report = permissions(&generator, &["report-base".to_string()])?;
+        assert_eq!(report.agents.len(), 1);
+        assert!(permissions(&generator, &["missing".to_string()]).is_err());
+        Ok(())
+    }
+}
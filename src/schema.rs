@@ -1,5 +1,5 @@
 use {
-    crate::{KgAgentFileDoc, Result, commands::SchemaCommand},
+    crate::{KgAgentFileDoc, KgPrompt, McpValue, Result, commands::SchemaCommand},
     color_eyre::eyre::{Context, eyre},
     facet::Facet,
     facet_json_schema::{AdditionalProperties, JsonSchema, schema_for},
@@ -112,6 +112,49 @@ fn build_manifest_schema() -> Result<JsonSchema> {
     Ok(manifest)
 }
 
+/// JsonSchema renders `#[facet(untagged)]` enums as externally tagged
+/// `{ "Variant": <schema> }` objects. Replace them with the variant schemas.
+fn untag<'a, T: Facet<'a>>(schema: &mut JsonSchema) {
+    let title = T::SHAPE.type_identifier;
+    if schema.title.as_deref() == Some(title)
+        && let Some(variants) = schema.one_of.as_mut()
+    {
+        for variant in variants.iter_mut() {
+            if let Some(inner) = variant
+                .properties
+                .as_mut()
+                .and_then(|p| p.values_mut().next())
+            {
+                let mut inner = inner.clone();
+                inner.title = None;
+                *variant = inner;
+            }
+        }
+    }
+    for child in schema
+        .properties
+        .iter_mut()
+        .chain(schema.defs.iter_mut())
+        .flat_map(|p| p.values_mut())
+    {
+        untag::<T>(child);
+    }
+    if let Some(AdditionalProperties::Schema(child)) = schema.additional_properties.as_mut() {
+        untag::<T>(child);
+    }
+    if let Some(items) = schema.items.as_mut() {
+        untag::<T>(items);
+    }
+    for child in schema
+        .any_of
+        .iter_mut()
+        .chain(schema.one_of.iter_mut())
+        .flatten()
+    {
+        untag::<T>(child);
+    }
+}
+
 #[tracing::instrument(skip(cmd), fields(command = %cmd))]
 pub(crate) fn build_schema(cmd: &SchemaCommand) -> Result<String> {
     let mut schema = match cmd {
@@ -120,6 +163,8 @@ pub(crate) fn build_schema(cmd: &SchemaCommand) -> Result<String> {
         SchemaCommand::KiroAgent => return Ok(KIRO_OUTPUT_SCHEMA.to_string()),
     };
     schema.schema = Some(JSON_SCHEMA_DIALECT.into());
+    untag::<McpValue>(&mut schema);
+    untag::<KgPrompt>(&mut schema);
     let schema_str = facet_json::to_string_pretty(&schema)
         .wrap_err_with(|| format!("unable to generate schema for {cmd}"))?;
     tracing::debug!(command = %cmd, "schema built");
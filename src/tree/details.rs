@@ -1,7 +1,13 @@
 use {
-    crate::{AgentSourceSlots, Manifest, generator::Generator},
+    crate::{
+        AgentSourceSlots,
+        Manifest,
+        generator::{Generator, InheritanceConflict},
+        is_removed,
+        removed_entry,
+    },
     facet::Facet,
-    std::collections::{BTreeMap, BTreeSet},
+    std::collections::{BTreeMap, BTreeSet, HashMap},
 };
 
 #[derive(Facet)]
@@ -37,9 +43,23 @@ pub struct TreeDetail {
     pub template: bool,
     pub output: String,
     pub description: String,
-    pub inherits: BTreeSet<String>,
+    /// Direct parents in the order listed, later parents win
+    pub inherits: Vec<String>,
+    /// Linearized ancestors, from base to most derived
     pub resolved_ancestors: Vec<String>,
+    /// Scalar fields set differently by two parents
+    pub conflicts: Vec<InheritanceConflict>,
     pub sources: BTreeSet<TreeSource>,
+    /// Inherited entries dropped with `!entry` or `!*`
+    pub removed: Vec<TreeRemoval>,
+}
+
+#[derive(Facet, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TreeRemoval {
+    pub field: String,
+    pub entry: String,
+    /// Ancestor which declared the removed entry
+    pub ancestor: String,
 }
 
 fn collect_sources(slots: &AgentSourceSlots) -> BTreeSet<TreeSource> {
@@ -61,6 +81,48 @@ fn collect_sources(slots: &AgentSourceSlots) -> BTreeSet<TreeSource> {
     .collect()
 }
 
+fn add_markers(manifest: &Manifest, markers: &mut HashMap<String, Vec<String>>) {
+    for (field, entries) in manifest.removable_fields() {
+        markers.entry(field).or_default().extend(
+            entries
+                .into_iter()
+                .filter(|e| removed_entry(e).is_some())
+                .cloned(),
+        );
+    }
+}
+
+/// Replay the merge order, most derived ancestor first, and record the entries
+/// blocked by removal markers collected so far
+fn collect_removals(generator: &Generator, agent: &AgentSourceSlots) -> Vec<TreeRemoval> {
+    let mut markers: HashMap<String, Vec<String>> = HashMap::new();
+    add_markers(&agent.merged, &mut markers);
+
+    let mut removed = Vec::new();
+    for ancestor in generator.inheritance_chain_safe(&agent.name).iter().rev() {
+        let Some(parent) = generator.agents.get(ancestor) else {
+            continue;
+        };
+        for (field, entries) in parent.merged.removable_fields() {
+            let Some(field_markers) = markers.get(&field) else {
+                continue;
+            };
+            for entry in entries {
+                if is_removed(field_markers.iter(), entry) {
+                    removed.push(TreeRemoval {
+                        field: field.clone(),
+                        entry: entry.clone(),
+                        ancestor: ancestor.clone(),
+                    });
+                }
+            }
+        }
+        add_markers(&parent.merged, &mut markers);
+    }
+    removed.sort();
+    removed
+}
+
 pub fn details(generator: &Generator, names: &[String]) -> BTreeMap<String, TreeDetail> {
     let mut out: BTreeMap<String, TreeDetail> = BTreeMap::new();
     for name in names {
@@ -77,14 +139,23 @@ pub fn details(generator: &Generator, names: &[String]) -> BTreeMap<String, Tree
             };
             let resolved_ancestors = generator.inheritance_chain_safe(name);
             let sources = collect_sources(agent);
+            let removed = collect_removals(generator, agent);
+            let conflicts = generator
+                .inheritance_conflicts(name)
+                .unwrap_or_else(|err| {
+                    tracing::warn!(agent = %name, error = %err, "failed to check inheritance conflicts");
+                    Vec::new()
+                });
 
             out.insert(name.clone(), TreeDetail {
                 template: manifest.template,
                 output,
                 description: manifest.description.clone().unwrap_or_default(),
-                inherits: manifest.inherits.iter().cloned().collect(),
+                inherits: manifest.inherits.clone(),
                 resolved_ancestors,
+                conflicts,
                 sources,
+                removed,
             });
         }
     }
@@ -189,6 +260,9 @@ fn manifest_fields(manifest: &Manifest) -> Vec<String> {
     if manifest.subagents != Default::default() {
         fields.push("subagents".to_string());
     }
+    for k in manifest.vars.keys() {
+        fields.push(format!("vars.{k}"));
+    }
     fields
 }
 
@@ -204,11 +278,52 @@ mod tests {
         let child = result.get("child").expect("child should exist");
         assert!(!child.template);
         assert_eq!(child.resolved_ancestors, vec![String::from("parent")]);
-        assert_eq!(child.inherits, BTreeSet::from(["parent".into()]));
+        assert_eq!(child.inherits, vec!["parent"]);
         assert!(!child.sources.is_empty());
         Ok(())
     }
 
+    #[tokio::test]
+    #[test_log::test]
+    async fn details_reports_removed_ancestor_entries() -> crate::Result<()> {
+        let mut generator = super::super::fixture_generator()?;
+        let child = generator
+            .agents
+            .get_mut("child")
+            .expect("child should exist");
+        child.merged.tools.insert("!web_search".into());
+        child
+            .merged
+            .native_tools
+            .shell
+            .allows
+            .insert("!git pull ,*".into());
+        let result = details(&generator, &["child".into()]);
+        let child = result.get("child").expect("child should exist");
+        assert_eq!(child.removed, vec![
+            TreeRemoval {
+                field: "nativeTools.shell.allow".into(),
+                entry: "git pull ,*".into(),
+                ancestor: "parent".into(),
+            },
+            TreeRemoval {
+                field: "tools".into(),
+                entry: "web_search".into(),
+                ancestor: "parent".into(),
+            },
+        ]);
+
+        let merged = generator.merge()?;
+        let child = merged
+            .iter()
+            .find(|a| a.name == "child")
+            .expect("child should exist");
+        assert!(!child.tools.contains("web_search"));
+        assert!(!child.tools.iter().any(|t| t.starts_with('!')));
+        assert!(!child.native_tools.shell.allows.contains("git pull ,*"));
+        Ok(())
+    }
+
     #[tokio::test]
     #[test_log::test]
     async fn details_skips_unknown_agent() -> crate::Result<()> {
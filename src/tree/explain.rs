@@ -0,0 +1,421 @@
+use {
+    crate::{Manifest, Result, SourceSlot, generator::Generator, is_removed, removed_entry},
+    color_eyre::eyre::bail,
+    facet::Facet,
+    std::{
+        collections::{HashMap, HashSet},
+        fmt::Display,
+    },
+};
+
+/// What happened to a value while merging the agent
+#[derive(Facet, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub enum Provenance {
+    /// Part of the final value
+    #[facet(rename = "added")]
+    Added,
+    /// Dropped by a `!entry` or `!*` marker
+    #[facet(rename = "removed")]
+    Removed,
+    /// Added to `allow` by `forceAllow`
+    #[facet(rename = "forced")]
+    Forced,
+    /// Dropped from `deny` because it is in `forceAllow`
+    #[facet(rename = "overridden")]
+    Overridden,
+    /// Declared again by a lower precedence source and merged into the
+    /// added entry
+    #[facet(rename = "merged")]
+    Merged,
+    /// Replaced by a higher precedence source
+    #[facet(rename = "shadowed")]
+    Shadowed,
+}
+
+impl Display for Provenance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::Forced => "forced",
+            Self::Overridden => "overridden",
+            Self::Merged => "merged",
+            Self::Shadowed => "shadowed",
+        })
+    }
+}
+
+#[derive(Facet, Clone, Debug, PartialEq, Eq)]
+pub struct ExplainEntry {
+    pub value: String,
+    pub status: Provenance,
+    /// Agent or ancestor whose configuration declares the value
+    pub agent: String,
+    pub source_type: String,
+    pub path: String,
+    /// Agent whose `!entry`, `forceAllow` or higher precedence value changed
+    /// this one
+    pub by: Option<String>,
+}
+
+#[derive(Facet, Clone, Debug)]
+pub struct ExplainReport {
+    pub agent: String,
+    pub field: String,
+    pub resolved_ancestors: Vec<String>,
+    pub entries: Vec<ExplainEntry>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    /// Set or list supporting `!entry` removal
+    List,
+    /// Map keys, the first source declaring a key wins
+    Keys,
+    /// Map keys where the last source declaring a key wins
+    KeysParentWins,
+    Scalar,
+}
+
+/// A source slot of the agent or one of its ancestors, in merge order
+struct Contributor<'a> {
+    agent: &'a str,
+    slot: &'a SourceSlot,
+}
+
+impl Contributor<'_> {
+    fn entry(&self, value: &str, status: Provenance, by: Option<&str>) -> ExplainEntry {
+        ExplainEntry {
+            value: value.to_string(),
+            status,
+            agent: self.agent.to_string(),
+            source_type: self.slot.source_type().unwrap_or_default(),
+            path: self
+                .slot
+                .location()
+                .unwrap_or_default()
+                .display()
+                .to_string(),
+            by: by.map(str::to_string),
+        }
+    }
+}
+
+fn is_list_field(field: &str) -> bool {
+    matches!(
+        field,
+        "tools" | "allowedTools" | "subagents.allow" | "subagents.deny" | "subagents.trusted"
+    ) || Manifest::default()
+        .native_tools
+        .lists()
+        .iter()
+        .any(|((tool, list), _)| field == format!("nativeTools.{tool}.{list}"))
+        || field.strip_prefix("mcpServers.").is_some_and(|f| {
+            [".args", ".autoApprove", ".disabledTools"]
+                .iter()
+                .filter_map(|list| f.strip_suffix(list))
+                .any(|name| !name.is_empty())
+        })
+}
+
+fn keys<V>(map: &HashMap<String, V>) -> Vec<String> {
+    let mut keys: Vec<String> = map.keys().cloned().collect();
+    keys.sort();
+    keys
+}
+
+/// Values `manifest` declares for `field`, `None` when the field is unknown
+fn field_values(manifest: &Manifest, field: &str) -> Option<(FieldKind, Vec<String>)> {
+    let scalar = |v: &Option<String>| Some((FieldKind::Scalar, v.iter().cloned().collect()));
+    match field {
+        "description" => scalar(&manifest.description),
+        "prompt" => scalar(
+            &manifest
+                .prompt
+                .as_ref()
+                .and_then(|p| p.text())
+                .map(String::from),
+        ),
+        "model" => scalar(&manifest.model),
+        "keyboardShortcut" => scalar(&manifest.keyboard_shortcut),
+        "welcomeMessage" => scalar(&manifest.welcome_message),
+        "useLegacyMcpJson" => scalar(&manifest.include_mcp_json.map(|b| b.to_string())),
+        "resources" => Some((FieldKind::Keys, keys(&manifest.resources))),
+        "skills" => Some((FieldKind::Keys, keys(&manifest.skills))),
+        "knowledge" => Some((FieldKind::Keys, keys(&manifest.knowledge))),
+        "mcpServers" => Some((FieldKind::Keys, keys(&manifest.mcp_servers))),
+        "hooks" => Some((FieldKind::Keys, keys(&manifest.hooks))),
+        "vars" => Some((FieldKind::Keys, keys(&manifest.vars))),
+        "toolAliases" => Some((FieldKind::KeysParentWins, keys(&manifest.tool_aliases))),
+        "toolSettings" => Some((FieldKind::KeysParentWins, keys(&manifest.tool_settings))),
+        _ => {
+            if let Some(trigger) = field.strip_prefix("hooks.") {
+                let hooks = manifest.hooks.get(trigger).map(keys).unwrap_or_default();
+                return Some((FieldKind::Keys, hooks));
+            }
+            if !is_list_field(field) {
+                return None;
+            }
+            let values = manifest
+                .removable_fields()
+                .remove(field)
+                .map(|v| v.into_iter().cloned().collect())
+                .unwrap_or_default();
+            Some((FieldKind::List, values))
+        }
+    }
+}
+
+fn values(contributor: &Contributor<'_>, field: &str) -> Vec<String> {
+    field_values(&contributor.slot.manifest, field)
+        .map(|(_, v)| v)
+        .unwrap_or_default()
+}
+
+fn explain_list(contributors: &[Contributor<'_>], field: &str) -> Vec<ExplainEntry> {
+    let mut entries = Vec::new();
+    let mut markers: Vec<(String, &str)> = Vec::new();
+    let mut seen = HashSet::new();
+    for contributor in contributors {
+        let mut own_markers = Vec::new();
+        for value in values(contributor, field) {
+            if removed_entry(&value).is_some() {
+                own_markers.push((value, contributor.agent));
+                continue;
+            }
+            match markers
+                .iter()
+                .find(|(m, _)| is_removed(std::iter::once(m), &value))
+            {
+                Some((_, by)) => {
+                    entries.push(contributor.entry(&value, Provenance::Removed, Some(by)))
+                }
+                None if seen.insert(value.clone()) => {
+                    entries.push(contributor.entry(&value, Provenance::Added, None))
+                }
+                None => {}
+            }
+        }
+        markers.extend(own_markers);
+    }
+    entries
+}
+
+fn explain_keys(
+    contributors: &[Contributor<'_>],
+    field: &str,
+    kind: FieldKind,
+) -> Vec<ExplainEntry> {
+    let ordered: Vec<&Contributor<'_>> = match kind {
+        FieldKind::KeysParentWins => contributors.iter().rev().collect(),
+        _ => contributors.iter().collect(),
+    };
+    let status = match kind {
+        FieldKind::Keys => Provenance::Merged,
+        _ => Provenance::Shadowed,
+    };
+    let mut winners: HashMap<String, &str> = HashMap::new();
+    let mut entries = Vec::new();
+    for contributor in ordered {
+        for value in values(contributor, field) {
+            // Scalars have a single value, whichever source sets it first wins
+            let key = if kind == FieldKind::Scalar {
+                String::new()
+            } else {
+                value.clone()
+            };
+            match winners.get(&key) {
+                Some(by) => entries.push(contributor.entry(&value, status, Some(by))),
+                None => {
+                    winners.insert(key, contributor.agent);
+                    entries.push(contributor.entry(&value, Provenance::Added, None));
+                }
+            }
+        }
+    }
+    entries
+}
+
+/// Apply `forceAllow` to an `allow` or `deny` list the way the Kiro tool
+/// conversion does. `aws` ignores `forceAllow`.
+fn apply_force_allow(
+    contributors: &[Contributor<'_>],
+    field: &str,
+    entries: &mut Vec<ExplainEntry>,
+) {
+    let Some(tool_list) = field.strip_prefix("nativeTools.") else {
+        return;
+    };
+    let Some((tool, list)) = tool_list.split_once('.') else {
+        return;
+    };
+    if tool == "aws" || !matches!(list, "allow" | "deny") {
+        return;
+    }
+    let forced: Vec<ExplainEntry> =
+        explain_list(contributors, &format!("nativeTools.{tool}.forceAllow"))
+            .into_iter()
+            .filter(|e| e.status == Provenance::Added)
+            .collect();
+    for force in forced {
+        let existing = entries
+            .iter_mut()
+            .find(|e| e.value == force.value && e.status == Provenance::Added);
+        match (list, existing) {
+            ("deny", Some(entry)) => {
+                entry.status = Provenance::Overridden;
+                entry.by = Some(force.agent);
+            }
+            ("allow", None) => entries.push(ExplainEntry {
+                status: Provenance::Forced,
+                ..force
+            }),
+            _ => {}
+        }
+    }
+}
+
+/// Trace every value of `field` in the merged `agent` back to the source slot
+/// and ancestor which declared it
+pub fn explain(generator: &Generator, agent: &str, field: &str) -> Result<ExplainReport> {
+    let Some((kind, _)) = field_values(&Manifest::default(), field) else {
+        bail!(
+            "Unknown field '{field}'. Use a TOML path such as tools, nativeTools.shell.allow, \
+             mcpServers.<name>.args, mcpServers.<name>.autoApprove, mcpServers or description"
+        );
+    };
+    if !generator.agents.contains_key(agent) {
+        bail!("Agent '{agent}' not found");
+    }
+
+    let resolved_ancestors = generator.inheritance_chain(agent)?;
+    let mut contributors = Vec::new();
+    for name in std::iter::once(agent).chain(resolved_ancestors.iter().rev().map(String::as_str)) {
+        let Some(slots) = generator.agents.get(name) else {
+            continue;
+        };
+        // Same precedence as discover::merge_manifests
+        for slot in [
+            &slots.local_agent_file,
+            &slots.local_manifest,
+            &slots.global_agent_file,
+            &slots.global_manifest,
+        ] {
+            if slot.path.is_some() {
+                contributors.push(Contributor { agent: name, slot });
+            }
+        }
+    }
+
+    let entries = match kind {
+        FieldKind::List => {
+            let mut entries = explain_list(&contributors, field);
+            apply_force_allow(&contributors, field, &mut entries);
+            entries
+        }
+        _ => explain_keys(&contributors, field, kind),
+    };
+
+    Ok(ExplainReport {
+        agent: agent.to_string(),
+        field: field.to_string(),
+        resolved_ancestors,
+        entries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statuses(report: &ExplainReport, status: Provenance) -> Vec<(&str, &str)> {
+        report
+            .entries
+            .iter()
+            .filter(|e| e.status == status)
+            .map(|e| (e.value.as_str(), e.agent.as_str()))
+            .collect()
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn explain_traces_list_entries_to_ancestors() -> Result<()> {
+        let mut generator = super::super::fixture_generator()?;
+        let child = generator
+            .agents
+            .get_mut("child")
+            .expect("child should exist");
+        child
+            .local_manifest
+            .manifest
+            .tools
+            .insert("!web_search".into());
+
+        let report = explain(&generator, "child", "tools")?;
+        assert_eq!(report.resolved_ancestors, vec![String::from("parent")]);
+        let mut added = statuses(&report, Provenance::Added);
+        added.sort();
+        assert_eq!(added, vec![("@awsdocs", "child"), ("shell", "child")]);
+        assert_eq!(statuses(&report, Provenance::Removed), vec![(
+            "web_search",
+            "parent"
+        )]);
+        assert!(report.entries.iter().all(|e| e.path == "test"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn explain_reports_force_allow_overrides() -> Result<()> {
+        let mut generator = super::super::fixture_generator()?;
+        let child = generator
+            .agents
+            .get_mut("child")
+            .expect("child should exist");
+        let shell = &mut child.local_manifest.manifest.native_tools.shell;
+        shell.force_allow.insert("rm -rf target".into());
+        shell.denies.insert("rm -rf target".into());
+
+        let allow = explain(&generator, "child", "nativeTools.shell.allow")?;
+        assert!(
+            allow
+                .entries
+                .iter()
+                .any(|e| e.value == "rm -rf target" && e.status == Provenance::Forced)
+        );
+        let deny = explain(&generator, "child", "nativeTools.shell.deny")?;
+        let overridden = deny
+            .entries
+            .iter()
+            .find(|e| e.value == "rm -rf target")
+            .expect("deny entry should be reported");
+        assert_eq!(overridden.status, Provenance::Overridden);
+        assert_eq!(overridden.by.as_deref(), Some("child"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn explain_scalar_child_shadows_parent() -> Result<()> {
+        let generator = super::super::fixture_generator()?;
+        let report = explain(&generator, "child", "description")?;
+        assert_eq!(statuses(&report, Provenance::Added), vec![(
+            "I am a child",
+            "child"
+        )]);
+        assert_eq!(report.entries.len(), 2);
+        assert_eq!(report.entries[1].status, Provenance::Shadowed);
+        assert_eq!(report.entries[1].by.as_deref(), Some("child"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[test_log::test]
+    async fn explain_rejects_unknown_field() -> Result<()> {
+        let generator = super::super::fixture_generator()?;
+        assert!(explain(&generator, "child", "nativeTools.shell.nope").is_err());
+        assert!(explain(&generator, "missing", "tools").is_err());
+        Ok(())
+    }
+}
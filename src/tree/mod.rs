@@ -1,8 +1,9 @@
 mod dependents;
 mod details;
+mod explain;
 mod search;
 mod summary;
-pub use {dependents::*, details::*, search::*, summary::*};
+pub use {dependents::*, details::*, explain::*, search::*, summary::*};
 
 #[cfg(test)]
 use crate::{Fs, Generator, GeneratorConfig, KgAgentSource, toml_parse};
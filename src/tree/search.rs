@@ -7,7 +7,7 @@ use {
         tree::SummaryEntry,
     },
     facet::Facet,
-    std::collections::{BTreeMap, BTreeSet},
+    std::collections::BTreeMap,
 };
 
 #[derive(Facet, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -21,7 +21,7 @@ pub struct SearchHit {
     #[facet(rename = "match")]
     matches: MatchFields,
     description: String,
-    inherits: BTreeSet<String>,
+    inherits: Vec<String>,
 }
 
 impl SearchHit {
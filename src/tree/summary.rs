@@ -1,10 +1,7 @@
 use {
     crate::{AgentSourceSlots, generator::Generator},
     facet::Facet,
-    std::{
-        collections::{BTreeMap, BTreeSet},
-        fmt::Display,
-    },
+    std::{collections::BTreeMap, fmt::Display},
 };
 
 #[derive(Clone, Debug, Facet, Default, PartialEq, Eq)]
@@ -12,13 +9,13 @@ pub struct SummaryEntry {
     #[facet(skip)]
     pub name: String,
     pub description: String,
-    pub inherits: BTreeSet<String>,
+    pub inherits: Vec<String>,
     pub locations: Vec<String>,
 }
 
 impl SummaryEntry {
     pub fn inherits_join(&self) -> crate::Result<String> {
-        Ok(self.inherits.iter().cloned().collect::<Vec<_>>().join(","))
+        Ok(self.inherits.join(","))
     }
 }
 
@@ -38,7 +35,7 @@ impl From<&AgentSourceSlots> for SummaryEntry {
         Self {
             name: slots.name.clone(),
             description: slots.merged.description.clone().unwrap_or_default(),
-            inherits: slots.merged.inherits.iter().cloned().collect(),
+            inherits: slots.merged.inherits.clone(),
             locations: slots.locations().iter().map(|p| p.to_string()).collect(),
         }
     }